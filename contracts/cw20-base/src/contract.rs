@@ -18,10 +18,10 @@ use crate::allowances::{
 };
 use crate::enumerable::{query_all_accounts, query_owner_allowances, query_spender_allowances};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, TotalSupplyResponse};
 use crate::state::{
     MinterData, TokenInfo, ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, LOGO, MARKETING_INFO,
-    TOKEN_INFO,
+    TOKEN_INFO, TOTAL_SUPPLY,
 };
 
 // version info for migration info
@@ -94,7 +94,7 @@ fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -102,7 +102,7 @@ pub fn instantiate(
     // check valid token info
     msg.validate()?;
     // create initial accounts
-    let total_supply = create_accounts(&mut deps, &msg.initial_balances)?;
+    let total_supply = create_accounts(&mut deps, env.block.height, &msg.initial_balances)?;
 
     if let Some(limit) = msg.get_cap() {
         if total_supply > limit {
@@ -127,6 +127,7 @@ pub fn instantiate(
         mint,
     };
     TOKEN_INFO.save(deps.storage, &data)?;
+    TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
 
     if let Some(marketing) = msg.marketing {
         let logo = if let Some(logo) = marketing.logo {
@@ -158,6 +159,7 @@ pub fn instantiate(
 
 pub fn create_accounts(
     deps: &mut DepsMut,
+    height: u64,
     accounts: &[Cw20Coin],
 ) -> Result<Uint128, ContractError> {
     validate_accounts(accounts)?;
@@ -165,7 +167,7 @@ pub fn create_accounts(
     let mut total_supply = Uint128::zero();
     for row in accounts {
         let address = deps.api.addr_validate(&row.address)?;
-        BALANCES.save(deps.storage, &address, &row.amount)?;
+        BALANCES.save(deps.storage, &address, &row.amount, height)?;
         total_supply += row.amount;
     }
 
@@ -238,7 +240,7 @@ pub fn execute(
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -252,6 +254,7 @@ pub fn execute_transfer(
     BALANCES.update(
         deps.storage,
         &info.sender,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> {
             Ok(balance.unwrap_or_default().checked_sub(amount)?)
         },
@@ -259,6 +262,7 @@ pub fn execute_transfer(
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -272,7 +276,7 @@ pub fn execute_transfer(
 
 pub fn execute_burn(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
@@ -284,15 +288,17 @@ pub fn execute_burn(
     BALANCES.update(
         deps.storage,
         &info.sender,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> {
             Ok(balance.unwrap_or_default().checked_sub(amount)?)
         },
     )?;
     // reduce total_supply
-    TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
+    let token_info = TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
         info.total_supply = info.total_supply.checked_sub(amount)?;
         Ok(info)
     })?;
+    TOTAL_SUPPLY.save(deps.storage, &token_info.total_supply, env.block.height)?;
 
     let res = Response::new()
         .add_attribute("action", "burn")
@@ -303,7 +309,7 @@ pub fn execute_burn(
 
 pub fn execute_mint(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -334,12 +340,14 @@ pub fn execute_mint(
         }
     }
     TOKEN_INFO.save(deps.storage, &config)?;
+    TOTAL_SUPPLY.save(deps.storage, &config.total_supply, env.block.height)?;
 
     // add amount to recipient balance
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -352,7 +360,7 @@ pub fn execute_mint(
 
 pub fn execute_send(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
     amount: Uint128,
@@ -368,6 +376,7 @@ pub fn execute_send(
     BALANCES.update(
         deps.storage,
         &info.sender,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> {
             Ok(balance.unwrap_or_default().checked_sub(amount)?)
         },
@@ -375,6 +384,7 @@ pub fn execute_send(
     BALANCES.update(
         deps.storage,
         &rcpt_addr,
+        env.block.height,
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
@@ -524,6 +534,12 @@ pub fn execute_upload_logo(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::BalanceAtHeight { address, height } => {
+            to_binary(&query_balance_at_height(deps, address, height)?)
+        }
+        QueryMsg::TotalSupplyAtHeight { height } => {
+            to_binary(&query_total_supply_at_height(deps, height)?)
+        }
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
         QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
         QueryMsg::Allowance { owner, spender } => {
@@ -560,6 +576,25 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
     Ok(BalanceResponse { balance })
 }
 
+pub fn query_balance_at_height(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_total_supply_at_height(deps: Deps, height: u64) -> StdResult<TotalSupplyResponse> {
+    let total_supply = TOTAL_SUPPLY
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    Ok(TotalSupplyResponse { total_supply })
+}
+
 pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
     let res = TokenInfoResponse {
@@ -603,7 +638,7 @@ pub fn query_download_logo(deps: Deps) -> StdResult<DownloadLogoResponse> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let original_version =
         ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -616,6 +651,11 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             ALLOWANCES_SPENDER.save(deps.storage, (&spender, &owner), &allowance)?;
         }
     }
+    // supply history starts at the migration, balances are read from the unchanged namespace
+    if TOTAL_SUPPLY.may_load(deps.storage)?.is_none() {
+        let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+        TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
+    }
     Ok(Response::default())
 }
 
@@ -1269,6 +1309,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn snapshots() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let addr1 = String::from("addr0001");
+        let addr2 = String::from("addr0002");
+        let minter = String::from("minter");
+        let amount1 = Uint128::from(12340000u128);
+        let height = mock_env().block.height;
+
+        do_instantiate_with_minter(deps.as_mut(), &addr1, amount1, &minter, None);
+
+        // transfer and mint in the next block
+        let mut env = mock_env();
+        env.block.height = height + 1;
+        let msg = ExecuteMsg::Transfer {
+            recipient: addr2.clone(),
+            amount: Uint128::new(3),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(addr1.as_ref(), &[]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Mint {
+            recipient: addr2.clone(),
+            amount: Uint128::new(10),
+        };
+        execute(deps.as_mut(), env, mock_info(minter.as_ref(), &[]), msg).unwrap();
+
+        // burn in the one after
+        let mut env = mock_env();
+        env.block.height = height + 2;
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1),
+        };
+        execute(deps.as_mut(), env, mock_info(addr1.as_ref(), &[]), msg).unwrap();
+
+        // snapshots are taken at the beginning of a block
+        let balance_at = |address: &str, height: u64| {
+            query_balance_at_height(deps.as_ref(), address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        assert_eq!(balance_at(&addr1, height), Uint128::zero());
+        assert_eq!(balance_at(&addr1, height + 1), amount1);
+        assert_eq!(balance_at(&addr1, height + 2), amount1 - Uint128::new(3));
+        assert_eq!(balance_at(&addr1, height + 3), amount1 - Uint128::new(4));
+        assert_eq!(balance_at(&addr2, height + 1), Uint128::zero());
+        assert_eq!(balance_at(&addr2, height + 2), Uint128::new(13));
+        assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(13));
+
+        let supply_at = |height: u64| {
+            let data = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TotalSupplyAtHeight { height },
+            )
+            .unwrap();
+            from_binary::<TotalSupplyResponse>(&data)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(supply_at(height), Uint128::zero());
+        assert_eq!(supply_at(height + 1), amount1);
+        assert_eq!(supply_at(height + 2), amount1 + Uint128::new(10));
+        assert_eq!(supply_at(height + 3), amount1 + Uint128::new(9));
+        assert_eq!(
+            query_token_info(deps.as_ref()).unwrap().total_supply,
+            amount1 + Uint128::new(9)
+        );
+    }
+
     #[test]
     fn send() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
@@ -76,6 +76,13 @@ pub enum QueryMsg {
     /// Returns the current balance of the given address, 0 if unset.
     #[returns(cw20::BalanceResponse)]
     Balance { address: String },
+    /// Returns the balance of the given address at the beginning of the block at `height`,
+    /// 0 if unset.
+    #[returns(cw20::BalanceResponse)]
+    BalanceAtHeight { address: String, height: u64 },
+    /// Returns the total supply at the beginning of the block at `height`.
+    #[returns(TotalSupplyResponse)]
+    TotalSupplyAtHeight { height: u64 },
     /// Returns metadata on the contract - name, decimals, supply, etc.
     #[returns(cw20::TokenInfoResponse)]
     TokenInfo {},
@@ -122,6 +129,11 @@ pub enum QueryMsg {
     DownloadLogo {},
 }
 
+#[cw_serde]
+pub struct TotalSupplyResponse {
+    pub total_supply: Uint128,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct MigrateMsg {}
 
@@ -1,21 +1,32 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, WasmMsg, Reply, StdError, Empty, Coin, coin, Uint128, BankMsg, attr, Addr, CosmosMsg,
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, WasmMsg, Reply, StdError, Empty, Coin, coin, Uint128, BankMsg, attr, Addr, CosmosMsg, Decimal, Order, Storage, SubMsgResult, Timestamp,
 };
 
-use cw2::{set_contract_version};
+use cw2::{get_contract_version, set_contract_version, ContractVersion};
 use cw20::{MinterResponse, Cw20ExecuteMsg};
+use etf_interface::events::{TokenSwapped, SHARES_OUT, TOKENS_IN, TOKENS_OUT};
+use etf_interface::pool::PoolInfo;
+use etf_interface::share_token;
+use etf_interface::swap::{self as swap_msg, Dex, DexPriceResponse};
 
 use cw_utils::{parse_reply_instantiate_data};
+use cw_storage_plus::Bound;
 
-use osmo_swap;
-use cw20_base;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GetTokensResponse, InstantiateMsg, QueryMsg, EtfSwapRoutes, GetInitialSwapResponse, GetBalanceResponse};
-use crate::state::{BALANCE, INITIAL_DEPOSIT_CACHE, LEDGER, ETF_CACHE, EtfCache, SWAP_CONTRACT, INITIAL_SWAP, MINT_CONTRACTS, MINT_CACHE, MintCache, MANAGER_CONTRACT, ETF_POOLS, REVERT_SWAP_CACHE, SwapCache, ETF_NAME_CACHE, EtfNameCache};
-use osmosis_std::types::osmosis::gamm::v1beta1::{SwapAmountInRoute, QueryPoolResponse, Pool};
-use prost::DecodeError;
+use crate::ibc_hooks::{bech32_prefix, derive_intermediate_sender};
+use crate::migrations;
+use crate::msg::{ExecuteMsg, GetTokensResponse, InstantiateMsg, QueryMsg, EtfSwapRoutes, GetInitialSwapResponse, GetBalanceResponse, MigrateMsg, GetPauseStateResponse, DowntimeBreaker, EtfLimits, EtfLimitsResponse, GetShareTokenResponse, Route, GetNavResponse, BatchConfig, BatchStateResponse, BatchClaim, DcaPlan, DcaPlansResponse, NavOrder, NavOrderKind, NavOrdersResponse, NavPerShareResponse, DonationSettings, Donation, DonationResponse, DonationBeneficiariesResponse, DonationTotalsResponse, GovConfig, GovConfigResponse, EtfAdminMsg, VoteOption, ProposalStatus, Proposal, ProposalResponse, ProposalsResponse, Ballot, VoteResponse, VotingPowerResponse, IndexMethodology, IndexWeights, IndexResponse, Weighting, PairRouteResponse, IbcSender, IbcRefund, IbcRefundResponse, SudoMsg, IbcLifecycleComplete, OutflowLimit, Outflow, QueuedRedemption, OutflowCapacityResponse, WindDown, WindDownResponse, WindDownClaimResponse};
+use crate::state::{BALANCE, INITIAL_DEPOSIT_CACHE, LEDGER, ETF_CACHE, EtfCache, SWAP_CONTRACT, INITIAL_SWAP, MINT_CONTRACTS, MINT_CACHE, MintCache, MANAGER_CONTRACT, ETF_POOLS, REVERT_SWAP_CACHE, SwapCache, ETF_NAME_CACHE, EtfNameCache, OWNER, PAUSED, PauseState, DOWNTIME_BREAKER, ETF_LIMITS, ETF_DEPOSITS, NATIVE_DENOMS, EXACT_OUT_CACHE, ExactOutCache, BATCH_CONFIGS, BATCH_DEPOSITS, BATCH_REDEMPTIONS, BATCH_CLAIMS, BATCH_CACHE, BatchCache, DCA_PLANS, DCA_PLAN_COUNT, DCA_SCHEDULE, DCA_PURCHASE_CACHE, DcaPurchaseCache, NAV_ORDERS, NAV_ORDER_COUNT, NAV_TWAP_WINDOW, DONATION_BENEFICIARIES, DONATIONS, DONATED, SHARES, TOTAL_SHARES, GOV_CONFIG, PROPOSALS, PROPOSAL_COUNT, VOTES, INDEX_METHODOLOGIES, INDEX_WEIGHTS, PAIR_ROUTES, IBC_REFUND_CACHE, IBC_REFUNDS, OUTFLOW_LIMITS, OUTFLOWS, REDEMPTION_QUEUE, REDEMPTION_QUEUE_COUNT, QUEUED_POSITIONS, WIND_DOWNS, WIND_DOWN_CLAIMS};
+use osmosis_std::shim::Duration;
+use osmosis_std::types::osmosis::downtimedetector::v1beta1::{Downtime, DowntimedetectorQuerier};
+use osmosis_std::types::osmosis::gamm::v1beta1::{SwapAmountInRoute, SwapAmountOutRoute, GammQuerier as GammV1Beta1Querier};
+use osmosis_std::types::osmosis::tokenfactory::v1beta1::{MsgCreateDenom, MsgMint, MsgBurn, MsgSetDenomMetadata};
+use osmosis_std::types::cosmos::bank::v1beta1::{Metadata, DenomUnit, BankQuerier};
+use osmosis_std::types::ibc::applications::transfer::v1::{MsgTransfer, MsgTransferResponse};
+use std::str::FromStr;
+use semver::Version;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:counter_manager";
@@ -29,20 +40,67 @@ const EXECUTE_SWAPS_REPLY_ID:u64 = 4;
 const EXECUTE_REVERT_SWAPS_REPLY_ID: u64 = 5;
 const EXECUTE_CONJUNCTION_SWAPS_REPLY_ID: u64 = 6;
 const EXECUTE_REDEEM_SWAP_REPLY_ID:u64 = 7;
+const EXECUTE_SWAP_EXACT_OUT_REPLY_ID:u64 = 8;
+const EXECUTE_JOIN_POOL_REPLY_ID:u64 = 9;
+const EXECUTE_EXIT_POOL_REPLY_ID:u64 = 10;
+const EXECUTE_BATCH_BUY_REPLY_ID:u64 = 11;
+const EXECUTE_BATCH_SWAPS_REPLY_ID:u64 = 12;
+const EXECUTE_BATCH_SELL_REPLY_ID:u64 = 13;
+const IBC_REFUND_REPLY_ID:u64 = 14;
+const DCA_PURCHASE_REPLY_ID:u64 = 15;
 
 const OSMO_ATOM_POOL_ID: u64 = 1;
 const OSMO_USDC_POOL_ID: u64 = 2;
 
+// each has its initial route, ibc vouchers have none, so ibc-hooks transfers of them fail and get refunded by ibc
+const DEPOSIT_DENOMS: [&str; 2] = ["uosmo", "usdc"];
+
+// part of every dca purchase paid to whoever executed it
+const DCA_KEEPER_BOUNTY_PERMILLE: u64 = 1;
+// keeps `ExecuteDue` within block gas limits, the rest is left for the next call
+const MAX_DUE_PURCHASES: usize = 10;
+// used until the owner sets a window of their own
+const DEFAULT_NAV_TWAP_WINDOW: u64 = 600;
+// refunds not relayed by then go to their recovery address
+const IBC_REFUND_TIMEOUT: u64 = 600;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     _msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     MANAGER_CONTRACT.save(deps.storage, &env.contract.address)?;
-    Ok(Response::new().add_attribute("method", "instantiate"))
+    OWNER.save(deps.storage, &info.sender)?;
+    PAUSED.save(deps.storage, &PauseState::default())?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Handling contract migration
+/// Every `MigrateMsg` variant migrates storage from the release preceding it,
+/// so it is only accepted when the stored version is older than the variant's target.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let (attributes, messages) = match msg {
+        MigrateMsg::V0_2_0 { owner } => {
+            ensure_older_version(&stored, "0.2.0")?;
+            let owner = deps.api.addr_validate(&owner)?;
+            migrations::v0_2_0::migrate(deps.branch(), &env, &owner)?
+        }
+    };
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "migrate")
+        .add_attribute("previous_version", stored.version)
+        .add_attribute("new_version", CONTRACT_VERSION)
+        .add_attributes(attributes))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -53,33 +111,180 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::InstantiateSwap { code_id, debug} 
+        ExecuteMsg::InstantiateSwap { code_id, debug}
             => execute_instantiate_swap(deps, info, code_id, debug
         ),
-        ExecuteMsg::InstantiateCw20 { code_id, etf_name, etf_symbol} 
+        ExecuteMsg::InstantiateCw20 { code_id, etf_name, etf_symbol}
             => execute_instantiate_cw20(deps, info, env, code_id, etf_name, etf_symbol
         ),
-        ExecuteMsg::SwapTokens { initial_balance, etf_swap_routes} 
-            => try_execute_swap_exact_amount_in(deps, env, info, etf_swap_routes,initial_balance
+        ExecuteMsg::CreateNativeDenom { etf_name, etf_symbol} => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            execute_create_native_denom(deps, env, etf_name, etf_symbol)
+        },
+        ExecuteMsg::SwapTokens { initial_balance, etf_swap_routes, on_behalf_of, ibc_sender} => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            try_execute_swap_exact_amount_in(deps, env, info, etf_swap_routes,initial_balance, on_behalf_of, ibc_sender)
+        },
+        ExecuteMsg::SwapTokensExactAmountOut { max_initial_balance, etf_swap_routes, shares, max_slippage} => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            try_execute_swap_exact_amount_out(deps, env, info, etf_swap_routes, shares, max_initial_balance, max_slippage)
+        },
+        ExecuteMsg::RedeemTokens {etf_name} => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            redeem_tokens(deps, info, env, etf_name)
+        },
+        // only ever dispatched by `redeem_tokens`
+        ExecuteMsg::Callback { operands } => {
+            if info.sender != env.contract.address {
+                return Err(ContractError::Unauthorized {});
+            }
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            Ok(Response::default().add_submessages(operands))
+        },
+        ExecuteMsg::Pause { buys, redemptions }
+            => execute_set_paused(deps, info, buys, redemptions, true
+        ),
+        ExecuteMsg::Unpause { buys, redemptions }
+            => execute_set_paused(deps, info, buys, redemptions, false
         ),
-        ExecuteMsg::RedeemTokens {etf_name} 
-            => redeem_tokens(deps, info, env, etf_name
+        ExecuteMsg::SetDowntimeBreaker { breaker }
+            => execute_set_downtime_breaker(deps, info, breaker
         ),
-        ExecuteMsg::Callback { operands } => Ok(Response::default().add_submessages(operands)),
-
+        ExecuteMsg::SetEtfLimits { etf_name, limits }
+            => execute_set_etf_limits(deps, info, etf_name, limits
+        ),
+        ExecuteMsg::SetBatchMode { etf_name, batch }
+            => execute_set_batch_mode(deps, info, etf_name, batch
+        ),
+        ExecuteMsg::QueueDeposit { etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            execute_queue_deposit(deps, env, info, etf_name)
+        },
+        ExecuteMsg::QueueRedemption { etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            execute_queue_redemption(deps, env, info, etf_name)
+        },
+        ExecuteMsg::SettleBatch { etf_name } => {
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_settle_batch(deps, env, etf_name)
+        },
+        // claims mint the shares of settled deposits and pay out the proceeds of settled redemptions
+        ExecuteMsg::ClaimBatch { etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            execute_claim_batch(deps, info, etf_name)
+        },
+        ExecuteMsg::CreateDcaPlan { etf_name, amount_per_period, period, num_periods, routes, ratios } => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            let etf_swap_routes = EtfSwapRoutes { name: etf_name, routes, ratios };
+            execute_create_dca_plan(deps, env, info, etf_swap_routes, amount_per_period, period, num_periods)
+        },
+        ExecuteMsg::ExecuteDue {} => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_due(deps, env, info)
+        },
+        ExecuteMsg::PauseDcaPlan { plan_id }
+            => execute_set_dca_plan_paused(deps, info, plan_id, true
+        ),
+        ExecuteMsg::ResumeDcaPlan { plan_id }
+            => execute_set_dca_plan_paused(deps, info, plan_id, false
+        ),
+        ExecuteMsg::CancelDcaPlan { plan_id }
+            => execute_cancel_dca_plan(deps, info, plan_id
+        ),
+        ExecuteMsg::DcaPurchase { owner, etf_swap_routes, deposit } => {
+            if info.sender != env.contract.address {
+                return Err(ContractError::Unauthorized {});
+            }
+            execute_dca_purchase(deps, env, owner, etf_swap_routes, deposit)
+        },
+        ExecuteMsg::SetNavTwapWindow { seconds }
+            => execute_set_nav_twap_window(deps, info, seconds
+        ),
+        ExecuteMsg::PlaceNavOrder { etf_name, kind, nav_per_share, expires_at } => {
+            match kind {
+                NavOrderKind::Buy { .. } => ensure_not_paused(deps.as_ref(), Operation::Buys)?,
+                NavOrderKind::Redeem {} => ensure_not_paused(deps.as_ref(), Operation::Redemptions)?,
+            }
+            execute_place_nav_order(deps, env, info, etf_name, kind, nav_per_share, expires_at)
+        },
+        ExecuteMsg::ExecuteNavOrder { owner, order_id } => {
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_nav_order(deps, env, owner, order_id)
+        },
+        ExecuteMsg::CancelNavOrder { order_id }
+            => execute_cancel_nav_order(deps, info, order_id
+        ),
+        ExecuteMsg::SetDonationBeneficiaries { etf_name, beneficiaries }
+            => execute_set_donation_beneficiaries(deps, info, etf_name, beneficiaries
+        ),
+        ExecuteMsg::SetDonation { etf_name, donation }
+            => execute_set_donation(deps, env, info, etf_name, donation
+        ),
+        ExecuteMsg::HarvestDonation { holder, etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_harvest_donation(deps, env, holder, etf_name)
+        },
+        ExecuteMsg::SetGovConfig { config }
+            => execute_set_gov_config(deps, info, config
+        ),
+        ExecuteMsg::Propose { etf_name, action }
+            => execute_propose(deps, env, info, etf_name, action
+        ),
+        ExecuteMsg::Vote { proposal_id, vote }
+            => execute_vote(deps, env, info, proposal_id, vote
+        ),
+        // not paused, passed proposals only change etf settings the owner can change while paused as well
+        ExecuteMsg::ExecuteProposal { proposal_id }
+            => execute_proposal(deps, env, proposal_id
+        ),
+        ExecuteMsg::SetIndexMethodology { etf_name, methodology }
+            => execute_set_index_methodology(deps, env, info, etf_name, methodology
+        ),
+        ExecuteMsg::Rebalance { etf_name } => {
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_rebalance(deps, env, etf_name)
+        },
+        ExecuteMsg::SetPairRoute { pool_id, pair }
+            => execute_set_pair_route(deps, info, pool_id, pair
+        ),
+        ExecuteMsg::SetOutflowLimit { etf_name, limit }
+            => execute_set_outflow_limit(deps, info, etf_name, limit
+        ),
+        ExecuteMsg::ProcessQueuedRedemption { etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            ensure_recovered_from_downtime(deps.as_ref())?;
+            execute_process_queued_redemption(deps, env, etf_name)
+        },
+        ExecuteMsg::WindDown { etf_name }
+            => execute_wind_down(deps, env, info, etf_name
+        ),
+        ExecuteMsg::ClaimWindDown { etf_name } => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            execute_claim_wind_down(deps, env, info, etf_name)
+        },
     }
 }
 
 pub fn execute_instantiate_swap(
-    _deps: DepsMut,
-    _info: MessageInfo,
+    deps: DepsMut,
+    info: MessageInfo,
     code_id: u64,
     debug: bool
 ) -> Result<Response, ContractError> {
+    // deposits go to whichever swap contract is instantiated last
+    ensure_owner(deps.as_ref(), &info)?;
+    // the owner migrates the swap contract along with the manager
     let instantiate_message = WasmMsg::Instantiate {
-        admin: None,
+        admin: Some(info.sender.to_string()),
         code_id,
-        msg: to_binary(&osmo_swap::msg::InstantiateMsg { debug: debug })?,
+        msg: to_binary(&swap_msg::InstantiateMsg { debug })?,
         funds: vec![],
         label: "osmo_swap".to_string(),
     };
@@ -91,19 +296,23 @@ pub fn execute_instantiate_swap(
 
 pub fn execute_instantiate_cw20(
     deps: DepsMut, 
-    _info: MessageInfo, 
+    info: MessageInfo, 
     env: Env, 
     code_id: u64,
     etf_name: String,
     etf_symbol: String
 ) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    if NATIVE_DENOMS.has(deps.storage, &etf_name) {
+        return Err(ContractError::EtfAlreadyExists { val: etf_name });
+    }
 
     let instantiate_mint_contract = WasmMsg::Instantiate {
         code_id: code_id,
         funds: vec![],
-        admin: None,
+        admin: Some(info.sender.to_string()),
         label: "lp_token".to_string(),
-        msg: to_binary(&cw20_base::msg::InstantiateMsg {
+        msg: to_binary(&share_token::InstantiateMsg {
             name: etf_name.to_owned(),
             symbol: etf_symbol.to_owned(),
             decimals: 6,
@@ -122,51 +331,149 @@ pub fn execute_instantiate_cw20(
     Ok(Response::new().add_submessage(reply_msg))
 }
 
+pub fn execute_create_native_denom(
+    deps: DepsMut,
+    env: Env,
+    etf_name: String,
+    etf_symbol: String
+) -> Result<Response, ContractError> {
+    if etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::EtfAlreadyExists { val: etf_name });
+    }
+
+    let manager_addr = env.contract.address.to_string();
+    let denom = format!("factory/{}/{}", manager_addr, etf_symbol);
+    let create_denom: CosmosMsg = MsgCreateDenom {
+        sender: manager_addr.to_owned(),
+        subdenom: etf_symbol.to_owned(),
+    }.into();
+    let set_metadata: CosmosMsg = MsgSetDenomMetadata {
+        sender: manager_addr,
+        metadata: Some(Metadata {
+            description: format!("Shares of {} etf", etf_name),
+            denom_units: vec![
+                DenomUnit { denom: denom.to_owned(), exponent: 0, aliases: vec![] },
+                DenomUnit { denom: etf_symbol.to_owned(), exponent: 6, aliases: vec![] },
+            ],
+            base: denom.to_owned(),
+            display: etf_symbol.to_owned(),
+            name: etf_name.to_owned(),
+            symbol: etf_symbol,
+        }),
+    }.into();
+    NATIVE_DENOMS.save(deps.storage, &etf_name, &denom)?;
+
+    Ok(Response::new()
+        .add_message(create_denom)
+        .add_message(set_metadata)
+        .add_attribute("method", "create_native_denom")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("denom", denom))
+}
+
 pub fn try_execute_swap_exact_amount_in(
     deps: DepsMut, 
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     etf_swap_routes: EtfSwapRoutes,
     deposit: Coin,
+    on_behalf_of: Option<String>,
+    ibc_sender: Option<IbcSender>,
 ) 
 -> Result<Response, ContractError> { 
 
-    if !MINT_CONTRACTS.has(deps.storage, &etf_swap_routes.name) {
-        return Err(ContractError::MintContractNotFound{val: etf_swap_routes.name});
-    }
-
-    // validate length of provided routes and ratios vectors
-    if &etf_swap_routes.ratios.len() != &etf_swap_routes.routes.len() {
-        return Err(ContractError::InvalidEntryParams{});
-    }
-    // validate sum of ratios
-    let ratios_sum: Uint128 = etf_swap_routes.clone().ratios.iter().sum();
-    if ratios_sum != Uint128::from(100u128) {
-        return Err(ContractError::InvalidRatio{});
-    }
+    validate_etf_swap_routes(deps.as_ref(), &etf_swap_routes, &deposit.denom)?;
     if !info.funds.iter().any(|i| i.denom == deposit.denom && i.amount >= deposit.amount) {
         return Err(ContractError::DepositNotFound { });
     }
  
-    if !vec!["uosmo", "usdc"].iter().any(|&i| i == deposit.denom) {
+    if !DEPOSIT_DENOMS.iter().any(|&i| i == deposit.denom) {
         return Err(ContractError::InvalidDepositDenom {val: deposit.denom.clone()});
     }
+    if let Some(ibc_sender) = &ibc_sender {
+        ensure_ibc_hooks_sender(&env, &info, ibc_sender)?;
+    }
 
-    let sender = info.sender.clone().to_string();
+    let sender = match on_behalf_of {
+        Some(recipient) => deps.api.addr_validate(&recipient)?.to_string(),
+        None => info.sender.to_string(),
+    };
     let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
-    let bank_msg = BankMsg::Send { to_address: swap_contract_addr.to_string(), amount: info.funds };
+    let (bank_msg, refund_msg) = create_msgs_forward_deposit(&swap_contract_addr, &info, &deposit);
+    let refund_submsgs = match &ibc_sender {
+        Some(ibc_sender) => create_submsgs_ibc_refund(
+            deps.storage, &env, ibc_sender, &sender, excess_funds(&info.funds, &deposit))?,
+        None => refund_msg.into_iter().map(SubMsg::new).collect(),
+    };
+    let submessage = create_submsg_swap_exact_amount_in(deps, &env, &sender, etf_swap_routes, deposit)?;
 
-    // let's keep track of user's deposited USDC
-    let depo_key = (sender.as_str(), etf_swap_routes.name.as_str());
-    let new_deposit;
-    if BALANCE.has(deps.storage, depo_key.clone()){
-        let curr_deposit = BALANCE.load(deps.storage, depo_key).unwrap();        
-        new_deposit = coin(curr_deposit.amount.checked_add(deposit.amount).unwrap().u128(),
-            curr_deposit.denom);
-    } else {
-        new_deposit = deposit.clone();
+    Ok(Response::new()
+        .add_message(bank_msg)
+        .add_submessages(refund_submsgs)
+        .add_submessage(submessage))
+}
+
+// ibc-hooks executes the memo from an address derived from the channel and the original sender
+fn ensure_ibc_hooks_sender(env: &Env, info: &MessageInfo, ibc_sender: &IbcSender) -> Result<(), ContractError> {
+    let prefix = bech32_prefix(env.contract.address.as_str())?;
+    if info.sender != derive_intermediate_sender(&ibc_sender.channel, &ibc_sender.sender, &prefix)? {
+        return Err(ContractError::IbcSenderMismatch { val: info.sender.to_string() });
+    }
+    Ok(())
+}
+
+// refunds go back over the channel the deposit came in through, ibc-hooks reports their outcome to `sudo`
+fn create_submsgs_ibc_refund(
+    storage: &mut dyn Storage,
+    env: &Env,
+    ibc_sender: &IbcSender,
+    recovery_addr: &str,
+    funds: Vec<Coin>,
+) -> StdResult<Vec<SubMsg>> {
+    let refunds: Vec<IbcRefund> = funds.into_iter()
+        .map(|amount| IbcRefund {
+            channel: ibc_sender.channel.to_owned(),
+            receiver: ibc_sender.sender.to_owned(),
+            amount,
+            recovery_addr: recovery_addr.to_string(),
+        })
+        .collect();
+    if refunds.is_empty() {
+        return Ok(vec![]);
     }
-    BALANCE.save(deps.storage,  depo_key,  &new_deposit)?;
+    IBC_REFUND_CACHE.save(storage, &refunds)?;
+    Ok(refunds.into_iter()
+        .map(|refund| {
+            let transfer = MsgTransfer {
+                source_port: "transfer".to_string(),
+                source_channel: refund.channel,
+                token: Some(refund.amount.into()),
+                sender: env.contract.address.to_string(),
+                receiver: refund.receiver,
+                timeout_height: None,
+                timeout_timestamp: env.block.time.plus_seconds(IBC_REFUND_TIMEOUT).nanos(),
+                memo: format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address),
+            };
+            SubMsg::reply_on_success(transfer, IBC_REFUND_REPLY_ID)
+        })
+        .collect())
+}
+
+// deposit has to be with the swap contract by the time the submessage executes
+fn create_submsg_swap_exact_amount_in(
+    deps: DepsMut,
+    env: &Env,
+    sender: &str,
+    etf_swap_routes: EtfSwapRoutes,
+    deposit: Coin,
+) -> Result<SubMsg<Empty>, ContractError> {
+    let etf_swap_routes = with_index_weights(deps.as_ref(), etf_swap_routes)?;
+    ensure_within_etf_limits(deps.as_ref(), env, sender, &etf_swap_routes.name, &deposit)?;
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+
+    // let's keep track of user's deposited USDC
+    let shares = shares_for_deposit(deps.as_ref(), env, &deposit)?;
+    save_deposit(deps.storage, env.block.height, sender, &etf_swap_routes.name, &deposit, shares)?;
 
     INITIAL_DEPOSIT_CACHE.save(deps.storage, &coin(deposit.amount.into(), deposit.denom.to_string()))?;
 
@@ -174,28 +481,310 @@ pub fn try_execute_swap_exact_amount_in(
     ETF_POOLS.save(deps.storage, &deposit_token_out_denom, &pool_id)?;
 
     let execute_message = create_msg_execute_swap(
-        swap_contract_addr.to_string(), pool_id, deposit_token_out_denom.to_owned(), deposit.clone()
-    );
-    let submessage:SubMsg<Empty> = SubMsg::reply_on_success(execute_message, EXECUTE_SWAP_REPLY_ID);
+        deps.storage, swap_contract_addr.to_string(), pool_id, deposit_token_out_denom.to_owned(), deposit.clone()
+    )?;
+    ETF_CACHE.save(deps.storage, &EtfCache { sender: sender.to_string(), etf_swap_routes, shares })?;
+
+    Ok(SubMsg::reply_on_success(execute_message, EXECUTE_SWAP_REPLY_ID))
+}
+
+pub fn try_execute_swap_exact_amount_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_swap_routes: EtfSwapRoutes,
+    shares: Uint128,
+    max_deposit: Coin,
+    max_slippage: Decimal,
+) -> Result<Response, ContractError> {
+    validate_etf_swap_routes(deps.as_ref(), &etf_swap_routes, &max_deposit.denom)?;
+    // batched etfs buy through their batch only
+    if BATCH_CONFIGS.has(deps.storage, &etf_swap_routes.name) {
+        return Err(ContractError::CustomError { val: "batched etfs can't be bought with exact output".to_string() });
+    }
+    if shares.is_zero() {
+        return Err(ContractError::InvalidEntryParams{});
+    }
+    if !info.funds.iter().any(|i| i.denom == max_deposit.denom && i.amount >= max_deposit.amount) {
+        return Err(ContractError::DepositNotFound { });
+    }
+    if !DEPOSIT_DENOMS.iter().any(|&i| i == max_deposit.denom) {
+        return Err(ContractError::InvalidDepositDenom {val: max_deposit.denom.clone()});
+    }
+    let etf_swap_routes = with_index_weights(deps.as_ref(), etf_swap_routes)?;
+
+    let sender = info.sender.to_string();
+    // the actual deposit is only known once swaps are done, limits are checked against its upper bound
+    ensure_within_etf_limits(deps.as_ref(), &env, &sender, &etf_swap_routes.name, &max_deposit)?;
+
+    if etf_swap_routes.routes.iter().any(|route| pool_share_id(&route.token_out_denom).is_some()) {
+        return Err(ContractError::CustomError { val: "pool shares can't be bought with exact output".to_string() });
+    }
+
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+    validate_routes(deps.as_ref(), &swap_contract_addr, &etf_swap_routes.routes)?;
+    let (bank_msg, refund_msg) = create_msgs_forward_deposit(&swap_contract_addr, &info, &max_deposit);
+
+    let (deposit_token_out_denom, pool_id) = get_initial_route_params(&max_deposit.denom)?;
+    ETF_POOLS.save(deps.storage, &deposit_token_out_denom, &pool_id)?;
+
+    // shares are worth their part of the etf, split into tokens by the ratios and priced at TWAPs
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps.as_ref())?);
+    let shares_value = value_shares(deps.as_ref(), &etf_swap_routes.name, shares, &max_deposit.denom, Some(twap_start))?;
+    let intermediate_value = checked_mul_dec(
+        shares_value, route_price(deps.as_ref(), pool_id, &max_deposit.denom, &deposit_token_out_denom, Some(twap_start))?)?;
+
+    // every token is bought straight from the deposit, through the same intermediate token exact in swaps use
+    let mut submessages: Vec<SubMsg<Empty>> = vec![];
+    let mut estimated_total = Uint128::zero();
+    let mut max_total = Uint128::zero();
+    for (route, ratio) in etf_swap_routes.routes.iter().zip(etf_swap_routes.ratios.iter()) {
+        let leg_value = intermediate_value.multiply_ratio(*ratio, 100u128);
+        let amount_out = match route.token_out_denom == deposit_token_out_denom {
+            true => leg_value,
+            false => checked_mul_dec(
+                leg_value, route_price(deps.as_ref(), route.pool_id, &deposit_token_out_denom, &route.token_out_denom, Some(twap_start))?)?,
+        };
+        if amount_out.is_zero() {
+            continue
+        }
+        let mut swap_routes = vec![SwapAmountOutRoute { pool_id, token_in_denom: max_deposit.denom.to_owned() }];
+        if route.token_out_denom != deposit_token_out_denom {
+            swap_routes.push(SwapAmountOutRoute { pool_id: route.pool_id, token_in_denom: deposit_token_out_denom.to_owned() });
+            if !ETF_POOLS.has(deps.storage, &route.token_out_denom) {
+                ETF_POOLS.save(deps.storage, &route.token_out_denom, &route.pool_id)?;
+            }
+        }
+        let token_out = coin(amount_out.u128(), route.token_out_denom.to_owned());
+        let estimated = GammV1Beta1Querier::new(&deps.querier)
+            .estimate_swap_exact_amount_out(swap_contract_addr.to_string(), pool_id, swap_routes.clone(), token_out.to_string())?
+            .token_in_amount;
+        let estimated = Uint128::from_str(&estimated)?;
+        let max_in = checked_mul_dec(estimated, Decimal::one().checked_add(max_slippage).map_err(StdError::from)?)?;
+        estimated_total = estimated_total.checked_add(estimated).map_err(StdError::from)?;
+        max_total = max_total.checked_add(max_in).map_err(StdError::from)?;
+
+        let execute_message = create_msg_execute_swap_exact_out(swap_contract_addr.to_string(), swap_routes, token_out, max_in);
+        submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_SWAP_EXACT_OUT_REPLY_ID));
+    }
+    if max_total > max_deposit.amount {
+        return Err(ContractError::InsufficientDeposit { required: max_total });
+    }
 
-    ETF_CACHE.save(deps.storage, &EtfCache { sender: sender.to_string(), etf_swap_routes: etf_swap_routes.clone()})?;
+    EXACT_OUT_CACHE.save(deps.storage, &ExactOutCache {
+        sender,
+        etf_name: etf_swap_routes.name,
+        max_deposit: max_deposit.to_owned(),
+        spent: Uint128::zero(),
+        initial_swap: coin(0, deposit_token_out_denom),
+        swaps_left: submessages.len() as u64,
+        shares,
+    })?;
 
     Ok(Response::new()
         .add_message(bank_msg)
-        .add_submessage(submessage))
+        .add_messages(refund_msg)
+        .add_submessages(submessages)
+        .add_attribute("method", "swap_tokens_exact_amount_out")
+        .add_attribute("estimated_deposit", estimated_total)
+        .add_attribute("max_deposit", max_total))
 }
 
 
 fn redeem_tokens(deps: DepsMut, info: MessageInfo, env: Env, etf_name: String) -> Result<Response, ContractError> { 
-    if !LEDGER.has(deps.storage, (&info.sender.as_str(), &etf_name.as_str())) {
+    let sender = info.sender.to_string();
+    ensure_not_queued(deps.as_ref(), &sender, &etf_name)?;
+    // native shares can change hands, whoever sends them back redeems any part of them
+    if let Some(denom) = NATIVE_DENOMS.may_load(deps.storage, &etf_name)? {
+        let shares = returned_shares(&info, &denom)?;
+        if !has_outflow_capacity(deps.storage, &env, &etf_name, shares)? {
+            return queue_outflow_redemption(deps, env, sender, etf_name, shares);
+        }
+        return redeem_native_shares(deps, env, sender, etf_name, shares);
+    }
+
+    if !LEDGER.has(deps.storage, (sender.as_str(), etf_name.as_str())) {
         return Err(ContractError::Unauthorized{});
     }
-    
+    let shares = SHARES.load(deps.storage, (&etf_name, &sender))?;
+    if !has_outflow_capacity(deps.storage, &env, &etf_name, shares)? {
+        return queue_outflow_redemption(deps, env, sender, etf_name, shares);
+    }
+    redeem_position(deps, env, sender, etf_name)
+}
+
+// the position stays in the ledger until it's redeemed, native shares stay with the manager
+fn queue_outflow_redemption(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    etf_name: String,
+    shares: Uint128
+) -> Result<Response, ContractError> {
+    let id = REDEMPTION_QUEUE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    REDEMPTION_QUEUE_COUNT.save(deps.storage, &id)?;
+    REDEMPTION_QUEUE.save(deps.storage, (&etf_name, id), &QueuedRedemption {
+        id,
+        sender: sender.to_owned(),
+        shares,
+        queued_at: env.block.time,
+    })?;
+    QUEUED_POSITIONS.save(deps.storage, (&sender, &etf_name), &id)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "redeem_tokens")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("redemption_queued", id.to_string()))
+}
+
+fn execute_process_queued_redemption(deps: DepsMut, env: Env, etf_name: String) -> Result<Response, ContractError> {
+    let queued = first_queued_redemption(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::EmptyRedemptionQueue { val: etf_name.to_owned() })?;
+    if !try_consume_outflow(deps.storage, &env, &etf_name, queued.shares)? {
+        return Err(ContractError::OutflowLimitReached { val: etf_name });
+    }
+    REDEMPTION_QUEUE.remove(deps.storage, (&etf_name, queued.id));
+    QUEUED_POSITIONS.remove(deps.storage, (&queued.sender, &etf_name));
+
+    let res = match NATIVE_DENOMS.has(deps.storage, &etf_name) {
+        true => redeem_native_shares(deps, env, queued.sender.to_owned(), etf_name, queued.shares)?,
+        false => redeem_position(deps, env, queued.sender.to_owned(), etf_name)?,
+    };
+    Ok(res
+        .add_attribute("queued_redemption", queued.id.to_string())
+        .add_attribute("sender", queued.sender))
+}
+
+fn execute_wind_down(deps: DepsMut, env: Env, info: MessageInfo, etf_name: String) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    // tokens of queued batch redemptions are no longer in the ledger
+    if batch_has_requests(deps.as_ref(), &etf_name) {
+        return Err(ContractError::BatchNotEmpty { val: etf_name });
+    }
+
+    let mut basket: Vec<Coin> = vec![];
+    for item in LEDGER.range(deps.storage, None, None, Order::Ascending) {
+        let ((_, etf), ledger) = item?;
+        if etf != etf_name {
+            continue
+        }
+        for c in ledger.into_iter() {
+            add_to_coins(&mut basket, c)?;
+        }
+    }
+
+    // queued redemptions are dropped, escrowed native shares go back to be claimed with
+    let queue = REDEMPTION_QUEUE
+        .prefix(&etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(u64, QueuedRedemption)>>>()?;
+    let native_denom = NATIVE_DENOMS.may_load(deps.storage, &etf_name)?;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for (id, queued) in queue.into_iter() {
+        REDEMPTION_QUEUE.remove(deps.storage, (&etf_name, id));
+        QUEUED_POSITIONS.remove(deps.storage, (&queued.sender, &etf_name));
+        if let Some(denom) = &native_denom {
+            msgs.push(BankMsg::Send { to_address: queued.sender, amount: vec![coin(queued.shares.u128(), denom)] }.into());
+        }
+    }
+
+    let total_shares = TOTAL_SHARES.may_load(deps.storage, &etf_name)?.unwrap_or_default();
+    WIND_DOWNS.save(deps.storage, &etf_name, &WindDown {
+        basket,
+        total_shares,
+        claimed: vec![],
+        claimed_shares: Uint128::zero(),
+        wound_down_at: env.block.time,
+    })?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "wind_down")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("total_shares", total_shares))
+}
+
+fn execute_claim_wind_down(deps: DepsMut, env: Env, info: MessageInfo, etf_name: String) -> Result<Response, ContractError> {
+    let mut wind_down = WIND_DOWNS.may_load(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::NotWoundDown { val: etf_name.to_owned() })?;
+    let holder = info.sender.to_string();
+    let shares = match NATIVE_DENOMS.may_load(deps.storage, &etf_name)? {
+        // native shares can change hands, whoever sends them back claims
+        Some(denom) => info.funds.iter().find(|c| c.denom == denom).map(|c| c.amount).unwrap_or_default(),
+        None => {
+            match BALANCE.has(deps.storage, (&holder, &etf_name)) {
+                true => {
+                    LEDGER.remove(deps.storage, (&holder, &etf_name));
+                    INITIAL_SWAP.remove(deps.storage, (&holder, &etf_name));
+                    DONATIONS.remove(deps.storage, (&holder, &etf_name));
+                    remove_deposit(deps.storage, env.block.height, &holder, &etf_name)?
+                },
+                false => Uint128::zero(),
+            }
+        },
+    };
+    if shares.is_zero() {
+        return Err(ContractError::NotFound { val: format!("shares of etf {} to claim with", etf_name) });
+    }
+
+    let tokens = wind_down_share(&wind_down, shares)?;
+    let mut claimed = WIND_DOWN_CLAIMS.may_load(deps.storage, (&etf_name, &holder))?.unwrap_or_default();
+    for c in tokens.iter() {
+        add_to_coins(&mut wind_down.claimed, c.to_owned())?;
+        add_to_coins(&mut claimed, c.to_owned())?;
+    }
+    wind_down.claimed_shares = wind_down.claimed_shares.checked_add(shares).map_err(StdError::from)?;
+    WIND_DOWNS.save(deps.storage, &etf_name, &wind_down)?;
+    WIND_DOWN_CLAIMS.save(deps.storage, (&etf_name, &holder), &claimed)?;
+
+    let mut msgs = vec![create_msg_burn_shares(deps.as_ref(), &etf_name, shares)?];
+    if !tokens.is_empty() {
+        let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+        msgs.push(create_msg_send_tokens_back(&swap_addr, tokens, holder.to_owned())?);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "claim_wind_down")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("holder", holder)
+        .add_attribute("shares_burnt", shares))
+}
+
+// Native shares are redeemed in kind, for their part of every position of the etf.
+// The shares have to be with the manager already
+fn redeem_native_shares(deps: DepsMut, env: Env, redeemer: String, etf_name: String, shares: Uint128) -> Result<Response, ContractError> {
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    let tokens = take_pro_rata(deps.storage, env.block.height, &etf_name, shares)?;
+
+    let mut msgs = vec![create_msg_burn_shares(deps.as_ref(), &etf_name, shares)?];
+    if !tokens.is_empty() {
+        let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+        msgs.push(create_msg_send_tokens_back(&swap_addr, tokens, redeemer.to_owned())?);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "redeem_tokens")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("redeemer", redeemer)
+        .add_attribute("shares_burnt", shares))
+}
+
+// cw20 shares stay with the original depositor, so their whole position is redeemed.
+// Native shares of the position have to be with the manager already
+fn redeem_position(mut deps: DepsMut, env: Env, sender: String, etf_name: String) -> Result<Response, ContractError> {
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    let donation_msgs = take_donation(deps.branch(), &env, &sender, &etf_name, true)?;
     let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
-    let ledger = LEDGER.load(deps.storage, (&info.sender.as_str(), &etf_name.as_str()))?;
+    let ledger = LEDGER.load(deps.storage, (sender.as_str(), etf_name.as_str()))?;
 
     // find pool for reverting transactions
-    let depo_coin = BALANCE.load(deps.storage, (&info.sender.to_string(), &etf_name))?;
+    let depo_coin = BALANCE.load(deps.storage, (&sender, &etf_name))?;
     let (token_out_denom, _) = get_initial_route_params(&depo_coin.denom)?;
 
     let mut submessages: Vec<SubMsg<Empty>> = vec![];
@@ -217,10 +806,16 @@ fn redeem_tokens(deps: DepsMut, info: MessageInfo, env: Env, etf_name: String) -
             }
             continue
         }
+        if let Some(pool_id) = pool_share_id(&c.denom) {
+            let execute_message = create_msg_execute_exit_pool(
+                swap_addr.to_string(), pool_id, token_out_denom.to_string(), c.amount);
+            submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_EXIT_POOL_REPLY_ID));
+            continue
+        }
         let pool_id = ETF_POOLS.load(deps.storage, &c.denom)?;
         let execute_message = create_msg_execute_swap(
-            swap_addr.to_string(), pool_id, token_out_denom.to_string(), 
-            c);
+            deps.storage, swap_addr.to_string(), pool_id, token_out_denom.to_string(), 
+            c)?;
         submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_REVERT_SWAPS_REPLY_ID));
     }
 
@@ -230,351 +825,2612 @@ fn redeem_tokens(deps: DepsMut, info: MessageInfo, env: Env, etf_name: String) -
         msg: to_binary(&ExecuteMsg::Callback { operands: submessages }).unwrap()
     };
     ETF_NAME_CACHE.save(deps.storage, &(EtfNameCache{
-        sender: info.sender.to_string(),
+        sender,
         etf_name: etf_name
     }))?;
  
     Ok(Response::new()
     .add_submessage(SubMsg::reply_on_success(callback_message, EXECUTE_CONJUNCTION_SWAPS_REPLY_ID))
+    .add_messages(donation_msgs)
     .add_attribute("method", "redeem_tokens")
 )
 }
 
-// ----------------------------------- REPLY HANDLING
-// ##############################################################################
+fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    buys: bool,
+    redemptions: bool,
+    paused: bool
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    let state = PAUSED.update(deps.storage, |mut state| -> StdResult<_> {
+        if buys {
+            state.buys = paused;
+        }
+        if redemptions {
+            state.redemptions = paused;
+        }
+        Ok(state)
+    })?;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
-    match msg.id {
-        INSTANTIATE_SWAP_REPLY_ID => handle_instantiate_swap_reply(deps, msg),
-        INSTANTIATE_CW20_REPLY_ID => handle_instantiate_cw20_reply(deps, msg),
-        EXECUTE_SWAP_REPLY_ID => handle_swap_reply(deps, msg),
-        EXECUTE_SWAPS_REPLY_ID => handle_swaps_reply(deps, msg),
-        EXECUTE_REVERT_SWAPS_REPLY_ID => handle_revert_swaps(deps, msg),
-        EXECUTE_CONJUNCTION_SWAPS_REPLY_ID => handle_conjunction_swaps(deps, msg),
-        EXECUTE_REDEEM_SWAP_REPLY_ID => handle_redeem_swap(deps, msg),
-        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
-    }
+    Ok(Response::new()
+        .add_attribute("method", if paused { "pause" } else { "unpause" })
+        .add_attribute("buys_paused", state.buys.to_string())
+        .add_attribute("redemptions_paused", state.redemptions.to_string()))
 }
 
-fn handle_instantiate_swap_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
-    let res = parse_reply_instantiate_data(msg).unwrap();
-    let checked: Addr = deps.api.addr_validate(&res.contract_address)?;
-    SWAP_CONTRACT.save(deps.storage,&checked)?;
-    Ok(Response::default())
-}
+fn execute_set_downtime_breaker(
+    deps: DepsMut,
+    info: MessageInfo,
+    breaker: Option<DowntimeBreaker>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    match breaker {
+        Some(breaker) => {
+            if !Downtime::is_valid(breaker.downtime) {
+                return Err(ContractError::InvalidDowntime { val: breaker.downtime });
+            }
+            if breaker.recovery_seconds < 0 {
+                return Err(ContractError::CustomError { val: "recovery_seconds can't be negative".to_string() });
+            }
+            DOWNTIME_BREAKER.save(deps.storage, &breaker)?;
+        },
+        None => DOWNTIME_BREAKER.remove(deps.storage),
+    }
 
-fn handle_instantiate_cw20_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
-    let mint_cache = MINT_CACHE.load(deps.storage)?;
-    let res = parse_reply_instantiate_data(msg).unwrap();
-    MINT_CONTRACTS.save(deps.storage,  &mint_cache.etf_name, &res.contract_address)?;
-    Ok(Response::default().add_attributes(vec![
-        attr("mint_contract_name", mint_cache.etf_name),
-        attr("mint_contract_address", res.contract_address)
-    ])) 
+    Ok(Response::new().add_attribute("method", "set_downtime_breaker"))
 }
 
-fn handle_swap_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+fn execute_set_etf_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    etf_name: String,
+    limits: Option<EtfLimits>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    set_etf_limits(deps, etf_name, limits)
+}
 
-    let result: String = parse_swap_reply(&msg);
+// owner-free setters below are also applied by passed holder proposals
+fn set_etf_limits(deps: DepsMut, etf_name: String, limits: Option<EtfLimits>) -> Result<Response, ContractError> {
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    match limits {
+        Some(limits) => {
+            if let (Some(min), Some(max)) = (limits.min_deposit, limits.max_deposit) {
+                if min > max {
+                    return Err(ContractError::CustomError { val: "min_deposit can't be above max_deposit".to_string() });
+                }
+            }
+            // make sure every allowed deposit can be valued, so limits never block a denom by accident
+            for denom in DEPOSIT_DENOMS {
+                value_in_quote_denom(deps.as_ref(), &limits, &[coin(1, denom)], None)?;
+            }
+            ETF_LIMITS.save(deps.storage, &etf_name, &limits)?;
+        },
+        None => ETF_LIMITS.remove(deps.storage, &etf_name),
+    }
 
-    // The result will be in a format: 9999ibc/sometxhash, so we need to split te initial numbers and the rest 
-    let (initial_amount_swapped, initial_denom_swapped) = split_result_no_regex(result.to_owned());
+    Ok(Response::new()
+        .add_attribute("method", "set_etf_limits")
+        .add_attribute("etf_name", etf_name))
+}
 
-    let cache = ETF_CACHE.load(deps.storage).unwrap();
+fn execute_set_batch_mode(
+    deps: DepsMut,
+    info: MessageInfo,
+    etf_name: String,
+    batch: Option<BatchConfig>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    set_batch_mode(deps, etf_name, batch)
+}
 
-    let swap_addr = SWAP_CONTRACT.load(deps.storage).unwrap();
-    let initial_deposit =  INITIAL_DEPOSIT_CACHE.load(deps.storage)?;
-    let (initial_deposit_token_out_denom, _) = get_initial_route_params(&initial_deposit.denom).unwrap();
-    let etf_swap_routes = cache.etf_swap_routes.to_owned();
-    
-    // validate if routes are passed properly before moving into execution
-    // TODO - looks like OSMOSIS does this on its side too, maybe there's no need to duplicate the process
-    for route in etf_swap_routes.clone().routes.into_iter() {
-        let res: QueryPoolResponse = deps.querier.query_wasm_smart(swap_addr.to_owned(),     
-            &osmo_swap::msg::QueryMsg::QueryPool{ pool_id: route.pool_id }).unwrap();
-
-        let pool: Pool = res.pool
-            .ok_or_else(|| StdError::NotFound {
-                kind: "pool".to_string(),
-            })?
-            .try_into() // convert `Any` to `osmosis_std::types::osmosis::gamm::v1beta1::Pool`
-            .map_err(|e: DecodeError| StdError::ParseErr {
-                target_type: "osmosis_std::types::osmosis::gamm::v1beta1::Pool".to_string(),
-                msg: e.to_string(),
-            })?;
-        
-        if !&pool.pool_assets.iter().any(|i| i.token.to_owned().unwrap().denom == route.token_out_denom) {
-            return Err(ContractError::PoolTokenNotFound { val: route.token_out_denom })
-        }
+fn set_batch_mode(deps: DepsMut, etf_name: String, batch: Option<BatchConfig>) -> Result<Response, ContractError> {
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
     }
-    let depo_key = (cache.sender.as_str(), cache.etf_swap_routes.name.as_str());
-    let mut submessages: std::vec::Vec<SubMsg<Empty>> = vec![];
-    let mut token_in_amnt_adder = Uint128::zero();
-    let init_amnt = Uint128::from(initial_amount_swapped.parse::<u128>().unwrap());
-    for (i, (route, ratio)) in etf_swap_routes.clone().routes.into_iter().zip(etf_swap_routes.ratios.into_iter()).enumerate() {
-        let token_in_amount: Uint128;
-        // no need to swap for denoms that have been received through initial swap
-        if route.token_out_denom == initial_deposit_token_out_denom.to_owned() {
-            token_in_amount = init_amnt.checked_multiply_ratio(ratio, 100u128).unwrap();
-            token_in_amnt_adder = token_in_amnt_adder.checked_add(token_in_amount).unwrap();
-            let new_ledger = update_ledger(&deps, depo_key, token_in_amount.to_string(), initial_deposit_token_out_denom.to_owned());
-            LEDGER.save(deps.storage, depo_key, &new_ledger)?;
-        } else {
-            if &i == &(etf_swap_routes.routes.len() - 1) {
-                token_in_amount = init_amnt.checked_sub(token_in_amnt_adder).unwrap();
-            } else {
-                token_in_amount = init_amnt.checked_multiply_ratio(ratio, 100u128).unwrap();
-                token_in_amnt_adder = token_in_amnt_adder.checked_add(token_in_amount).unwrap();
+    // queued requests were made against the current config
+    if batch_has_requests(deps.as_ref(), &etf_name) {
+        return Err(ContractError::BatchNotEmpty { val: etf_name });
+    }
+    if batch.is_some() && INDEX_METHODOLOGIES.has(deps.storage, &etf_name) {
+        return Err(ContractError::IndexedEtf { val: etf_name });
+    }
+    match batch {
+        Some(batch) => {
+            if batch.ratios.len() != batch.routes.len() {
+                return Err(ContractError::InvalidEntryParams{});
             }
-            
+            if batch.ratios.iter().sum::<Uint128>() != Uint128::from(100u128) {
+                return Err(ContractError::InvalidRatio{});
+            }
+            if !DEPOSIT_DENOMS.iter().any(|&i| i == batch.deposit_denom) {
+                return Err(ContractError::InvalidDepositDenom {val: batch.deposit_denom});
+            }
+            if batch.routes.iter().any(|route| pool_share_id(&route.token_out_denom).is_some()) {
+                return Err(ContractError::CustomError { val: "pool shares can't be bought in batches".to_string() });
+            }
+            let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+            validate_routes(deps.as_ref(), &swap_contract_addr, &batch.routes)?;
+
+            let (deposit_token_out_denom, pool_id) = get_initial_route_params(&batch.deposit_denom)?;
+            ETF_POOLS.save(deps.storage, &deposit_token_out_denom, &pool_id)?;
+            for route in batch.routes.iter() {
+                if !ETF_POOLS.has(deps.storage, &route.token_out_denom) {
+                    ETF_POOLS.save(deps.storage, &route.token_out_denom, &route.pool_id)?;
+                }
+            }
+            BATCH_CONFIGS.save(deps.storage, &etf_name, &batch)?;
+        },
+        None => BATCH_CONFIGS.remove(deps.storage, &etf_name),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "set_batch_mode")
+        .add_attribute("etf_name", etf_name))
+}
+
+fn execute_queue_deposit(deps: DepsMut, env: Env, info: MessageInfo, etf_name: String) -> Result<Response, ContractError> {
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    let config = BATCH_CONFIGS.may_load(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::BatchingDisabled { val: etf_name.to_owned() })?;
+    let deposit = info.funds.iter()
+        .find(|c| c.denom == config.deposit_denom && !c.amount.is_zero())
+        .cloned()
+        .ok_or(ContractError::DepositNotFound {})?;
+
+    let sender = info.sender.to_string();
+    ensure_within_etf_limits(deps.as_ref(), &env, &sender, &etf_name, &deposit)?;
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+    let (bank_msg, refund_msg) = create_msgs_forward_deposit(&swap_contract_addr, &info, &deposit);
+
+    let queued = BATCH_DEPOSITS.update(deps.storage, (&etf_name, &sender), |queued| -> StdResult<_> {
+        Ok(queued.unwrap_or_default().checked_add(deposit.amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(bank_msg)
+        .add_messages(refund_msg)
+        .add_attribute("method", "queue_deposit")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("queued", coin(queued.u128(), deposit.denom).to_string()))
+}
+
+fn execute_queue_redemption(mut deps: DepsMut, env: Env, info: MessageInfo, etf_name: String) -> Result<Response, ContractError> {
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    let config = BATCH_CONFIGS.may_load(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::BatchingDisabled { val: etf_name.to_owned() })?;
+    let sender = info.sender.to_string();
+    ensure_not_queued(deps.as_ref(), &sender, &etf_name)?;
+
+    // the tokens leave the ledger right away, so they can't be redeemed twice
+    let (tokens, shares, donation_msgs) = match NATIVE_DENOMS.may_load(deps.storage, &etf_name)? {
+        // native shares are queued with their part of every position of the etf
+        Some(denom) => {
+            let shares = returned_shares(&info, &denom)?;
+            if !has_outflow_capacity(deps.storage, &env, &etf_name, shares)? {
+                return Err(ContractError::OutflowLimitReached { val: etf_name });
+            }
+            (take_pro_rata(deps.storage, env.block.height, &etf_name, shares)?, shares, vec![])
+        },
+        None => {
+            if !LEDGER.has(deps.storage, (&sender, &etf_name)) {
+                return Err(ContractError::Unauthorized {});
+            }
+            let depo_coin = BALANCE.load(deps.storage, (&sender, &etf_name))?;
+            // proceeds are paid out in the batch's deposit denom
+            if depo_coin.denom != config.deposit_denom {
+                return Err(ContractError::InvalidDepositDenom { val: depo_coin.denom });
+            }
+            let shares = SHARES.load(deps.storage, (&etf_name, &sender))?;
+            if !has_outflow_capacity(deps.storage, &env, &etf_name, shares)? {
+                return Err(ContractError::OutflowLimitReached { val: etf_name });
+            }
+            let donation_msgs = take_donation(deps.branch(), &env, &sender, &etf_name, true)?;
+            let ledger = LEDGER.load(deps.storage, (&sender, &etf_name))?;
+            LEDGER.remove(deps.storage, (&sender, &etf_name));
+            INITIAL_SWAP.remove(deps.storage, (&sender, &etf_name));
+            remove_deposit(deps.storage, env.block.height, &sender, &etf_name)?;
+            (ledger, shares, donation_msgs)
+        },
+    };
+    if tokens.iter().any(|c| pool_share_id(&c.denom).is_some()) {
+        return Err(ContractError::CustomError { val: "positions holding pool shares have to be redeemed directly".to_string() });
+    }
+
+    let mut queued = BATCH_REDEMPTIONS.may_load(deps.storage, (&etf_name, &sender))?.unwrap_or_default();
+    for c in tokens.into_iter() {
+        add_to_coins(&mut queued, c)?;
+    }
+    BATCH_REDEMPTIONS.save(deps.storage, (&etf_name, &sender), &queued)?;
+    let burn_tokens = create_msg_burn_shares(deps.as_ref(), &etf_name, shares)?;
+
+    Ok(Response::new()
+        .add_message(burn_tokens)
+        .add_messages(donation_msgs)
+        .add_attribute("method", "queue_redemption")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("shares_burnt", shares))
+}
+
+// Depositors take over the tokens of redeemed positions and redeemers get the matching part of deposits,
+// both valued at spot prices. Only what's left on one of the sides gets swapped.
+fn execute_settle_batch(deps: DepsMut, env: Env, etf_name: String) -> Result<Response, ContractError> {
+    let config = BATCH_CONFIGS.may_load(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::BatchingDisabled { val: etf_name.to_owned() })?;
+    let deposits = BATCH_DEPOSITS
+        .prefix(&etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+    let redemptions = BATCH_REDEMPTIONS
+        .prefix(&etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Vec<Coin>)>>>()?;
+    if deposits.is_empty() && redemptions.is_empty() {
+        return Err(ContractError::EmptyBatch { val: etf_name });
+    }
+    if !deposits.is_empty() {
+        ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+    }
+    if !redemptions.is_empty() {
+        ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+    }
+
+    let deposits_total = deposits.iter()
+        .try_fold(Uint128::zero(), |total, (_, amount)| total.checked_add(*amount))
+        .map_err(StdError::from)?;
+    // anyone can settle, so redemptions are netted at TWAPs like nav orders rather than at prices of the block
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps.as_ref())?);
+    let mut redeemed_tokens: Vec<Coin> = vec![];
+    let mut redemption_values: Vec<(String, Uint128)> = vec![];
+    for (sender, tokens) in redemptions.into_iter() {
+        redemption_values.push((sender, value_ledger(deps.as_ref(), &tokens, &config.deposit_denom, Some(twap_start))?));
+        for c in tokens.into_iter() {
+            add_to_coins(&mut redeemed_tokens, c)?;
+        }
+    }
+    let redemptions_total = redemption_values.iter()
+        .try_fold(Uint128::zero(), |total, (_, value)| total.checked_add(*value))
+        .map_err(StdError::from)?;
+
+    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+    let (intermediate_denom, initial_pool_id) = get_initial_route_params(&config.deposit_denom)?;
+    let mut cache = BatchCache {
+        etf_name: etf_name.to_owned(),
+        config: config.to_owned(),
+        deposits_total,
+        redemptions: redemption_values,
+        buyers_tokens: vec![],
+        intermediate: Uint128::zero(),
+        proceeds: Uint128::zero(),
+        swaps_left: 0,
+    };
+    let mut submessages: Vec<SubMsg<Empty>> = vec![];
+
+    if deposits_total >= redemptions_total {
+        // redeemed tokens all go to depositors, the rest of deposits is swapped into the basket
+        cache.buyers_tokens = redeemed_tokens;
+        cache.proceeds = redemptions_total;
+        let to_buy = deposits_total - redemptions_total;
+        if !to_buy.is_zero() {
             let execute_message = create_msg_execute_swap(
-                swap_addr.to_string(), route.pool_id, route.token_out_denom.to_owned(), 
-                coin(token_in_amount.into(), initial_deposit_token_out_denom.to_owned())
-            );
-            if !ETF_POOLS.has(deps.storage, &route.token_out_denom) {
-                ETF_POOLS.save(deps.storage, &route.token_out_denom, &route.pool_id)?;
+                deps.storage, swap_addr.to_string(), initial_pool_id, intermediate_denom, coin(to_buy.u128(), config.deposit_denom.to_owned()))?;
+            submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_BATCH_BUY_REPLY_ID));
+        }
+    } else {
+        // depositors take their share of every redeemed token, the rest is sold
+        cache.proceeds = deposits_total;
+        for c in redeemed_tokens.into_iter() {
+            let to_buyers = c.amount.multiply_ratio(deposits_total, redemptions_total);
+            let to_sell = c.amount - to_buyers;
+            if !to_buyers.is_zero() {
+                cache.buyers_tokens.push(coin(to_buyers.u128(), c.denom.to_owned()));
+            }
+            if to_sell.is_zero() {
+                continue
+            }
+            if c.denom == intermediate_denom {
+                cache.intermediate = cache.intermediate.checked_add(to_sell).map_err(StdError::from)?;
+                continue
             }
-            submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_SWAPS_REPLY_ID));
+            let pool_id = ETF_POOLS.load(deps.storage, &c.denom)?;
+            let execute_message = create_msg_execute_swap(
+                deps.storage, swap_addr.to_string(), pool_id, intermediate_denom.to_owned(), coin(to_sell.u128(), c.denom))?;
+            submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_BATCH_SWAPS_REPLY_ID));
+        }
+        cache.swaps_left = submessages.len() as u64;
+        if submessages.is_empty() && !cache.intermediate.is_zero() {
+            submessages.push(create_submsg_batch_sell(deps.storage, &swap_addr, &cache)?);
         }
     }
 
-    INITIAL_SWAP.save(deps.storage, &cache.sender, &coin(initial_amount_swapped.parse::<u128>().unwrap(), 
-                    initial_denom_swapped.to_owned()))?;
+    let res = Response::new()
+        .add_attribute("method", "settle_batch")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("deposits_total", deposits_total)
+        .add_attribute("redemptions_value", redemptions_total);
+    if submessages.is_empty() {
+        return finalize_batch(deps, &env, cache, res);
+    }
+    BATCH_CACHE.save(deps.storage, &cache)?;
+    Ok(res.add_submessages(submessages))
+}
 
-    let mint_contract_addr = MINT_CONTRACTS.load(deps.storage, etf_swap_routes.name.as_str())?;
+fn execute_claim_batch(deps: DepsMut, info: MessageInfo, etf_name: String) -> Result<Response, ContractError> {
+    let sender = info.sender.to_string();
+    let claim = BATCH_CLAIMS.may_load(deps.storage, (&sender, &etf_name))?
+        .ok_or_else(|| ContractError::NotFound { val: format!("batch claim of {}", etf_name) })?;
+    BATCH_CLAIMS.remove(deps.storage, (&sender, &etf_name));
 
-    let manager_addr = MANAGER_CONTRACT.load(deps.storage)?;
-    let msg_execute_mint_tokens = create_msg_execute_mint_tokens(
-        manager_addr.to_string(),
-        initial_deposit.amount, 
-        mint_contract_addr.to_string());
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if !claim.shares.is_zero() {
+        msgs.extend(create_msgs_mint_shares(deps.as_ref(), &etf_name, &sender, claim.shares)?);
+    }
+    if !claim.proceeds.is_empty() {
+        let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+        msgs.push(create_msg_send_tokens_back(&swap_addr, claim.proceeds.to_owned(), sender.to_owned())?);
+    }
 
-    return Ok(Response::default()
-        .add_attributes(vec![
-            attr("initial_swap_received_amount", initial_amount_swapped),
-            attr("initial_swap_received_denom", initial_denom_swapped),
-            attr("initial_swap_sender", &cache.sender),
-            ])
-        .add_submessages(submessages)
-        .add_message(msg_execute_mint_tokens)
-        );            
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("method", "claim_batch")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("shares", claim.shares)
+        .add_attribute("proceeds", claim.proceeds.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",")))
+}
 
- }
+fn execute_create_dca_plan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_swap_routes: EtfSwapRoutes,
+    amount_per_period: Coin,
+    period: u64,
+    num_periods: u32,
+) -> Result<Response, ContractError> {
+    validate_etf_swap_routes(deps.as_ref(), &etf_swap_routes, &amount_per_period.denom)?;
+    if !DEPOSIT_DENOMS.iter().any(|&i| i == amount_per_period.denom) {
+        return Err(ContractError::InvalidDepositDenom {val: amount_per_period.denom});
+    }
+    if amount_per_period.amount.is_zero() || period == 0 || num_periods == 0 {
+        return Err(ContractError::InvalidEntryParams {});
+    }
+    let escrow = coin(
+        amount_per_period.amount.checked_mul(num_periods.into()).map_err(StdError::from)?.u128(),
+        amount_per_period.denom.to_owned());
+    if !info.funds.iter().any(|c| c.denom == escrow.denom && c.amount >= escrow.amount) {
+        return Err(ContractError::DepositNotFound {});
+    }
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+    validate_routes(deps.as_ref(), &swap_contract_addr, &etf_swap_routes.routes)?;
 
- fn handle_swaps_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let id = DCA_PLAN_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    DCA_PLAN_COUNT.save(deps.storage, &id)?;
+    let plan = DcaPlan {
+        id,
+        etf_swap_routes,
+        amount_per_period,
+        period,
+        periods_left: num_periods,
+        next_execution: env.block.time,
+        paused: false,
+    };
+    DCA_PLANS.save(deps.storage, (info.sender.as_str(), id), &plan)?;
+    schedule_dca_plan(deps.storage, info.sender.as_str(), &plan)?;
 
-    // Filter the result so that it returns single event value
-    let result: String = msg.result.clone()
-        .unwrap()
-        .events.iter()
-        .filter(|event| event.ty == "token_swapped" && event.attributes[4].key == "tokens_out")
-        .map(|p| p.attributes[4].value.clone())
-        .collect();
-    let (amount_swapped, denom_swapped) = split_result_no_regex(result.to_owned());
+    // escrow stays with the manager, anything on top of it goes back
+    let refund = excess_funds(&info.funds, &escrow);
+    let refund_msg = (!refund.is_empty())
+        .then(|| BankMsg::Send { to_address: info.sender.to_string(), amount: refund });
 
-    let cache = ETF_CACHE.load(deps.storage).unwrap();
-    let depo_key = (cache.sender.as_str(), cache.etf_swap_routes.name.as_str());
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_attribute("method", "create_dca_plan")
+        .add_attribute("plan_id", id.to_string())
+        .add_attribute("escrow", escrow.to_string()))
+}
 
-    let mut new_ledger: Vec<Coin> = vec![];
-    let mut found = false;
-    if LEDGER.has(deps.storage, depo_key.clone()) {
-        let curr_ledger = LEDGER.load(deps.storage, depo_key).unwrap();  
-        new_ledger = curr_ledger.clone();
-        for (i, c) in new_ledger.clone().into_iter().enumerate() {
-            if &c.denom == &denom_swapped {
-                let new_val = Uint128::from(amount_swapped.parse::<u128>().unwrap());
-                new_ledger[i] = coin(c.amount.checked_add(new_val).unwrap().u128(), c.denom);
-                found = true;
-                break
-            } 
+fn execute_due(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let due = DCA_SCHEDULE
+        .range(deps.storage, None, Some(Bound::inclusive((env.block.time.nanos(), u64::MAX))), Order::Ascending)
+        .take(MAX_DUE_PURCHASES)
+        .collect::<StdResult<Vec<((u64, u64), String)>>>()?;
+
+    let mut submessages: Vec<SubMsg<Empty>> = vec![];
+    let mut purchases: Vec<DcaPurchaseCache> = vec![];
+    let mut skipped: Vec<String> = vec![];
+    for ((_, id), owner) in due.into_iter() {
+        let mut plan = DCA_PLANS.load(deps.storage, (&owner, id))?;
+        unschedule_dca_plan(deps.storage, &plan);
+        // a purchase that would fail on its own skips its period, so that it doesn't hold up the plans behind it
+        if ensure_within_etf_limits(deps.as_ref(), &env, &owner, &plan.etf_swap_routes.name, &plan.amount_per_period).is_err()
+            || ensure_not_wound_down(deps.as_ref(), &plan.etf_swap_routes.name).is_err() {
+            plan.next_execution = plan.next_execution.plus_seconds(plan.period);
+            DCA_PLANS.save(deps.storage, (&owner, id), &plan)?;
+            schedule_dca_plan(deps.storage, &owner, &plan)?;
+            skipped.push(id.to_string());
+            continue
+        }
+        let bounty = checked_mul_dec(plan.amount_per_period.amount, Decimal::permille(DCA_KEEPER_BOUNTY_PERMILLE))?;
+        let deposit = coin((plan.amount_per_period.amount - bounty).u128(), plan.amount_per_period.denom.to_owned());
+        let execute_message = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::DcaPurchase {
+                owner: owner.to_owned(),
+                etf_swap_routes: plan.etf_swap_routes.to_owned(),
+                deposit: deposit.to_owned(),
+            })?,
+        };
+        // failed purchases are handled in the reply instead of reverting the others
+        submessages.push(SubMsg::reply_always(execute_message, DCA_PURCHASE_REPLY_ID));
+        // the bounty is only paid once the purchase went through
+        purchases.push(DcaPurchaseCache {
+            owner: owner.to_owned(),
+            plan_id: id,
+            bounty: coin(bounty.u128(), deposit.denom.to_owned()),
+            deposit,
+            keeper: info.sender.to_string(),
+        });
+
+        plan.periods_left -= 1;
+        plan.next_execution = plan.next_execution.plus_seconds(plan.period);
+        if plan.periods_left == 0 {
+            DCA_PLANS.remove(deps.storage, (&owner, id));
+        } else {
+            DCA_PLANS.save(deps.storage, (&owner, id), &plan)?;
+            schedule_dca_plan(deps.storage, &owner, &plan)?;
         }
     }
-    if !found {
-        new_ledger.push(coin(amount_swapped.parse::<u128>().unwrap(), denom_swapped.to_owned()));
+
+    let executed = submessages.len();
+    if !purchases.is_empty() {
+        DCA_PURCHASE_CACHE.save(deps.storage, &purchases)?;
     }
-    LEDGER.save(deps.storage, depo_key, &new_ledger)?;
-    
-    Ok(Response::default()
-        .add_attribute("swap_received_amount", amount_swapped)
-        .add_attribute("swap_received_denom", denom_swapped)
-    )
- }
 
- fn handle_revert_swaps(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
-    let (amount_swapped_string, denom_swapped) = split_result_no_regex(parse_swap_reply(&msg));
-    let amount_swapped = amount_swapped_string.parse::<u128>().unwrap();
-    
-    // TODO - convert this part to a function
-    let mut updated: Uint128 = Uint128::zero();
-
-    if  REVERT_SWAP_CACHE.may_load(deps.storage).unwrap() != None {
-        REVERT_SWAP_CACHE.update(deps.storage, |mut rev_coin| -> Result<_, ContractError> {
-            updated = rev_coin.coin_to_revert.amount.checked_add(amount_swapped.into()).unwrap();
-            rev_coin.coin_to_revert.amount = updated;
-            Ok(rev_coin)
-        })?;        
-    } else {
-        REVERT_SWAP_CACHE.save(deps.storage, &SwapCache{
-         coin_to_revert: coin(amount_swapped, denom_swapped.to_owned()),
-        }
-        )?
+    Ok(Response::new()
+        .add_submessages(submessages)
+        .add_attribute("method", "execute_due")
+        .add_attribute("executed", executed.to_string())
+        .add_attribute("skipped", skipped.join(",")))
+}
+
+// only plans that can be executed are scheduled
+fn schedule_dca_plan(storage: &mut dyn Storage, owner: &str, plan: &DcaPlan) -> StdResult<()> {
+    if plan.paused || plan.periods_left == 0 {
+        return Ok(());
     }
+    DCA_SCHEDULE.save(storage, (plan.next_execution.nanos(), plan.id), &owner.to_string())
+}
 
-    Ok(Response::default()
-    .add_attributes([
-        attr("revert_swap_received_amount", amount_swapped_string),
-        attr("revert_swap_received_denom", denom_swapped),
-        attr("revert_swap_amount_total", updated),]
-        )
-    )
- }
- 
- fn handle_conjunction_swaps(deps: DepsMut, msg_: Reply) -> Result<Response, ContractError>  {
-    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+fn unschedule_dca_plan(storage: &mut dyn Storage, plan: &DcaPlan) {
+    DCA_SCHEDULE.remove(storage, (plan.next_execution.nanos(), plan.id));
+}
 
-    let etf_name_cache = ETF_NAME_CACHE.load(deps.storage)?;
+// every purchase runs in its own message, so that swap caches aren't shared between plans
+fn execute_dca_purchase(deps: DepsMut, env: Env, owner: String, etf_swap_routes: EtfSwapRoutes, deposit: Coin) -> Result<Response, ContractError> {
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+    let forward = BankMsg::Send { to_address: swap_contract_addr.to_string(), amount: vec![deposit.to_owned()] };
+    let etf_name = etf_swap_routes.name.to_owned();
+    let submessage = create_submsg_swap_exact_amount_in(deps, &env, &owner, etf_swap_routes, deposit.to_owned())?;
+
+    Ok(Response::new()
+        .add_message(forward)
+        .add_submessage(submessage)
+        .add_attribute("method", "dca_purchase")
+        .add_attribute("owner", owner)
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("deposit", deposit.to_string()))
+}
+
+fn execute_set_dca_plan_paused(deps: DepsMut, info: MessageInfo, plan_id: u64, paused: bool) -> Result<Response, ContractError> {
+    let key = (info.sender.as_str(), plan_id);
+    let mut plan = DCA_PLANS.may_load(deps.storage, key)?
+        .ok_or(ContractError::DcaPlanNotFound { val: plan_id })?;
+    unschedule_dca_plan(deps.storage, &plan);
+    plan.paused = paused;
+    DCA_PLANS.save(deps.storage, key, &plan)?;
+    schedule_dca_plan(deps.storage, key.0, &plan)?;
+
+    Ok(Response::new()
+        .add_attribute("method", if paused { "pause_dca_plan" } else { "resume_dca_plan" })
+        .add_attribute("plan_id", plan_id.to_string()))
+}
+
+fn execute_cancel_dca_plan(deps: DepsMut, info: MessageInfo, plan_id: u64) -> Result<Response, ContractError> {
+    let key = (info.sender.as_str(), plan_id);
+    let plan = DCA_PLANS.may_load(deps.storage, key)?
+        .ok_or(ContractError::DcaPlanNotFound { val: plan_id })?;
+    DCA_PLANS.remove(deps.storage, key);
+    unschedule_dca_plan(deps.storage, &plan);
+
+    let refund = coin(
+        plan.amount_per_period.amount.checked_mul(plan.periods_left.into()).map_err(StdError::from)?.u128(),
+        plan.amount_per_period.denom);
+    let refund_msg = (!refund.amount.is_zero())
+        .then(|| BankMsg::Send { to_address: info.sender.to_string(), amount: vec![refund.to_owned()] });
+
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_attribute("method", "cancel_dca_plan")
+        .add_attribute("plan_id", plan_id.to_string())
+        .add_attribute("refund", refund.to_string()))
+}
+
+fn execute_set_nav_twap_window(deps: DepsMut, info: MessageInfo, seconds: u64) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    if seconds == 0 {
+        return Err(ContractError::InvalidEntryParams {});
+    }
+    NAV_TWAP_WINDOW.save(deps.storage, &seconds)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_nav_twap_window")
+        .add_attribute("seconds", seconds.to_string()))
+}
+
+fn execute_set_pair_route(deps: DepsMut, info: MessageInfo, pool_id: u64, pair: Option<String>) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    let res = Response::new()
+        .add_attribute("method", "set_pair_route")
+        .add_attribute("pool_id", pool_id.to_string());
+    match pair {
+        Some(pair) => {
+            let pair = deps.api.addr_validate(&pair)?;
+            PAIR_ROUTES.save(deps.storage, pool_id, &pair)?;
+            Ok(res.add_attribute("pair", pair))
+        },
+        None => {
+            PAIR_ROUTES.remove(deps.storage, pool_id);
+            Ok(res)
+        },
+    }
+}
+
+fn execute_set_outflow_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    etf_name: String,
+    limit: Option<OutflowLimit>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    set_outflow_limit(deps, etf_name, limit)
+}
+
+// the current window is dropped, the next redemption starts one with the new limit
+fn set_outflow_limit(deps: DepsMut, etf_name: String, limit: Option<OutflowLimit>) -> Result<Response, ContractError> {
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    match limit {
+        Some(limit) => {
+            if limit.max_percent.is_zero() || limit.max_percent > Decimal::one() {
+                return Err(ContractError::CustomError { val: "max_percent has to be above 0 and up to 1".to_string() });
+            }
+            if limit.window == 0 {
+                return Err(ContractError::CustomError { val: "window can't be 0".to_string() });
+            }
+            OUTFLOW_LIMITS.save(deps.storage, &etf_name, &limit)?;
+        },
+        None => OUTFLOW_LIMITS.remove(deps.storage, &etf_name),
+    }
+    OUTFLOWS.remove(deps.storage, &etf_name);
+
+    Ok(Response::new()
+        .add_attribute("method", "set_outflow_limit")
+        .add_attribute("etf_name", etf_name))
+}
+
+fn execute_place_nav_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_name: String,
+    kind: NavOrderKind,
+    nav_per_share: Decimal,
+    expires_at: Timestamp,
+) -> Result<Response, ContractError> {
+    if expires_at <= env.block.time {
+        return Err(ContractError::CustomError { val: "expires_at has to be in the future".to_string() });
+    }
+    if nav_per_share.is_zero() {
+        return Err(ContractError::CustomError { val: "nav_per_share can't be zero".to_string() });
+    }
+    let sender = info.sender.to_string();
+    let (denom, escrow, refund_msg) = match &kind {
+        NavOrderKind::Buy { deposit, routes, ratios } => {
+            validate_etf_swap_routes(deps.as_ref(), &EtfSwapRoutes {
+                name: etf_name.to_owned(), routes: routes.to_owned(), ratios: ratios.to_owned() }, &deposit.denom)?;
+            if !DEPOSIT_DENOMS.iter().any(|&i| i == deposit.denom) {
+                return Err(ContractError::InvalidDepositDenom {val: deposit.denom.to_owned()});
+            }
+            if !info.funds.iter().any(|c| c.denom == deposit.denom && c.amount >= deposit.amount) {
+                return Err(ContractError::DepositNotFound {});
+            }
+            let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+            validate_routes(deps.as_ref(), &swap_contract_addr, routes)?;
+            let refund = excess_funds(&info.funds, deposit);
+            let refund_msg = (!refund.is_empty())
+                .then(|| BankMsg::Send { to_address: sender.to_owned(), amount: refund });
+            (deposit.denom.to_owned(), vec![deposit.to_owned()], refund_msg)
+        },
+        NavOrderKind::Redeem {} => match NATIVE_DENOMS.may_load(deps.storage, &etf_name)? {
+            // any amount of native shares can be escrowed, shares are counted in uosmo
+            Some(denom) => {
+                let shares = returned_shares(&info, &denom)?;
+                ("uosmo".to_string(), vec![coin(shares.u128(), denom)], None)
+            },
+            None => {
+                let depo_coin = BALANCE.may_load(deps.storage, (&sender, &etf_name))?
+                    .ok_or(ContractError::Unauthorized {})?;
+                (depo_coin.denom, vec![], None)
+            },
+        },
+    };
+
+    let id = NAV_ORDER_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    NAV_ORDER_COUNT.save(deps.storage, &id)?;
+    NAV_ORDERS.save(deps.storage, (&sender, id), &NavOrder {
+        id,
+        etf_name,
+        kind,
+        nav_per_share,
+        denom,
+        expires_at,
+        escrow,
+    })?;
+
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_attribute("method", "place_nav_order")
+        .add_attribute("order_id", id.to_string()))
+}
+
+fn execute_nav_order(deps: DepsMut, env: Env, owner: String, order_id: u64) -> Result<Response, ContractError> {
+    let order = NAV_ORDERS.may_load(deps.storage, (&owner, order_id))?
+        .ok_or(ContractError::NavOrderNotFound { val: order_id })?;
+    if order.expires_at <= env.block.time {
+        return Err(ContractError::NavOrderExpired { val: order_id });
+    }
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps.as_ref())?);
+    let nav = etf_nav_per_share(deps.as_ref(), &order.etf_name, &order.denom, Some(twap_start))?;
+    NAV_ORDERS.remove(deps.storage, (&owner, order_id));
+
+    let res = match order.kind {
+        NavOrderKind::Buy { deposit, routes, ratios } => {
+            ensure_not_paused(deps.as_ref(), Operation::Buys)?;
+            if nav > order.nav_per_share {
+                return Err(ContractError::NavConditionNotMet { nav, limit: order.nav_per_share });
+            }
+            let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+            let forward = BankMsg::Send { to_address: swap_contract_addr.to_string(), amount: vec![deposit.to_owned()] };
+            let etf_swap_routes = EtfSwapRoutes { name: order.etf_name, routes, ratios };
+            let submessage = create_submsg_swap_exact_amount_in(deps, &env, &owner, etf_swap_routes, deposit)?;
+            Response::new()
+                .add_message(forward)
+                .add_submessage(submessage)
+        },
+        NavOrderKind::Redeem {} => {
+            ensure_not_paused(deps.as_ref(), Operation::Redemptions)?;
+            if nav < order.nav_per_share {
+                return Err(ContractError::NavConditionNotMet { nav, limit: order.nav_per_share });
+            }
+            // escrowed native shares are redeemed as they are, cw20 positions as a whole
+            let shares = match order.escrow.first() {
+                Some(escrow) => escrow.amount,
+                None => SHARES.may_load(deps.storage, (&order.etf_name, &owner))?
+                    .ok_or(ContractError::Unauthorized {})?,
+            };
+            ensure_not_queued(deps.as_ref(), &owner, &order.etf_name)?;
+            if !has_outflow_capacity(deps.storage, &env, &order.etf_name, shares)? {
+                return Err(ContractError::OutflowLimitReached { val: order.etf_name });
+            }
+            match order.escrow.is_empty() {
+                true => redeem_position(deps, env, owner.to_owned(), order.etf_name)?,
+                false => redeem_native_shares(deps, env, owner.to_owned(), order.etf_name, shares)?,
+            }
+        },
+    };
+
+    Ok(res
+        .add_attribute("method", "execute_nav_order")
+        .add_attribute("owner", owner)
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("nav_per_share", nav.to_string()))
+}
+
+fn execute_cancel_nav_order(deps: DepsMut, info: MessageInfo, order_id: u64) -> Result<Response, ContractError> {
+    let key = (info.sender.as_str(), order_id);
+    let order = NAV_ORDERS.may_load(deps.storage, key)?
+        .ok_or(ContractError::NavOrderNotFound { val: order_id })?;
+    NAV_ORDERS.remove(deps.storage, key);
+
+    let refund_msg = (!order.escrow.is_empty())
+        .then(|| BankMsg::Send { to_address: info.sender.to_string(), amount: order.escrow });
+
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_attribute("method", "cancel_nav_order")
+        .add_attribute("order_id", order_id.to_string()))
+}
+
+fn execute_set_donation_beneficiaries(
+    deps: DepsMut,
+    info: MessageInfo,
+    etf_name: String,
+    beneficiaries: Vec<String>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    set_donation_beneficiaries(deps, etf_name, beneficiaries)
+}
+
+fn set_donation_beneficiaries(deps: DepsMut, etf_name: String, beneficiaries: Vec<String>) -> Result<Response, ContractError> {
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    let beneficiaries = beneficiaries.iter()
+        .map(|addr| Ok(deps.api.addr_validate(addr)?.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+    DONATION_BENEFICIARIES.save(deps.storage, &etf_name, &beneficiaries)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_donation_beneficiaries")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("beneficiaries", beneficiaries.join(",")))
+}
+
+fn execute_set_donation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_name: String,
+    donation: Option<DonationSettings>
+) -> Result<Response, ContractError> {
+    let holder = info.sender.to_string();
+    match donation {
+        Some(settings) => {
+            if settings.percent.is_zero() || settings.percent > Decimal::one() {
+                return Err(ContractError::CustomError { val: "percent has to be above 0 and up to 1".to_string() });
+            }
+            let beneficiary = deps.api.addr_validate(&settings.beneficiary)?.to_string();
+            if !DONATION_BENEFICIARIES.may_load(deps.storage, &etf_name)?.unwrap_or_default().contains(&beneficiary) {
+                return Err(ContractError::CustomError { val: format!("{} is not a beneficiary of etf {}", beneficiary, etf_name) });
+            }
+            // growth made before donating was enabled isn't donated
+            let high_water_mark = match DONATIONS.may_load(deps.storage, (&holder, &etf_name))? {
+                Some(donation) => donation.high_water_mark,
+                None => position_value(deps.as_ref(), &env, &holder, &etf_name)?,
+            };
+            DONATIONS.save(deps.storage, (&holder, &etf_name), &Donation {
+                beneficiary,
+                percent: settings.percent,
+                high_water_mark,
+            })?;
+        },
+        None => DONATIONS.remove(deps.storage, (&holder, &etf_name)),
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "set_donation")
+        .add_attribute("etf_name", etf_name))
+}
+
+fn execute_harvest_donation(deps: DepsMut, env: Env, holder: String, etf_name: String) -> Result<Response, ContractError> {
+    if !DONATIONS.has(deps.storage, (&holder, &etf_name)) {
+        return Err(ContractError::NotFound { val: format!("donation of {} to etf {}", holder, etf_name) });
+    }
+    ensure_not_wound_down(deps.as_ref(), &etf_name)?;
+    let donation_msgs = take_donation(deps, &env, &holder, &etf_name, false)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "harvest_donation")
+        .add_attribute("holder", holder)
+        .add_attribute("donated", (!donation_msgs.is_empty()).to_string())
+        .add_messages(donation_msgs))
+}
+
+fn execute_set_gov_config(deps: DepsMut, info: MessageInfo, config: Option<GovConfig>) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    match config {
+        Some(config) => {
+            if config.quorum.is_zero() || config.quorum > Decimal::one() {
+                return Err(ContractError::CustomError { val: "quorum has to be above 0 and up to 1".to_string() });
+            }
+            if config.threshold.is_zero() || config.threshold > Decimal::one() {
+                return Err(ContractError::CustomError { val: "threshold has to be above 0 and up to 1".to_string() });
+            }
+            if config.voting_period == 0 {
+                return Err(ContractError::CustomError { val: "voting_period can't be 0".to_string() });
+            }
+            GOV_CONFIG.save(deps.storage, &config)?;
+        },
+        None => GOV_CONFIG.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("method", "set_gov_config"))
+}
+
+fn execute_propose(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_name: String,
+    action: EtfAdminMsg
+) -> Result<Response, ContractError> {
+    let config = GOV_CONFIG.may_load(deps.storage)?.ok_or(ContractError::GovernanceDisabled {})?;
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    let proposer = info.sender.to_string();
+    if holder_shares(deps.as_ref(), &etf_name, &proposer, None)?.is_zero() {
+        return Err(ContractError::NoVotingPower { val: proposer });
+    }
+
+    let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    PROPOSAL_COUNT.save(deps.storage, &id)?;
+    // snapshot at the start of this block, shares bought later don't count
+    PROPOSALS.save(deps.storage, id, &Proposal {
+        id,
+        etf_name: etf_name.to_owned(),
+        proposer,
+        action,
+        snapshot_height: env.block.height,
+        voting_end: env.block.time.plus_seconds(config.voting_period),
+        config,
+        yes: Uint128::zero(),
+        no: Uint128::zero(),
+        abstain: Uint128::zero(),
+        status: ProposalStatus::Open,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("proposal_id", id.to_string()))
+}
+
+fn execute_vote(deps: DepsMut, env: Env, info: MessageInfo, proposal_id: u64, vote: VoteOption) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS.may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalNotFound { val: proposal_id })?;
+    if proposal.status != ProposalStatus::Open || env.block.time >= proposal.voting_end {
+        return Err(ContractError::VotingClosed { val: proposal_id });
+    }
+    let voter = info.sender.to_string();
+    if VOTES.has(deps.storage, (proposal_id, &voter)) {
+        return Err(ContractError::AlreadyVoted { val: voter });
+    }
+    // native shares change hands without the manager knowing, they vote with what's escrowed until the
+    // proposal is executed so that the same shares can't vote twice. Cw20 shares stay with the manager
+    let (weight, escrow) = match NATIVE_DENOMS.may_load(deps.storage, &proposal.etf_name)? {
+        Some(denom) => {
+            let shares = info.funds.iter().find(|c| c.denom == denom).map(|c| c.amount).unwrap_or_default();
+            (shares, Some(coin(shares.u128(), denom)))
+        },
+        None => (holder_shares(deps.as_ref(), &proposal.etf_name, &voter, Some(proposal.snapshot_height))?, None),
+    };
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower { val: voter });
+    }
+
+    match vote {
+        VoteOption::Yes => proposal.yes = proposal.yes.checked_add(weight).map_err(StdError::from)?,
+        VoteOption::No => proposal.no = proposal.no.checked_add(weight).map_err(StdError::from)?,
+        VoteOption::Abstain => proposal.abstain = proposal.abstain.checked_add(weight).map_err(StdError::from)?,
+    }
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    VOTES.save(deps.storage, (proposal_id, &voter), &Ballot { vote, weight, escrow })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "vote")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("voter", voter)
+        .add_attribute("weight", weight))
+}
+
+fn execute_proposal(mut deps: DepsMut, env: Env, proposal_id: u64) -> Result<Response, ContractError> {
+    if GOV_CONFIG.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::GovernanceDisabled {});
+    }
+    let mut proposal = PROPOSALS.may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalNotFound { val: proposal_id })?;
+    if proposal.status != ProposalStatus::Open {
+        return Err(ContractError::VotingClosed { val: proposal_id });
+    }
+    if env.block.time < proposal.voting_end {
+        return Err(ContractError::VotingOpen { val: proposal_id });
+    }
+
+    let total_shares = TOTAL_SHARES
+        .may_load_at_height(deps.storage, &proposal.etf_name, proposal.snapshot_height)?
+        .unwrap_or_default();
+    let yes_no = proposal.yes.checked_add(proposal.no).map_err(StdError::from)?;
+    let voted = yes_no.checked_add(proposal.abstain).map_err(StdError::from)?;
+    let quorum_reached = !total_shares.is_zero()
+        && Decimal::from_ratio(voted, total_shares) >= proposal.config.quorum;
+    let passed = quorum_reached
+        && !proposal.yes.is_zero()
+        && Decimal::from_ratio(proposal.yes, yes_no) >= proposal.config.threshold;
+
+    let mut res = Response::new();
+    if passed {
+        // applied directly rather than dispatched, the owner checks of admin messages don't apply here
+        let etf_name = proposal.etf_name.to_owned();
+        let action_res = match proposal.action.to_owned() {
+            EtfAdminMsg::SetEtfLimits { limits } => set_etf_limits(deps.branch(), etf_name, limits)?,
+            EtfAdminMsg::SetBatchMode { batch } => set_batch_mode(deps.branch(), etf_name, batch)?,
+            EtfAdminMsg::SetDonationBeneficiaries { beneficiaries }
+                => set_donation_beneficiaries(deps.branch(), etf_name, beneficiaries)?,
+            EtfAdminMsg::SetIndexMethodology { methodology }
+                => set_index_methodology(deps.branch(), &env, etf_name, methodology)?,
+            EtfAdminMsg::SetOutflowLimit { limit } => set_outflow_limit(deps.branch(), etf_name, limit)?,
+        };
+        res = res.add_submessages(action_res.messages);
+        proposal.status = ProposalStatus::Executed;
+    } else {
+        proposal.status = ProposalStatus::Rejected;
+    }
+    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+
+    // escrowed native shares go back to their voters
+    let refunds = VOTES
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((voter, Ballot { escrow: Some(escrow), .. })) => Some(Ok(BankMsg::Send { to_address: voter, amount: vec![escrow] })),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<Vec<BankMsg>>>()?;
+
+    Ok(res
+        .add_messages(refunds)
+        .add_attribute("method", "execute_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("passed", passed.to_string()))
+}
+
+fn execute_set_index_methodology(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    etf_name: String,
+    methodology: Option<IndexMethodology>
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.as_ref(), &info)?;
+    set_index_methodology(deps, &env, etf_name, methodology)
+}
+
+fn set_index_methodology(
+    deps: DepsMut,
+    env: &Env,
+    etf_name: String,
+    methodology: Option<IndexMethodology>
+) -> Result<Response, ContractError> {
+    if !etf_exists(deps.as_ref(), &etf_name) {
+        return Err(ContractError::MintContractNotFound{val: etf_name});
+    }
+    let methodology = match methodology {
+        Some(methodology) => methodology,
+        None => {
+            INDEX_METHODOLOGIES.remove(deps.storage, &etf_name);
+            INDEX_WEIGHTS.remove(deps.storage, &etf_name);
+            return Ok(Response::new()
+                .add_attribute("method", "set_index_methodology")
+                .add_attribute("etf_name", etf_name));
+        },
+    };
+    if BATCH_CONFIGS.has(deps.storage, &etf_name) {
+        return Err(ContractError::CustomError { val: "batched etfs can't follow an index methodology".to_string() });
+    }
+    if methodology.components.is_empty() || methodology.rebalance_period == 0 {
+        return Err(ContractError::InvalidEntryParams {});
+    }
+    if !DEPOSIT_DENOMS.iter().any(|&i| i == methodology.deposit_denom) {
+        return Err(ContractError::InvalidDepositDenom {val: methodology.deposit_denom});
+    }
+    let mut caps_sum = Decimal::zero();
+    for (i, component) in methodology.components.iter().enumerate() {
+        let denom = &component.route.token_out_denom;
+        if methodology.components[..i].iter().any(|c| &c.route.token_out_denom == denom) {
+            return Err(ContractError::CustomError { val: format!("{} is listed more than once", denom) });
+        }
+        if pool_share_id(denom).is_some() {
+            return Err(ContractError::CustomError { val: "pool shares can't be index components".to_string() });
+        }
+        let cap = component.cap.unwrap_or_else(Decimal::one);
+        if cap.is_zero() || cap > Decimal::one() {
+            return Err(ContractError::CustomError { val: "cap has to be above 0 and up to 1".to_string() });
+        }
+        caps_sum += cap;
+    }
+    if caps_sum < Decimal::one() {
+        return Err(ContractError::CustomError { val: "caps have to add up to at least 1".to_string() });
+    }
+    let routes: Vec<Route> = methodology.components.iter().map(|c| c.route.to_owned()).collect();
+    let swap_contract_addr = SWAP_CONTRACT.load(deps.storage)?;
+    validate_routes(deps.as_ref(), &swap_contract_addr, &routes)?;
+    // components are valued through their route pools
+    let (deposit_token_out_denom, pool_id) = get_initial_route_params(&methodology.deposit_denom)?;
+    ETF_POOLS.save(deps.storage, &deposit_token_out_denom, &pool_id)?;
+    for route in routes.iter() {
+        if !ETF_POOLS.has(deps.storage, &route.token_out_denom) {
+            ETF_POOLS.save(deps.storage, &route.token_out_denom, &route.pool_id)?;
+        }
+    }
+
+    let ratios = compute_index_ratios(deps.as_ref(), env, &methodology)?;
+    INDEX_WEIGHTS.save(deps.storage, &etf_name, &IndexWeights {
+        ratios: ratios.to_owned(),
+        next_rebalance: env.block.time.plus_seconds(methodology.rebalance_period),
+    })?;
+    INDEX_METHODOLOGIES.save(deps.storage, &etf_name, &methodology)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_index_methodology")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("ratios", join_ratios(&ratios)))
+}
+
+// only new purchases follow the recalculated weights, existing positions keep their tokens
+fn execute_rebalance(deps: DepsMut, env: Env, etf_name: String) -> Result<Response, ContractError> {
+    let methodology = INDEX_METHODOLOGIES.may_load(deps.storage, &etf_name)?
+        .ok_or_else(|| ContractError::NotFound { val: format!("index methodology of etf {}", etf_name) })?;
+    let weights = INDEX_WEIGHTS.load(deps.storage, &etf_name)?;
+    if env.block.time < weights.next_rebalance {
+        return Err(ContractError::RebalanceNotDue { val: etf_name, next: weights.next_rebalance });
+    }
+    let ratios = compute_index_ratios(deps.as_ref(), &env, &methodology)?;
+    INDEX_WEIGHTS.save(deps.storage, &etf_name, &IndexWeights {
+        ratios: ratios.to_owned(),
+        next_rebalance: env.block.time.plus_seconds(methodology.rebalance_period),
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "rebalance")
+        .add_attribute("etf_name", etf_name)
+        .add_attribute("ratios", join_ratios(&ratios)))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck { channel, sequence, ack: _, success })
+            => handle_ibc_refund_outcome(deps, channel, sequence, success),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout { channel, sequence })
+            => handle_ibc_refund_outcome(deps, channel, sequence, false),
+    }
+}
+
+// failed and timed out refunds are sent to the recovery address on this chain instead
+fn handle_ibc_refund_outcome(deps: DepsMut, channel: String, sequence: u64, delivered: bool) -> Result<Response, ContractError> {
+    let refund = IBC_REFUNDS.may_load(deps.storage, (&channel, sequence))?
+        .ok_or_else(|| ContractError::IbcRefundNotFound { channel: channel.to_owned(), sequence })?;
+    IBC_REFUNDS.remove(deps.storage, (&channel, sequence));
+
+    let res = Response::new()
+        .add_attribute("method", "ibc_lifecycle_complete")
+        .add_attribute("channel", channel)
+        .add_attribute("sequence", sequence.to_string())
+        .add_attribute("delivered", delivered.to_string());
+    if delivered {
+        return Ok(res);
+    }
+    Ok(res
+        .add_message(BankMsg::Send { to_address: refund.recovery_addr.to_owned(), amount: vec![refund.amount] })
+        .add_attribute("recovered_to", refund.recovery_addr))
+}
+
+// ----------------------------------- REPLY HANDLING
+// ##############################################################################
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_SWAP_REPLY_ID => handle_instantiate_swap_reply(deps, msg),
+        INSTANTIATE_CW20_REPLY_ID => handle_instantiate_cw20_reply(deps, msg),
+        EXECUTE_SWAP_REPLY_ID => handle_swap_reply(deps, msg),
+        EXECUTE_SWAPS_REPLY_ID => handle_swaps_reply(deps, msg),
+        EXECUTE_REVERT_SWAPS_REPLY_ID => handle_revert_swaps(deps, msg),
+        EXECUTE_CONJUNCTION_SWAPS_REPLY_ID => handle_conjunction_swaps(deps, env, msg),
+        EXECUTE_REDEEM_SWAP_REPLY_ID => handle_redeem_swap(deps, msg),
+        EXECUTE_SWAP_EXACT_OUT_REPLY_ID => handle_swap_exact_out_reply(deps, env, msg),
+        EXECUTE_JOIN_POOL_REPLY_ID => handle_join_pool_reply(deps, msg),
+        EXECUTE_EXIT_POOL_REPLY_ID => handle_exit_pool_reply(deps, msg),
+        EXECUTE_BATCH_BUY_REPLY_ID => handle_batch_buy_reply(deps, env, msg),
+        EXECUTE_BATCH_SWAPS_REPLY_ID => handle_batch_swaps_reply(deps, env, msg),
+        EXECUTE_BATCH_SELL_REPLY_ID => handle_batch_sell_reply(deps, env, msg),
+        IBC_REFUND_REPLY_ID => handle_ibc_refund_reply(deps, msg),
+        DCA_PURCHASE_REPLY_ID => handle_dca_purchase_reply(deps, msg),
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
+    }
+}
+
+fn handle_instantiate_swap_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(msg).unwrap();
+    let checked: Addr = deps.api.addr_validate(&res.contract_address)?;
+    SWAP_CONTRACT.save(deps.storage,&checked)?;
+    Ok(Response::default())
+}
+
+fn handle_instantiate_cw20_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let mint_cache = MINT_CACHE.load(deps.storage)?;
+    let res = parse_reply_instantiate_data(msg).unwrap();
+    MINT_CONTRACTS.save(deps.storage,  &mint_cache.etf_name, &res.contract_address)?;
+    Ok(Response::default().add_attributes(vec![
+        attr("mint_contract_name", mint_cache.etf_name),
+        attr("mint_contract_address", res.contract_address)
+    ])) 
+}
+
+fn handle_swap_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+
+    let result: String = parse_swap_reply(&msg);
+
+    // The result will be in a format: 9999ibc/sometxhash, so we need to split te initial numbers and the rest 
+    let (initial_amount_swapped, initial_denom_swapped) = split_result_no_regex(result.to_owned());
+
+    let cache = ETF_CACHE.load(deps.storage).unwrap();
+
+    let swap_addr = SWAP_CONTRACT.load(deps.storage).unwrap();
+    let initial_deposit =  INITIAL_DEPOSIT_CACHE.load(deps.storage)?;
+    let (initial_deposit_token_out_denom, _) = get_initial_route_params(&initial_deposit.denom).unwrap();
+    let etf_swap_routes = cache.etf_swap_routes.to_owned();
+    
+    // validate if routes are passed properly before moving into execution
+    // TODO - looks like OSMOSIS does this on its side too, maybe there's no need to duplicate the process
+    validate_routes(deps.as_ref(), &swap_addr, &etf_swap_routes.routes)?;
+    let depo_key = (cache.sender.as_str(), cache.etf_swap_routes.name.as_str());
+    let mut submessages: std::vec::Vec<SubMsg<Empty>> = vec![];
+    let mut token_in_amnt_adder = Uint128::zero();
+    let init_amnt = Uint128::from(initial_amount_swapped.parse::<u128>().unwrap());
+    for (i, (route, ratio)) in etf_swap_routes.clone().routes.into_iter().zip(etf_swap_routes.ratios.into_iter()).enumerate() {
+        let token_in_amount: Uint128;
+        // no need to swap for denoms that have been received through initial swap
+        if route.token_out_denom == initial_deposit_token_out_denom.to_owned() {
+            token_in_amount = init_amnt.checked_multiply_ratio(ratio, 100u128).unwrap();
+            token_in_amnt_adder = token_in_amnt_adder.checked_add(token_in_amount).unwrap();
+            let new_ledger = update_ledger(&deps, depo_key, token_in_amount.to_string(), initial_deposit_token_out_denom.to_owned());
+            LEDGER.save(deps.storage, depo_key, &new_ledger)?;
+        } else {
+            if &i == &(etf_swap_routes.routes.len() - 1) {
+                token_in_amount = init_amnt.checked_sub(token_in_amnt_adder).unwrap();
+            } else {
+                token_in_amount = init_amnt.checked_multiply_ratio(ratio, 100u128).unwrap();
+                token_in_amnt_adder = token_in_amnt_adder.checked_add(token_in_amount).unwrap();
+            }
+            
+            let token_in = coin(token_in_amount.into(), initial_deposit_token_out_denom.to_owned());
+            if !ETF_POOLS.has(deps.storage, &route.token_out_denom) {
+                ETF_POOLS.save(deps.storage, &route.token_out_denom, &route.pool_id)?;
+            }
+            // pool share components are bought by entering the pool with the intermediate token
+            if pool_share_id(&route.token_out_denom) == Some(route.pool_id) {
+                let execute_message = create_msg_execute_join_pool(swap_addr.to_string(), route.pool_id, token_in);
+                submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_JOIN_POOL_REPLY_ID));
+            } else {
+                let execute_message = create_msg_execute_swap(
+                    deps.storage, swap_addr.to_string(), route.pool_id, route.token_out_denom.to_owned(), token_in
+                )?;
+                submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_SWAPS_REPLY_ID));
+            }
+        }
+    }
+
+    INITIAL_SWAP.save(deps.storage, depo_key, &coin(initial_amount_swapped.parse::<u128>().unwrap(), 
+                    initial_denom_swapped.to_owned()))?;
+
+    let msgs_mint_tokens = create_msgs_mint_shares(deps.as_ref(), &etf_swap_routes.name, &cache.sender, cache.shares)?;
+
+    return Ok(Response::default()
+        .add_attributes(vec![
+            attr("initial_swap_received_amount", initial_amount_swapped),
+            attr("initial_swap_received_denom", initial_denom_swapped),
+            attr("initial_swap_sender", &cache.sender),
+            ])
+        .add_submessages(submessages)
+        .add_messages(msgs_mint_tokens)
+        );            
+
+ }
+
+ fn handle_swaps_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+
+    // Filter the result so that it returns single event value
+    let result: String = parse_swap_reply(&msg);
+    let (amount_swapped, denom_swapped) = split_result_no_regex(result.to_owned());
+
+    let cache = ETF_CACHE.load(deps.storage).unwrap();
+    let depo_key = (cache.sender.as_str(), cache.etf_swap_routes.name.as_str());
+
+    let mut new_ledger: Vec<Coin> = vec![];
+    let mut found = false;
+    if LEDGER.has(deps.storage, depo_key.clone()) {
+        let curr_ledger = LEDGER.load(deps.storage, depo_key).unwrap();  
+        new_ledger = curr_ledger.clone();
+        for (i, c) in new_ledger.clone().into_iter().enumerate() {
+            if &c.denom == &denom_swapped {
+                let new_val = Uint128::from(amount_swapped.parse::<u128>().unwrap());
+                new_ledger[i] = coin(c.amount.checked_add(new_val).unwrap().u128(), c.denom);
+                found = true;
+                break
+            } 
+        }
+    }
+    if !found {
+        new_ledger.push(coin(amount_swapped.parse::<u128>().unwrap(), denom_swapped.to_owned()));
+    }
+    LEDGER.save(deps.storage, depo_key, &new_ledger)?;
+    
+    Ok(Response::default()
+        .add_attribute("swap_received_amount", amount_swapped)
+        .add_attribute("swap_received_denom", denom_swapped)
+    )
+ }
+
+ fn handle_revert_swaps(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let (amount_swapped_string, denom_swapped) = split_result_no_regex(parse_swap_reply(&msg));
+    let amount_swapped = amount_swapped_string.parse::<u128>().unwrap();
+    let updated = add_to_revert_swap_cache(deps.storage, coin(amount_swapped, denom_swapped.to_owned()))?;
+
+    Ok(Response::default()
+    .add_attributes([
+        attr("revert_swap_received_amount", amount_swapped_string),
+        attr("revert_swap_received_denom", denom_swapped),
+        attr("revert_swap_amount_total", updated),]
+        )
+    )
+ }
+ 
+ fn handle_join_pool_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let (shares_amount, shares_denom) = split_result_no_regex(parse_wasm_attribute(&msg, SHARES_OUT)?);
+
+    let cache = ETF_CACHE.load(deps.storage)?;
+    let depo_key = (cache.sender.as_str(), cache.etf_swap_routes.name.as_str());
+    let new_ledger = update_ledger(&deps, depo_key, shares_amount.to_owned(), shares_denom.to_owned());
+    LEDGER.save(deps.storage, depo_key, &new_ledger)?;
+
+    Ok(Response::default()
+        .add_attribute("swap_received_amount", shares_amount)
+        .add_attribute("swap_received_denom", shares_denom)
+    )
+ }
+
+ fn handle_exit_pool_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let (amount_string, denom) = split_result_no_regex(parse_wasm_attribute(&msg, TOKENS_OUT)?);
+    let amount = amount_string.parse::<u128>()
+        .map_err(|_| ContractError::DenomParsingError { val: amount_string.to_owned() })?;
+    let updated = add_to_revert_swap_cache(deps.storage, coin(amount, denom.to_owned()))?;
+
+    Ok(Response::default()
+    .add_attributes([
+        attr("revert_swap_received_amount", amount_string),
+        attr("revert_swap_received_denom", denom),
+        attr("revert_swap_amount_total", updated),]
+        )
+    )
+ }
+
+ fn handle_conjunction_swaps(deps: DepsMut, env: Env, msg_: Reply) -> Result<Response, ContractError>  {
+    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+
+    let etf_name_cache = ETF_NAME_CACHE.load(deps.storage)?;
+
+    // find pool for reverting transactions
+    let depo_coin = BALANCE.load(deps.storage, (&etf_name_cache.sender, &etf_name_cache.etf_name))?;
+    let (_, exit_pool_id) = get_initial_route_params(&depo_coin.denom)?;
+
+    // use cache that stores all uosmo swapped back through messages created above
+    let revert_swap_cache = REVERT_SWAP_CACHE.load(deps.storage)?;
+    LEDGER.remove(deps.storage, (&etf_name_cache.sender, &etf_name_cache.etf_name));
+    let execute_message = create_msg_execute_swap(
+        deps.storage, swap_addr.to_string(), exit_pool_id, depo_coin.denom.to_owned(), 
+        revert_swap_cache.coin_to_revert)?;
+    let submessage:SubMsg<Empty> = SubMsg::reply_on_success(execute_message, EXECUTE_REDEEM_SWAP_REPLY_ID);
+
+    // the whole deposit is redeemed at once, so there is nothing left to keep track of
+    let shares = remove_deposit(deps.storage, env.block.height, &etf_name_cache.sender, &etf_name_cache.etf_name)?;
+    let burn_tokens = create_msg_burn_shares(deps.as_ref(), &etf_name_cache.etf_name, shares)?;
+
+    Ok(Response::default()
+        .add_submessage(submessage)
+        .add_message(burn_tokens)
+    )
+ }
+
+ fn handle_redeem_swap(deps: DepsMut, msg: Reply) -> Result<Response, ContractError>  {
+    let tokens_out_string = split_result_no_regex(parse_swap_reply(&msg));
+    let tokens_out = coin(tokens_out_string.0.parse::<u128>().unwrap(), tokens_out_string.1);
+    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+    let etf_name_cache = ETF_NAME_CACHE.load(deps.storage)?;
+
+    let send_tokens_back = create_msg_send_tokens_back(&swap_addr, vec![tokens_out.clone()], etf_name_cache.sender.to_owned())?;
+    REVERT_SWAP_CACHE.remove(deps.storage);
+    ETF_NAME_CACHE.remove(deps.storage);
+    Ok(Response::default()
+        .add_message(send_tokens_back)
+        .add_attribute("denom_returned", tokens_out.denom)
+        .add_attribute("amount_returned", tokens_out.amount)
+        .add_attribute("returned_to", etf_name_cache.sender))
+ }
+
+fn handle_swap_exact_out_reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut cache = EXACT_OUT_CACHE.load(deps.storage)?;
+    let swapped = parse_swapped_tokens(&msg)?;
+
+    for (token_in, token_out) in swapped.iter() {
+        if token_in.denom == cache.max_deposit.denom {
+            cache.spent = cache.spent.checked_add(token_in.amount).map_err(StdError::from)?;
+            cache.initial_swap.amount = cache.initial_swap.amount.checked_add(token_out.amount).map_err(StdError::from)?;
+        }
+    }
+    // the last hop is the one ending with etf's token
+    let (_, bought) = swapped.last()
+        .ok_or_else(|| ContractError::CustomError { val: "no swap found in reply".to_string() })?;
+    let depo_key = (cache.sender.as_str(), cache.etf_name.as_str());
+    let new_ledger = update_ledger(&deps, depo_key, bought.amount.to_string(), bought.denom.to_owned());
+    LEDGER.save(deps.storage, depo_key, &new_ledger)?;
+
+    cache.swaps_left -= 1;
+    let res = Response::default()
+        .add_attribute("swap_received_amount", bought.amount)
+        .add_attribute("swap_received_denom", bought.denom.to_owned());
+    if cache.swaps_left > 0 {
+        EXACT_OUT_CACHE.save(deps.storage, &cache)?;
+        return Ok(res);
+    }
+
+    // all swaps are done - settle the deposit
+    EXACT_OUT_CACHE.remove(deps.storage);
+    let spent = coin(cache.spent.u128(), cache.max_deposit.denom.to_owned());
+    let shares = cache.shares;
+    save_deposit(deps.storage, env.block.height, &cache.sender, &cache.etf_name, &spent, shares)?;
+    let initial_swap = match INITIAL_SWAP.may_load(deps.storage, depo_key)? {
+        Some(curr) if curr.denom == cache.initial_swap.denom => coin(
+            curr.amount.checked_add(cache.initial_swap.amount).map_err(StdError::from)?.u128(), curr.denom),
+        _ => cache.initial_swap.to_owned(),
+    };
+    INITIAL_SWAP.save(deps.storage, depo_key, &initial_swap)?;
+
+    let mut msgs = create_msgs_mint_shares(deps.as_ref(), &cache.etf_name, &cache.sender, shares)?;
+    let refund = cache.max_deposit.amount.checked_sub(cache.spent).map_err(StdError::from)?;
+    if !refund.is_zero() {
+        let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+        msgs.push(create_msg_send_tokens_back(&swap_addr, vec![coin(refund.u128(), cache.max_deposit.denom)], cache.sender.to_owned())?);
+    }
+
+    Ok(res
+        .add_messages(msgs)
+        .add_attribute("deposit_spent", spent.to_string())
+        .add_attribute("shares_minted", shares)
+        .add_attribute("deposit_refunded", refund)
+        .add_attribute("initial_swap_sender", cache.sender))
+}
+
+// splits the intermediate token bought with the rest of deposits between the batch's routes
+fn handle_batch_buy_reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut cache = BATCH_CACHE.load(deps.storage)?;
+    let (amount_string, intermediate_denom) = split_result_no_regex(parse_swap_reply(&msg));
+    let bought = amount_string.parse::<u128>()
+        .map(Uint128::from)
+        .map_err(|_| ContractError::DenomParsingError { val: amount_string.to_owned() })?;
+
+    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+    let mut submessages: Vec<SubMsg<Empty>> = vec![];
+    let mut left = bought;
+    let routes_count = cache.config.routes.len();
+    for (i, (route, ratio)) in cache.config.routes.iter().zip(cache.config.ratios.iter()).enumerate() {
+        let token_in_amount = if i == routes_count - 1 {
+            left
+        } else {
+            bought.multiply_ratio(*ratio, 100u128)
+        };
+        left -= token_in_amount;
+        if token_in_amount.is_zero() {
+            continue
+        }
+        if route.token_out_denom == intermediate_denom {
+            add_to_coins(&mut cache.buyers_tokens, coin(token_in_amount.u128(), intermediate_denom.to_owned()))?;
+            continue
+        }
+        let execute_message = create_msg_execute_swap(
+            deps.storage, swap_addr.to_string(), route.pool_id, route.token_out_denom.to_owned(), coin(token_in_amount.u128(), intermediate_denom.to_owned()))?;
+        submessages.push(SubMsg::reply_on_success(execute_message, EXECUTE_BATCH_SWAPS_REPLY_ID));
+    }
+    cache.swaps_left = submessages.len() as u64;
+
+    let res = Response::default()
+        .add_attribute("batch_swap_received_amount", bought)
+        .add_attribute("batch_swap_received_denom", intermediate_denom);
+    if submessages.is_empty() {
+        return finalize_batch(deps, &env, cache, res);
+    }
+    BATCH_CACHE.save(deps.storage, &cache)?;
+    Ok(res.add_submessages(submessages))
+}
+
+// etf tokens bought for depositors or intermediate token received for sold ones
+fn handle_batch_swaps_reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut cache = BATCH_CACHE.load(deps.storage)?;
+    let (_, token_out) = parse_swapped_tokens(&msg)?
+        .pop()
+        .ok_or_else(|| ContractError::CustomError { val: "no swap found in reply".to_string() })?;
+    let (intermediate_denom, _) = get_initial_route_params(&cache.config.deposit_denom)?;
+    if token_out.denom == intermediate_denom {
+        cache.intermediate = cache.intermediate.checked_add(token_out.amount).map_err(StdError::from)?;
+    } else {
+        add_to_coins(&mut cache.buyers_tokens, token_out.to_owned())?;
+    }
+    cache.swaps_left -= 1;
+
+    let res = Response::default()
+        .add_attribute("batch_swap_received_amount", token_out.amount)
+        .add_attribute("batch_swap_received_denom", token_out.denom);
+    if cache.swaps_left > 0 {
+        BATCH_CACHE.save(deps.storage, &cache)?;
+        return Ok(res);
+    }
+    // intermediate token is only collected when selling, it goes back to the deposit denom in one swap
+    if !cache.intermediate.is_zero() {
+        let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+        let submessage = create_submsg_batch_sell(deps.storage, &swap_addr, &cache)?;
+        BATCH_CACHE.save(deps.storage, &cache)?;
+        return Ok(res.add_submessage(submessage));
+    }
+    finalize_batch(deps, &env, cache, res)
+}
+
+fn handle_batch_sell_reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut cache = BATCH_CACHE.load(deps.storage)?;
+    let (amount_string, denom) = split_result_no_regex(parse_swap_reply(&msg));
+    let amount = amount_string.parse::<u128>()
+        .map(Uint128::from)
+        .map_err(|_| ContractError::DenomParsingError { val: amount_string.to_owned() })?;
+    cache.proceeds = cache.proceeds.checked_add(amount).map_err(StdError::from)?;
+    cache.intermediate = Uint128::zero();
+
+    let res = Response::default()
+        .add_attribute("batch_swap_received_amount", amount)
+        .add_attribute("batch_swap_received_denom", denom);
+    finalize_batch(deps, &env, cache, res)
+}
+
+// splits batch results pro-rata, depositors by their deposits and redeemers by the value of their positions
+fn finalize_batch(deps: DepsMut, env: &Env, cache: BatchCache, res: Response) -> Result<Response, ContractError> {
+    let etf_name = cache.etf_name.as_str();
+    let deposit_denom = cache.config.deposit_denom.as_str();
+    let deposits = BATCH_DEPOSITS
+        .prefix(etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+
+    // the last depositor also gets the rounding leftovers
+    let mut tokens_left = cache.buyers_tokens.to_owned();
+    for (i, (sender, amount)) in deposits.iter().enumerate() {
+        let mut ledger = LEDGER.may_load(deps.storage, (sender, etf_name))?.unwrap_or_default();
+        for (c, left) in cache.buyers_tokens.iter().zip(tokens_left.iter_mut()) {
+            let share = if i == deposits.len() - 1 {
+                left.amount
+            } else {
+                c.amount.multiply_ratio(*amount, cache.deposits_total)
+            };
+            left.amount -= share;
+            if !share.is_zero() {
+                add_to_coins(&mut ledger, coin(share.u128(), c.denom.to_owned()))?;
+            }
+        }
+        LEDGER.save(deps.storage, (sender, etf_name), &ledger)?;
+        let deposit = coin(amount.u128(), deposit_denom);
+        let shares = shares_for_deposit(deps.as_ref(), env, &deposit)?;
+        save_deposit(deps.storage, env.block.height, sender, etf_name, &deposit, shares)?;
+        add_batch_claim(deps.storage, sender, etf_name, shares, None)?;
+        BATCH_DEPOSITS.remove(deps.storage, (etf_name, sender));
+    }
+
+    let redemptions_total = cache.redemptions.iter()
+        .try_fold(Uint128::zero(), |total, (_, value)| total.checked_add(*value))
+        .map_err(StdError::from)?;
+    let mut proceeds_left = cache.proceeds;
+    for (i, (sender, value)) in cache.redemptions.iter().enumerate() {
+        let share = if i == cache.redemptions.len() - 1 {
+            proceeds_left
+        } else if redemptions_total.is_zero() {
+            Uint128::zero()
+        } else {
+            cache.proceeds.multiply_ratio(*value, redemptions_total)
+        };
+        proceeds_left -= share;
+        add_batch_claim(deps.storage, sender, etf_name, Uint128::zero(), Some(coin(share.u128(), deposit_denom)))?;
+        BATCH_REDEMPTIONS.remove(deps.storage, (etf_name, sender));
+    }
+    BATCH_CACHE.remove(deps.storage);
+
+    Ok(res
+        .add_attribute("batch_settled", etf_name)
+        .add_attribute("depositors", deposits.len().to_string())
+        .add_attribute("redeemers", cache.redemptions.len().to_string())
+        .add_attribute("proceeds", coin(cache.proceeds.u128(), deposit_denom).to_string()))
+}
+
+// ----------------------------------- QUERIES
+// ##############################################################################
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetTokens {sender, etf_type} => to_binary(&query_get_tokens(deps, sender, etf_type)?),
+        QueryMsg::GetInitialSwap {sender, etf_type} => to_binary(&query_get_initial_swap(deps, sender, etf_type)?),
+        QueryMsg::GetBalance {sender, etf_type} => to_binary(&query_balance(deps, &sender, etf_type)?),
+        QueryMsg::GetPauseState {} => to_binary(&query_pause_state(deps)?),
+        QueryMsg::EtfLimits {etf_name} => to_binary(&query_etf_limits(deps, env, etf_name)?),
+        QueryMsg::GetShareToken {etf_name} => to_binary(&query_share_token(deps, etf_name)?),
+        QueryMsg::GetNav {sender, etf_type} => to_binary(&query_nav(deps, sender, etf_type)?),
+        QueryMsg::BatchState {etf_name} => to_binary(&query_batch_state(deps, etf_name)?),
+        QueryMsg::BatchClaim {sender, etf_name} => to_binary(&query_batch_claim(deps, sender, etf_name)?),
+        QueryMsg::DcaPlans {owner} => to_binary(&query_dca_plans(deps, owner)?),
+        QueryMsg::NavOrders {owner} => to_binary(&query_nav_orders(deps, owner)?),
+        QueryMsg::NavPerShare {etf_name, denom} => to_binary(&query_nav_per_share(deps, env, etf_name, denom)?),
+        QueryMsg::Donation {holder, etf_name} => to_binary(&DonationResponse {
+            donation: DONATIONS.may_load(deps.storage, (&holder, &etf_name))?
+        }),
+        QueryMsg::DonationBeneficiaries {etf_name} => to_binary(&DonationBeneficiariesResponse {
+            beneficiaries: DONATION_BENEFICIARIES.may_load(deps.storage, &etf_name)?.unwrap_or_default()
+        }),
+        QueryMsg::DonationTotals {beneficiary} => to_binary(&query_donation_totals(deps, beneficiary)?),
+        QueryMsg::GovConfig {} => to_binary(&GovConfigResponse { config: GOV_CONFIG.may_load(deps.storage)? }),
+        QueryMsg::Proposal {proposal_id} => to_binary(&ProposalResponse {
+            proposal: PROPOSALS.load(deps.storage, proposal_id)?
+        }),
+        QueryMsg::Proposals {etf_name} => to_binary(&query_proposals(deps, etf_name)?),
+        QueryMsg::Vote {proposal_id, voter} => to_binary(&VoteResponse {
+            ballot: VOTES.may_load(deps.storage, (proposal_id, &voter))?
+        }),
+        QueryMsg::VotingPower {etf_name, holder, height}
+            => to_binary(&query_voting_power(deps, etf_name, holder, height)?),
+        QueryMsg::Index {etf_name} => to_binary(&IndexResponse {
+            methodology: INDEX_METHODOLOGIES.may_load(deps.storage, &etf_name)?,
+            weights: INDEX_WEIGHTS.may_load(deps.storage, &etf_name)?,
+        }),
+        QueryMsg::PairRoute {pool_id} => to_binary(&PairRouteResponse {
+            pair: PAIR_ROUTES.may_load(deps.storage, pool_id)?,
+        }),
+        QueryMsg::IbcRefund {channel, sequence} => to_binary(&IbcRefundResponse {
+            refund: IBC_REFUNDS.may_load(deps.storage, (&channel, sequence))?,
+        }),
+        QueryMsg::OutflowCapacity {etf_name} => to_binary(&query_outflow_capacity(deps, env, etf_name)?),
+        QueryMsg::WindDown {etf_name} => to_binary(&WindDownResponse {
+            wind_down: WIND_DOWNS.may_load(deps.storage, &etf_name)?,
+        }),
+        QueryMsg::WindDownClaim {etf_name, holder} => to_binary(&query_wind_down_claim(deps, etf_name, holder)?),
+    }
+}
+
+fn query_get_tokens(deps: Deps, sender: String, etf_type: String) -> StdResult<GetTokensResponse> {
+    let res = LEDGER.load(deps.storage, (&sender, &etf_type))?;
+    Ok(GetTokensResponse { tokens_per_etf: res })
+}
+
+fn query_get_initial_swap(deps: Deps, sender: String, etf_type: String) -> StdResult<GetInitialSwapResponse> {
+    let res = INITIAL_SWAP.load(deps.storage, (&sender, &etf_type))?;
+    Ok(GetInitialSwapResponse {initial_swap: res})
+}
+
+fn query_balance(deps: Deps, sender: &String, etf_type: String) -> StdResult<GetBalanceResponse> {
+    // redeemed positions are removed from storage, report them as an empty balance
+    let res = BALANCE.may_load(deps.storage, (sender, &etf_type))?.unwrap_or_default();
+    Ok(GetBalanceResponse {  balance: res })
+}
+
+fn query_pause_state(deps: Deps) -> StdResult<GetPauseStateResponse> {
+    let paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    Ok(GetPauseStateResponse {
+        buys_paused: paused.buys,
+        redemptions_paused: paused.redemptions,
+        downtime_breaker: DOWNTIME_BREAKER.may_load(deps.storage)?,
+        recovered_from_downtime: recovered_from_downtime(deps)?,
+    })
+}
+
+fn query_nav(deps: Deps, sender: String, etf_type: String) -> StdResult<GetNavResponse> {
+    let ledger = LEDGER.load(deps.storage, (&sender, &etf_type))?;
+    let deposit = BALANCE.load(deps.storage, (&sender, &etf_type))?;
+    let nav = value_ledger(deps, &ledger, &deposit.denom, None)?;
+    Ok(GetNavResponse { nav: coin(nav.u128(), deposit.denom) })
+}
+
+fn query_batch_state(deps: Deps, etf_name: String) -> StdResult<BatchStateResponse> {
+    let queued_deposits = BATCH_DEPOSITS
+        .prefix(&etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<_> { Ok(total.checked_add(item?.1)?) })?;
+    Ok(BatchStateResponse {
+        config: BATCH_CONFIGS.may_load(deps.storage, &etf_name)?,
+        queued_deposits,
+        queued_redemptions: BATCH_REDEMPTIONS
+            .prefix(&etf_name)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count() as u64,
+    })
+}
+
+fn query_batch_claim(deps: Deps, sender: String, etf_name: String) -> StdResult<BatchClaim> {
+    Ok(BATCH_CLAIMS.may_load(deps.storage, (&sender, &etf_name))?.unwrap_or_default())
+}
+
+fn query_dca_plans(deps: Deps, owner: String) -> StdResult<DcaPlansResponse> {
+    let plans = DCA_PLANS
+        .prefix(&owner)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, plan)| plan))
+        .collect::<StdResult<Vec<DcaPlan>>>()?;
+    Ok(DcaPlansResponse { plans })
+}
+
+fn query_nav_orders(deps: Deps, owner: String) -> StdResult<NavOrdersResponse> {
+    let orders = NAV_ORDERS
+        .prefix(&owner)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, order)| order))
+        .collect::<StdResult<Vec<NavOrder>>>()?;
+    Ok(NavOrdersResponse { orders })
+}
+
+fn query_nav_per_share(deps: Deps, env: Env, etf_name: String, denom: String) -> StdResult<NavPerShareResponse> {
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    let nav_per_share = etf_nav_per_share(deps, &etf_name, &denom, Some(twap_start))?;
+    Ok(NavPerShareResponse { nav_per_share, denom })
+}
+
+fn query_donation_totals(deps: Deps, beneficiary: String) -> StdResult<DonationTotalsResponse> {
+    let donated = DONATED
+        .prefix(&beneficiary)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .collect::<StdResult<Vec<Coin>>>()?;
+    Ok(DonationTotalsResponse { donated })
+}
+
+fn query_proposals(deps: Deps, etf_name: String) -> StdResult<ProposalsResponse> {
+    let proposals = PROPOSALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, proposal)) if proposal.etf_name != etf_name))
+        .map(|item| item.map(|(_, proposal)| proposal))
+        .collect::<StdResult<Vec<Proposal>>>()?;
+    Ok(ProposalsResponse { proposals })
+}
+
+fn query_voting_power(deps: Deps, etf_name: String, holder: String, height: Option<u64>) -> StdResult<VotingPowerResponse> {
+    let total_shares = match height {
+        Some(height) => TOTAL_SHARES.may_load_at_height(deps.storage, &etf_name, height)?,
+        None => TOTAL_SHARES.may_load(deps.storage, &etf_name)?,
+    };
+    Ok(VotingPowerResponse {
+        shares: holder_shares(deps, &etf_name, &holder, height)?,
+        total_shares: total_shares.unwrap_or_default(),
+    })
+}
+
+// native shares are the holder's bank balance, which has no history, cw20 shares are those of the position
+fn holder_shares(deps: Deps, etf_name: &str, holder: &str, height: Option<u64>) -> StdResult<Uint128> {
+    let shares = match (NATIVE_DENOMS.may_load(deps.storage, etf_name)?, height) {
+        (Some(denom), _) => Some(deps.querier.query_balance(holder, denom)?.amount),
+        (None, Some(height)) => SHARES.may_load_at_height(deps.storage, (etf_name, holder), height)?,
+        (None, None) => SHARES.may_load(deps.storage, (etf_name, holder))?,
+    };
+    Ok(shares.unwrap_or_default())
+}
+
+fn query_outflow_capacity(deps: Deps, env: Env, etf_name: String) -> StdResult<OutflowCapacityResponse> {
+    let limit = OUTFLOW_LIMITS.may_load(deps.storage, &etf_name)?;
+    let (outflow, remaining) = match &limit {
+        Some(limit) => {
+            let outflow = current_outflow(deps.storage, &env, &etf_name, limit)?;
+            let remaining = outflow.capacity.saturating_sub(rolling_outflow(&env, &outflow, limit)?);
+            (Some(outflow), Some(remaining))
+        },
+        None => (None, None),
+    };
+    let queue = REDEMPTION_QUEUE
+        .prefix(&etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, queued)| queued))
+        .collect::<StdResult<Vec<QueuedRedemption>>>()?;
+    Ok(OutflowCapacityResponse {
+        remaining,
+        limit,
+        outflow,
+        queue,
+    })
+}
+
+fn query_wind_down_claim(deps: Deps, etf_name: String, holder: String) -> StdResult<WindDownClaimResponse> {
+    let claimed = WIND_DOWN_CLAIMS.may_load(deps.storage, (&etf_name, &holder))?.unwrap_or_default();
+    let wind_down = match WIND_DOWNS.may_load(deps.storage, &etf_name)? {
+        Some(wind_down) => wind_down,
+        None => return Ok(WindDownClaimResponse { claimable: vec![], claimed }),
+    };
+    let shares = match NATIVE_DENOMS.may_load(deps.storage, &etf_name)? {
+        Some(denom) => deps.querier.query_balance(&holder, denom)?.amount,
+        None => SHARES.may_load(deps.storage, (&etf_name, &holder))?.unwrap_or_default(),
+    };
+    Ok(WindDownClaimResponse { claimable: wind_down_share(&wind_down, shares)?, claimed })
+}
+
+fn nav_twap_window(deps: Deps) -> StdResult<u64> {
+    Ok(NAV_TWAP_WINDOW.may_load(deps.storage)?.unwrap_or(DEFAULT_NAV_TWAP_WINDOW))
+}
+
+// every position is valued in the denom it was deposited in, then in `denom`, against all shares of the etf.
+// Goes through all positions, which is fine as long as the number of holders stays moderate
+fn etf_nav_per_share(deps: Deps, etf_name: &str, denom: &str, twap_start: Option<Timestamp>) -> StdResult<Decimal> {
+    let mut value = Uint128::zero();
+    for item in BALANCE.range(deps.storage, None, None, Order::Ascending) {
+        let ((sender, etf), depo_coin) = item?;
+        if etf != etf_name {
+            continue
+        }
+        let ledger = LEDGER.may_load(deps.storage, (&sender, &etf))?.unwrap_or_default();
+        let position_value = value_ledger(deps, &ledger, &depo_coin.denom, twap_start)?;
+        value = value.checked_add(checked_mul_dec(position_value, deposit_denom_price(deps, &depo_coin.denom, denom, twap_start)?)?)?;
+    }
+    let shares = TOTAL_SHARES.may_load(deps.storage, etf_name)?.unwrap_or_default();
+    if shares.is_zero() {
+        return Err(StdError::generic_err(format!("No shares of etf {} outstanding", etf_name)));
+    }
+    Ok(Decimal::from_ratio(value, shares))
+}
+
+// what `shares` of the etf are worth in `denom`, a share of an etf without any is worth a uosmo like in `shares_for_deposit`
+fn value_shares(deps: Deps, etf_name: &str, shares: Uint128, denom: &str, twap_start: Option<Timestamp>) -> StdResult<Uint128> {
+    let price = if TOTAL_SHARES.may_load(deps.storage, etf_name)?.unwrap_or_default().is_zero() {
+        deposit_denom_price(deps, "uosmo", denom, twap_start)?
+    } else {
+        etf_nav_per_share(deps, etf_name, denom, twap_start)?
+    };
+    checked_mul_dec(shares, price)
+}
+
+// deposit denoms are all priced on the osmo/usdc pool
+fn deposit_denom_price(deps: Deps, base_denom: &str, quote_denom: &str, twap_start: Option<Timestamp>) -> StdResult<Decimal> {
+    if base_denom == quote_denom {
+        return Ok(Decimal::one());
+    }
+    let pool = PoolInfo::query(&deps.querier, OSMO_USDC_POOL_ID)?;
+    pool_price(deps, &pool, base_denom, quote_denom, twap_start)
+}
+
+// ledger tokens are priced in the intermediate token of the initial swap, then in the deposit denom,
+// with spot prices or TWAPs since twap_start
+fn value_ledger(deps: Deps, ledger: &[Coin], deposit_denom: &str, twap_start: Option<Timestamp>) -> StdResult<Uint128> {
+    let (intermediate_denom, initial_pool_id) = get_initial_route_params(&deposit_denom.to_string())
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    let mut intermediate_value = Uint128::zero();
+    for c in ledger.iter() {
+        let value = if c.denom == intermediate_denom {
+            c.amount
+        } else if let Some(pool_id) = pool_share_id(&c.denom) {
+            // pool shares are worth the tokens they'd exit into
+            let pool = PoolInfo::query(&deps.querier, pool_id)?;
+            let tokens_out = GammV1Beta1Querier::new(&deps.querier)
+                .calc_exit_pool_coins_from_shares(pool_id, c.amount.to_string())?
+                .tokens_out;
+            let mut shares_value = Uint128::zero();
+            for token in tokens_out.into_iter() {
+                let token: Coin = token.try_into()?;
+                shares_value = shares_value.checked_add(if token.denom == intermediate_denom {
+                    token.amount
+                } else {
+                    checked_mul_dec(token.amount, pool_price(deps, &pool, &token.denom, &intermediate_denom, twap_start)?)?
+                })?;
+            }
+            shares_value
+        } else {
+            let pool_id = ETF_POOLS.load(deps.storage, &c.denom)?;
+            checked_mul_dec(c.amount, route_price(deps, pool_id, &c.denom, &intermediate_denom, twap_start)?)?
+        };
+        intermediate_value = intermediate_value.checked_add(value)?;
+    }
+    let price = route_price(deps, initial_pool_id, &intermediate_denom, deposit_denom, twap_start)?;
+    checked_mul_dec(intermediate_value, price)
+}
+
+// pairs keep no TWAP, they're always priced at spot
+fn route_price(deps: Deps, pool_id: u64, base_denom: &str, quote_denom: &str, twap_start: Option<Timestamp>) -> StdResult<Decimal> {
+    match pair_dex(deps, pool_id)? {
+        Some(dex) => {
+            let res: DexPriceResponse = deps.querier.query_wasm_smart(SWAP_CONTRACT.load(deps.storage)?, &swap_msg::QueryMsg::DexPrice {
+                dex,
+                base_denom: base_denom.to_string(),
+                quote_denom: quote_denom.to_string(),
+            })?;
+            Ok(res.price)
+        },
+        None => pool_price(deps, &PoolInfo::query(&deps.querier, pool_id)?, base_denom, quote_denom, twap_start),
+    }
+}
+
+// pair contracts are priced by the swap contract's adapters
+fn pair_dex(deps: Deps, pool_id: u64) -> StdResult<Option<Dex>> {
+    Ok(PAIR_ROUTES.may_load(deps.storage, pool_id)?.map(|pair| Dex::Pair { contract: pair.into_string() }))
+}
+
+fn pool_price(deps: Deps, pool: &PoolInfo, base_denom: &str, quote_denom: &str, twap_start: Option<Timestamp>) -> StdResult<Decimal> {
+    match twap_start {
+        Some(start_time) => pool.twap_price(&deps.querier, base_denom, quote_denom, start_time),
+        None => pool.spot_price(&deps.querier, base_denom, quote_denom),
+    }
+}
+
+fn query_share_token(deps: Deps, etf_name: String) -> StdResult<GetShareTokenResponse> {
+    Ok(GetShareTokenResponse {
+        cw20_contract: MINT_CONTRACTS.may_load(deps.storage, &etf_name)?,
+        native_denom: NATIVE_DENOMS.may_load(deps.storage, &etf_name)?,
+    })
+}
+
+fn query_etf_limits(deps: Deps, env: Env, etf_name: String) -> StdResult<EtfLimitsResponse> {
+    let limits = ETF_LIMITS.may_load(deps.storage, &etf_name)?;
+    let total_deposits = load_etf_deposits(deps, &etf_name)?;
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    let total_deposits_value = match &limits {
+        Some(limits) => Some(value_in_quote_denom(deps, limits, &total_deposits, Some(twap_start))
+            .map_err(|err| StdError::generic_err(err.to_string()))?),
+        None => None,
+    };
+    Ok(EtfLimitsResponse { limits, total_deposits, total_deposits_value })
+}
+
+// ----------------------------------- HELPER FUNCTIONS
+// ##############################################################################
+
+// pools registered in PAIR_ROUTES are swapped in their pair contract, gamm otherwise
+fn create_msg_execute_swap(storage: &dyn Storage,
+    contract: String, 
+    pool_id: u64, 
+    token_out_denom: String, 
+    token_in: Coin,
+    ) -> StdResult<WasmMsg> {
+    let msg = match PAIR_ROUTES.may_load(storage, pool_id)? {
+        Some(pair) => to_binary(&swap_msg::ExecuteMsg::ExecuteSwap {
+            dex: Dex::Pair { contract: pair.to_string() },
+            token_in,
+            token_out_denom,
+            token_out_min_amount: Uint128::one(),
+        })?,
+        None => to_binary(&swap_msg::ExecuteMsg::ExecuteSwapExactAmountIn {
+            routes: vec![SwapAmountInRoute {
+                pool_id: pool_id, 
+                token_out_denom: token_out_denom
+            }],
+            token_in: Some(coin(
+        token_in.amount.into(), 
+        token_in.denom.clone()).into()
+        ),
+        // TODO add twap query in order to estimate token_out_min_amount
+        token_out_min_amount: "1".to_string()
+          })?,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: contract,
+        funds: vec![],
+        msg,
+    })
+}
+
+
+fn create_msg_execute_swap_exact_out(contract: String,
+    routes: Vec<SwapAmountOutRoute>,
+    token_out: Coin,
+    token_in_max_amount: Uint128,
+    ) -> WasmMsg {
+    WasmMsg::Execute {
+        contract_addr: contract,
+        funds: vec![],
+        msg: to_binary(&swap_msg::ExecuteMsg::ExecuteSwapExactAmountOut {
+            routes,
+            token_out: Some(token_out.into()),
+            token_in_max_amount: token_in_max_amount.to_string(),
+        }).unwrap()
+    }
+}
+
+
+fn create_msg_execute_join_pool(contract: String, pool_id: u64, token_in: Coin) -> WasmMsg {
+    WasmMsg::Execute {
+        contract_addr: contract,
+        funds: vec![],
+        msg: to_binary(&swap_msg::ExecuteMsg::ExecuteJoinSwapExternAmountIn {
+            pool_id,
+            token_in: Some(token_in.into()),
+            share_out_min_amount: "1".to_string(),
+        }).unwrap()
+    }
+}
+
+fn create_msg_execute_exit_pool(contract: String, pool_id: u64, token_out_denom: String, share_in_amount: Uint128) -> WasmMsg {
+    WasmMsg::Execute {
+        contract_addr: contract,
+        funds: vec![],
+        msg: to_binary(&swap_msg::ExecuteMsg::ExecuteExitSwapShareAmountIn {
+            pool_id,
+            token_out_denom,
+            share_in_amount: share_in_amount.to_string(),
+            token_out_min_amount: "1".to_string(),
+        }).unwrap()
+    }
+}
+
+fn create_msg_execute_mint_tokens(        
+    recipient: String,
+    amount_to_mint: Uint128,
+    mint_contract_address: String,
+) -> WasmMsg { 
+
+    WasmMsg::Execute {
+        contract_addr: mint_contract_address.to_string(),
+        funds: vec![],
+        msg: to_binary(&share_token::ExecuteMsg::Mint {
+            recipient: recipient.to_owned(),
+            amount: amount_to_mint,
+        }).unwrap()
+    }
+}
+
+fn create_msg_burn_tokens(
+    mint_contract_address: &String, 
+    amount: Uint128
+) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: mint_contract_address.to_string(),
+        funds: vec![],
+        msg: to_binary(&share_token::ExecuteMsg::Burn {
+            amount,
+        }).unwrap(),
+    }.into())
+}
+
+pub(crate) fn create_msgs_mint_shares(deps: Deps, etf_name: &str, sender: &str, amount: Uint128) -> StdResult<Vec<CosmosMsg>> {
+    let manager_addr = MANAGER_CONTRACT.load(deps.storage)?;
+    Ok(match NATIVE_DENOMS.may_load(deps.storage, etf_name)? {
+        // tokenfactory mints to the manager, shares are passed on so that they can be traded or bridged
+        Some(denom) => {
+            let shares = coin(amount.u128(), denom);
+            vec![
+                create_msg_mint_native_tokens(&manager_addr, shares.to_owned()),
+                BankMsg::Send { to_address: sender.to_string(), amount: vec![shares] }.into(),
+            ]
+        },
+        None => {
+            let mint_contract_addr = MINT_CONTRACTS.load(deps.storage, etf_name)?;
+            vec![create_msg_execute_mint_tokens(
+                manager_addr.to_string(),
+                amount, 
+                mint_contract_addr).into()]
+        },
+    })
+}
+
+pub(crate) fn create_msg_burn_shares(deps: Deps, etf_name: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    match NATIVE_DENOMS.may_load(deps.storage, etf_name)? {
+        Some(denom) => {
+            let manager_addr = MANAGER_CONTRACT.load(deps.storage)?;
+            Ok(create_msg_burn_native_tokens(&manager_addr, coin(amount.u128(), denom)))
+        },
+        None => {
+            let mint_contract = MINT_CONTRACTS.load(deps.storage, etf_name)?;
+            create_msg_burn_tokens(&mint_contract, amount)
+        },
+    }
+}
+
+fn create_msg_mint_native_tokens(manager_addr: &Addr, amount: Coin) -> CosmosMsg {
+    MsgMint {
+        sender: manager_addr.to_string(),
+        amount: Some(amount.into()),
+    }.into()
+}
+
+fn create_msg_burn_native_tokens(manager_addr: &Addr, amount: Coin) -> CosmosMsg {
+    MsgBurn {
+        sender: manager_addr.to_string(),
+        amount: Some(amount.into()),
+    }.into()
+}
+
+// only the deposit goes to the swap contract, anything else attached is sent straight back to the sender
+fn create_msgs_forward_deposit(swap_addr: &Addr, info: &MessageInfo, deposit: &Coin) -> (CosmosMsg, Option<CosmosMsg>) {
+    let refund = excess_funds(&info.funds, deposit);
+    let forward = BankMsg::Send { to_address: swap_addr.to_string(), amount: vec![deposit.to_owned()] };
+    let refund = (!refund.is_empty())
+        .then(|| BankMsg::Send { to_address: info.sender.to_string(), amount: refund }.into());
+    (forward.into(), refund)
+}
+
+fn excess_funds(funds: &[Coin], deposit: &Coin) -> Vec<Coin> {
+    funds.iter()
+        .map(|c| if c.denom == deposit.denom {
+            coin(c.amount.saturating_sub(deposit.amount).u128(), c.denom.to_owned())
+        } else {
+            c.to_owned()
+        })
+        .filter(|c| !c.amount.is_zero())
+        .collect()
+}
+
+// amount * dec rounded down, erroring instead of panicking on overflow
+fn checked_mul_dec(amount: Uint128, dec: Decimal) -> StdResult<Uint128> {
+    amount.checked_multiply_ratio(dec.atomics(), Decimal::one().atomics())
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+fn create_msg_send_tokens_back(
+    swap_addr: &Addr, 
+    tokens_back: Vec<Coin>,
+    recipient: String
+) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: swap_addr.to_string(),
+        funds: vec![],
+        msg: to_binary(&swap_msg::ExecuteMsg::SendTokensBack { tokens: tokens_back, recipient }).unwrap(),
+    }.into())
+}
+
+// adds to user's deposit and shares, and to etf's totals
+fn save_deposit(storage: &mut dyn Storage, height: u64, sender: &str, etf_name: &str, deposit: &Coin, shares: Uint128) -> StdResult<()> {
+    let depo_key = (sender, etf_name);
+    // a queued redemption covers the position as it was queued
+    if QUEUED_POSITIONS.has(storage, depo_key) {
+        return Err(StdError::generic_err(format!("Position in etf {} has a queued redemption", etf_name)));
+    }
+    if WIND_DOWNS.has(storage, etf_name) {
+        return Err(StdError::generic_err(format!("Etf {} is wound down", etf_name)));
+    }
+    // new deposits aren't growth
+    if let Some(mut donation) = DONATIONS.may_load(storage, depo_key)? {
+        donation.high_water_mark = donation.high_water_mark.checked_add(deposit.amount)?;
+        DONATIONS.save(storage, depo_key, &donation)?;
+    }
+    let new_deposit = match BALANCE.may_load(storage, depo_key)? {
+        Some(curr_deposit) => coin(curr_deposit.amount.checked_add(deposit.amount)?.u128(), curr_deposit.denom),
+        None => deposit.clone(),
+    };
+    BALANCE.save(storage, depo_key, &new_deposit)?;
+    SHARES.update(storage, (etf_name, sender), height, |curr| -> StdResult<_> {
+        Ok(curr.unwrap_or_default().checked_add(shares)?)
+    })?;
+    TOTAL_SHARES.update(storage, etf_name, height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(shares)?)
+    })?;
+    ETF_DEPOSITS.update(storage, (etf_name, &new_deposit.denom), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(deposit.amount)?)
+    })?;
+    Ok(())
+}
+
+// drops user's deposit and shares from their balance and from etf's totals, returns the shares dropped
+fn remove_deposit(storage: &mut dyn Storage, height: u64, sender: &str, etf_name: &str) -> StdResult<Uint128> {
+    let depo_coin = BALANCE.load(storage, (sender, etf_name))?;
+    let shares = SHARES.may_load(storage, (etf_name, sender))?.unwrap_or_default();
+    BALANCE.remove(storage, (sender, etf_name));
+    SHARES.remove(storage, (etf_name, sender), height)?;
+    TOTAL_SHARES.update(storage, etf_name, height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().saturating_sub(shares))
+    })?;
+    let deposits_key = (etf_name, depo_coin.denom.as_str());
+    let total_deposits = ETF_DEPOSITS.may_load(storage, deposits_key)?.unwrap_or_default();
+    ETF_DEPOSITS.save(storage, deposits_key, &total_deposits.saturating_sub(depo_coin.amount))?;
+    Ok(shares)
+}
+
+// shares are counted in uosmo, so that deposits in any denom get the same number of shares for the same value
+pub(crate) fn shares_for_deposit(deps: Deps, env: &Env, deposit: &Coin) -> StdResult<Uint128> {
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    checked_mul_dec(deposit.amount, deposit_denom_price(deps, &deposit.denom, "uosmo", Some(twap_start))?)
+}
+
+// native shares are held by the user, they get burnt once they're back with the manager
+fn returned_shares(info: &MessageInfo, denom: &str) -> Result<Uint128, ContractError> {
+    match info.funds.iter().find(|c| c.denom == denom) {
+        Some(shares) if !shares.amount.is_zero() => Ok(shares.amount),
+        _ => Err(ContractError::SharesNotReturned { val: denom.to_string() }),
+    }
+}
+
+// Takes `shares`' part out of every position of the etf, keeping their tokens, deposits and shares in proportion.
+// Goes through all holders, which is fine as long as their number stays moderate
+fn take_pro_rata(storage: &mut dyn Storage, height: u64, etf_name: &str, shares: Uint128) -> StdResult<Vec<Coin>> {
+    let total_shares = TOTAL_SHARES.may_load(storage, etf_name)?.unwrap_or_default();
+    if shares > total_shares {
+        return Err(StdError::generic_err(format!("Etf {} has only {} shares outstanding", etf_name, total_shares)));
+    }
+    let holders = SHARES
+        .prefix(etf_name)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, Uint128)>>>()?;
+
+    let mut tokens: Vec<Coin> = vec![];
+    let mut shares_left = shares;
+    for (i, (holder, holder_shares)) in holders.iter().enumerate() {
+        // the last position also covers the rounding leftovers
+        let taken = if i == holders.len() - 1 {
+            shares_left.min(*holder_shares)
+        } else {
+            holder_shares.multiply_ratio(shares, total_shares)
+        };
+        if taken.is_zero() {
+            continue
+        }
+        shares_left -= taken;
+        let depo_key = (holder.as_str(), etf_name);
+        let mut ledger = LEDGER.may_load(storage, depo_key)?.unwrap_or_default();
+        if taken == *holder_shares {
+            for c in ledger.into_iter() {
+                add_to_coins(&mut tokens, c)?;
+            }
+            LEDGER.remove(storage, depo_key);
+            INITIAL_SWAP.remove(storage, depo_key);
+            DONATIONS.remove(storage, depo_key);
+            remove_deposit(storage, height, holder, etf_name)?;
+            continue
+        }
+        for c in ledger.iter_mut() {
+            let amount = c.amount.multiply_ratio(taken, *holder_shares);
+            c.amount -= amount;
+            if !amount.is_zero() {
+                add_to_coins(&mut tokens, coin(amount.u128(), c.denom.to_owned()))?;
+            }
+        }
+        ledger.retain(|c| !c.amount.is_zero());
+        LEDGER.save(storage, depo_key, &ledger)?;
+        shrink_deposit(storage, height, holder, etf_name, taken, *holder_shares)?;
+    }
+    Ok(tokens)
+}
+
+// drops `shares` out of holder's `holder_shares`, along with the same part of their deposit and high-water mark
+fn shrink_deposit(storage: &mut dyn Storage, height: u64, holder: &str, etf_name: &str, shares: Uint128, holder_shares: Uint128) -> StdResult<()> {
+    let depo_key = (holder, etf_name);
+    let mut depo_coin = BALANCE.load(storage, depo_key)?;
+    let deposit = depo_coin.amount.multiply_ratio(shares, holder_shares);
+    depo_coin.amount -= deposit;
+    BALANCE.save(storage, depo_key, &depo_coin)?;
+    if let Some(mut donation) = DONATIONS.may_load(storage, depo_key)? {
+        donation.high_water_mark -= donation.high_water_mark.multiply_ratio(shares, holder_shares);
+        DONATIONS.save(storage, depo_key, &donation)?;
+    }
+    SHARES.save(storage, (etf_name, holder), &holder_shares.checked_sub(shares)?, height)?;
+    TOTAL_SHARES.update(storage, etf_name, height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().saturating_sub(shares))
+    })?;
+    let deposits_key = (etf_name, depo_coin.denom.as_str());
+    let total_deposits = ETF_DEPOSITS.may_load(storage, deposits_key)?.unwrap_or_default();
+    ETF_DEPOSITS.save(storage, deposits_key, &total_deposits.saturating_sub(deposit))
+}
+
+fn ensure_not_wound_down(deps: Deps, etf_name: &str) -> Result<(), ContractError> {
+    if WIND_DOWNS.has(deps.storage, etf_name) {
+        return Err(ContractError::WoundDown { val: etf_name.to_string() });
+    }
+    Ok(())
+}
+
+// pro-rata part of the basket, the last shares get whatever is left so that no dust stays behind
+fn wind_down_share(wind_down: &WindDown, shares: Uint128) -> StdResult<Vec<Coin>> {
+    if shares.is_zero() {
+        return Ok(vec![]);
+    }
+    let last = wind_down.claimed_shares.checked_add(shares)? >= wind_down.total_shares;
+    let mut tokens: Vec<Coin> = vec![];
+    for c in wind_down.basket.iter() {
+        let amount = if last {
+            let claimed = wind_down.claimed.iter().find(|claimed| claimed.denom == c.denom).map(|claimed| claimed.amount);
+            c.amount.saturating_sub(claimed.unwrap_or_default())
+        } else {
+            c.amount.multiply_ratio(shares, wind_down.total_shares)
+        };
+        if !amount.is_zero() {
+            tokens.push(coin(amount.u128(), c.denom.to_owned()));
+        }
+    }
+    Ok(tokens)
+}
+
+fn ensure_not_queued(deps: Deps, sender: &str, etf_name: &str) -> Result<(), ContractError> {
+    if QUEUED_POSITIONS.has(deps.storage, (sender, etf_name)) {
+        return Err(ContractError::RedemptionQueued { val: etf_name.to_string() });
+    }
+    Ok(())
+}
+
+// window as of now. The one that just ended carries over as the previous one, after that a new one is started
+fn current_outflow(storage: &dyn Storage, env: &Env, etf_name: &str, limit: &OutflowLimit) -> StdResult<Outflow> {
+    let outflow = match OUTFLOWS.may_load(storage, etf_name)? {
+        Some(outflow) if env.block.time < outflow.window_end => return Ok(outflow),
+        Some(outflow) if env.block.time < outflow.window_end.plus_seconds(limit.window) => Outflow {
+            capacity: Uint128::zero(),
+            outflow: Uint128::zero(),
+            previous_outflow: outflow.outflow,
+            window_end: outflow.window_end.plus_seconds(limit.window),
+        },
+        _ => Outflow {
+            capacity: Uint128::zero(),
+            outflow: Uint128::zero(),
+            previous_outflow: Uint128::zero(),
+            window_end: env.block.time.plus_seconds(limit.window),
+        },
+    };
+    Ok(Outflow {
+        capacity: checked_mul_dec(TOTAL_SHARES.may_load(storage, etf_name)?.unwrap_or_default(), limit.max_percent)?,
+        ..outflow
+    })
+}
+
+// shares redeemed over the last `window` seconds, the previous window's outflow is taken for the part
+// of it that's still in them, like ibcratelimit's two-bucket approximation
+fn rolling_outflow(env: &Env, outflow: &Outflow, limit: &OutflowLimit) -> StdResult<Uint128> {
+    let left = outflow.window_end.seconds().saturating_sub(env.block.time.seconds()).min(limit.window);
+    let carried = outflow.previous_outflow.multiply_ratio(left, limit.window);
+    Ok(outflow.outflow.checked_add(carried)?)
+}
+
+// Counts the shares against the etf's rolling window, false if they don't fit. A position above the capacity
+// of a whole window still fits once nothing was redeemed over the last window, otherwise it'd never be redeemed
+fn try_consume_outflow(storage: &mut dyn Storage, env: &Env, etf_name: &str, shares: Uint128) -> StdResult<bool> {
+    let limit = match OUTFLOW_LIMITS.may_load(storage, etf_name)? {
+        Some(limit) => limit,
+        None => return Ok(true),
+    };
+    let mut outflow = current_outflow(storage, env, etf_name, &limit)?;
+    let rolling = rolling_outflow(env, &outflow, &limit)?;
+    if rolling.checked_add(shares)? > outflow.capacity && !rolling.is_zero() {
+        return Ok(false);
+    }
+    outflow.outflow = outflow.outflow.checked_add(shares)?;
+    OUTFLOWS.save(storage, etf_name, &outflow)?;
+    Ok(true)
+}
+
+// queued redemptions are served first, later ones only get capacity once the queue is empty
+fn has_outflow_capacity(storage: &mut dyn Storage, env: &Env, etf_name: &str, shares: Uint128) -> StdResult<bool> {
+    if first_queued_redemption(storage, etf_name)?.is_some() {
+        return Ok(false);
+    }
+    try_consume_outflow(storage, env, etf_name, shares)
+}
+
+fn first_queued_redemption(storage: &dyn Storage, etf_name: &str) -> StdResult<Option<QueuedRedemption>> {
+    REDEMPTION_QUEUE
+        .prefix(etf_name)
+        .range(storage, None, None, Order::Ascending)
+        .next()
+        .transpose()
+        .map(|queued| queued.map(|(_, queued)| queued))
+}
+
+fn batch_has_requests(deps: Deps, etf_name: &str) -> bool {
+    BATCH_DEPOSITS.prefix(etf_name).keys(deps.storage, None, None, Order::Ascending).next().is_some()
+        || BATCH_REDEMPTIONS.prefix(etf_name).keys(deps.storage, None, None, Order::Ascending).next().is_some()
+}
+
+fn add_batch_claim(storage: &mut dyn Storage, sender: &str, etf_name: &str, shares: Uint128, proceeds: Option<Coin>) -> StdResult<()> {
+    let mut claim = BATCH_CLAIMS.may_load(storage, (sender, etf_name))?.unwrap_or_default();
+    claim.shares = claim.shares.checked_add(shares)?;
+    if let Some(proceeds) = proceeds.filter(|c| !c.amount.is_zero()) {
+        add_to_coins(&mut claim.proceeds, proceeds)?;
+    }
+    BATCH_CLAIMS.save(storage, (sender, etf_name), &claim)
+}
+
+fn create_submsg_batch_sell(storage: &dyn Storage, swap_addr: &Addr, cache: &BatchCache) -> Result<SubMsg<Empty>, ContractError> {
+    let (intermediate_denom, initial_pool_id) = get_initial_route_params(&cache.config.deposit_denom)?;
+    let execute_message = create_msg_execute_swap(
+        storage, swap_addr.to_string(), initial_pool_id, cache.config.deposit_denom.to_owned(), coin(cache.intermediate.u128(), intermediate_denom))?;
+    Ok(SubMsg::reply_on_success(execute_message, EXECUTE_BATCH_SELL_REPLY_ID))
+}
+
+fn validate_etf_swap_routes(deps: Deps, etf_swap_routes: &EtfSwapRoutes, deposit_denom: &str) -> Result<(), ContractError> {
+    if !etf_exists(deps, &etf_swap_routes.name) {
+        return Err(ContractError::MintContractNotFound{val: etf_swap_routes.name.to_owned()});
+    }
+    ensure_not_wound_down(deps, &etf_swap_routes.name)?;
+    // indexed etfs get their routes and ratios filled in at purchase time
+    if let Some(methodology) = INDEX_METHODOLOGIES.may_load(deps.storage, &etf_swap_routes.name)? {
+        if !etf_swap_routes.routes.is_empty() || !etf_swap_routes.ratios.is_empty() {
+            return Err(ContractError::IndexedEtf { val: etf_swap_routes.name.to_owned() });
+        }
+        if methodology.deposit_denom != deposit_denom {
+            return Err(ContractError::InvalidDepositDenom { val: deposit_denom.to_string() });
+        }
+        return Ok(());
+    }
+    // validate length of provided routes and ratios vectors
+    if etf_swap_routes.ratios.len() != etf_swap_routes.routes.len() {
+        return Err(ContractError::InvalidEntryParams{});
+    }
+    // validate sum of ratios
+    let ratios_sum: Uint128 = etf_swap_routes.ratios.iter().sum();
+    if ratios_sum != Uint128::from(100u128) {
+        return Err(ContractError::InvalidRatio{});
+    }
+    Ok(())
+}
 
-    // find pool for reverting transactions
-    let depo_coin = BALANCE.load(deps.storage, (&etf_name_cache.sender, &etf_name_cache.etf_name))?;
-    let (_, exit_pool_id) = get_initial_route_params(&depo_coin.denom)?;
+// components the index currently weighs at 0% are left out of the purchase
+fn with_index_weights(deps: Deps, etf_swap_routes: EtfSwapRoutes) -> Result<EtfSwapRoutes, ContractError> {
+    let methodology = match INDEX_METHODOLOGIES.may_load(deps.storage, &etf_swap_routes.name)? {
+        Some(methodology) => methodology,
+        None => return Ok(etf_swap_routes),
+    };
+    let weights = INDEX_WEIGHTS.load(deps.storage, &etf_swap_routes.name)?;
+    let (routes, ratios) = methodology.components.into_iter()
+        .zip(weights.ratios)
+        .filter(|(_, ratio)| !ratio.is_zero())
+        .map(|(component, ratio)| (component.route, ratio))
+        .unzip();
+    Ok(EtfSwapRoutes { name: etf_swap_routes.name, routes, ratios })
+}
 
-    // use cache that stores all uosmo swapped back through messages created above
-    let revert_swap_cache = REVERT_SWAP_CACHE.load(deps.storage)?;
-    LEDGER.remove(deps.storage, (&etf_name_cache.sender, &etf_name_cache.etf_name));
-    let execute_message = create_msg_execute_swap(
-        swap_addr.to_string(), exit_pool_id, depo_coin.denom, 
-        revert_swap_cache.coin_to_revert);
-    let submessage:SubMsg<Empty> = SubMsg::reply_on_success(execute_message, EXECUTE_REDEEM_SWAP_REPLY_ID);
+// TWAP based, so weights can't be skewed by moving spot prices right before a rebalance
+fn compute_index_ratios(deps: Deps, env: &Env, methodology: &IndexMethodology) -> StdResult<Vec<Uint128>> {
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    let mut values = vec![];
+    for component in methodology.components.iter() {
+        let denom = &component.route.token_out_denom;
+        let value = match methodology.weighting {
+            Weighting::Equal => Uint128::one(),
+            Weighting::MarketCap => {
+                let supply = BankQuerier::new(&deps.querier).supply_of(denom.to_owned())?.amount
+                    .map(Coin::try_from)
+                    .transpose()?
+                    .map(|c| c.amount)
+                    .unwrap_or_default();
+                value_ledger(deps, &[coin(supply.u128(), denom)], &methodology.deposit_denom, Some(twap_start))?
+            },
+            Weighting::Liquidity => {
+                let liquidity = match pair_dex(deps, component.route.pool_id)? {
+                    Some(dex) => {
+                        let pool: PoolInfo = deps.querier
+                            .query_wasm_smart(SWAP_CONTRACT.load(deps.storage)?, &swap_msg::QueryMsg::DexPoolInfo { dex })?;
+                        pool.assets
+                    },
+                    None => GammV1Beta1Querier::new(&deps.querier)
+                        .total_pool_liquidity(component.route.pool_id)?
+                        .liquidity.into_iter()
+                        .map(Coin::try_from)
+                        .collect::<StdResult<Vec<Coin>>>()?,
+                };
+                value_ledger(deps, &liquidity, &methodology.deposit_denom, Some(twap_start))?
+            },
+        };
+        values.push(value);
+    }
+    let caps: Vec<Decimal> = methodology.components.iter().map(|c| c.cap.unwrap_or_else(Decimal::one)).collect();
+    Ok(to_percent_ratios(&cap_weights(&values, &caps)))
+}
 
-    let mint_contract = MINT_CONTRACTS.load(deps.storage, &etf_name_cache.etf_name)?;
-    
-    let burn_tokens = create_msg_burn_tokens(&mint_contract, depo_coin.amount)?;
-    BALANCE.update(
-        deps.storage,
-        (&etf_name_cache.sender, &etf_name_cache.etf_name),
-        |balance: Option<Coin>| -> StdResult<_> {
-            Ok(coin(balance.to_owned().unwrap_or_default().amount.checked_sub(depo_coin.amount)?.into(), balance.unwrap_or_default().denom))
-        },
-    )?;
+const WEIGHT_PRECISION: u128 = 1_000_000;
 
-    Ok(Response::default()
-        .add_submessage(submessage)
-        .add_message(burn_tokens)
-    )
- }
+// Weights in millionths adding up to 1, proportional to values. Components above their cap are held at it
+// and the excess goes to the others, repeated until nothing is above its cap
+fn cap_weights(values: &[Uint128], caps: &[Decimal]) -> Vec<Uint128> {
+    let precision = Uint128::new(WEIGHT_PRECISION);
+    let caps: Vec<Uint128> = caps.iter().map(|cap| precision * *cap).collect();
+    let mut capped = vec![false; values.len()];
+    let mut weights = vec![Uint128::zero(); values.len()];
+    loop {
+        let left = precision.saturating_sub((0..values.len()).filter(|&i| capped[i]).map(|i| caps[i]).sum());
+        let free: Vec<usize> = (0..values.len()).filter(|&i| !capped[i]).collect();
+        let free_value: Uint128 = free.iter().map(|&i| values[i]).sum();
+        for &i in free.iter() {
+            weights[i] = if free_value.is_zero() {
+                left.multiply_ratio(1u128, free.len() as u128)
+            } else {
+                left.multiply_ratio(values[i], free_value)
+            };
+        }
+        let over: Vec<usize> = free.into_iter().filter(|&i| weights[i] > caps[i]).collect();
+        if over.is_empty() {
+            return weights;
+        }
+        for i in over {
+            capped[i] = true;
+            weights[i] = caps[i];
+        }
+    }
+}
 
- fn handle_redeem_swap(deps: DepsMut, msg: Reply) -> Result<Response, ContractError>  {
-    let tokens_out_string = split_result_no_regex(parse_swap_reply(&msg));
-    let tokens_out = coin(tokens_out_string.0.parse::<u128>().unwrap(), tokens_out_string.1);
-    let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
-    let etf_name_cache = ETF_NAME_CACHE.load(deps.storage)?;
+// whole percents adding up to 100, leftover points go to the largest remainders
+fn to_percent_ratios(weights: &[Uint128]) -> Vec<Uint128> {
+    let per_percent = Uint128::new(WEIGHT_PRECISION / 100);
+    let mut ratios: Vec<Uint128> = weights.iter().map(|w| *w / per_percent).collect();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by(|&a, &b| (weights[b] % per_percent).cmp(&(weights[a] % per_percent)));
+    let missing = Uint128::new(100).saturating_sub(ratios.iter().sum());
+    for &i in by_remainder.iter().cycle().take(missing.u128() as usize) {
+        ratios[i] += Uint128::one();
+    }
+    ratios
+}
 
-    let send_tokens_back = create_msg_send_tokens_back(&swap_addr, vec![tokens_out.clone()], etf_name_cache.sender.to_owned())?;
-    REVERT_SWAP_CACHE.remove(deps.storage);
-    ETF_NAME_CACHE.remove(deps.storage);
-    Ok(Response::default()
-        .add_message(send_tokens_back)
-        .add_attribute("denom_returned", tokens_out.denom)
-        .add_attribute("amount_returned", tokens_out.amount)
-        .add_attribute("returned_to", etf_name_cache.sender))
- }
+fn join_ratios(ratios: &[Uint128]) -> String {
+    ratios.iter().map(|r| r.to_string()).collect::<Vec<String>>().join(",")
+}
 
-// ----------------------------------- QUERIES
-// ##############################################################################
+// TWAP based, anyone can trigger a donation so spot prices could be pushed around for it
+fn position_value(deps: Deps, env: &Env, holder: &str, etf_name: &str) -> StdResult<Uint128> {
+    let ledger = match LEDGER.may_load(deps.storage, (holder, etf_name))? {
+        Some(ledger) => ledger,
+        None => return Ok(Uint128::zero()),
+    };
+    let deposit = BALANCE.load(deps.storage, (holder, etf_name))?;
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    value_ledger(deps, &ledger, &deposit.denom, Some(twap_start))
+}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetTokens {sender, etf_type} => to_binary(&query_get_tokens(deps, sender, etf_type)?),
-        QueryMsg::GetInitialSwap {sender} => to_binary(&query_get_initial_swap(deps, sender)?),
-        QueryMsg::GetBalance {sender, etf_type} => to_binary(&query_balance(deps, &sender, etf_type)?),
+// Moves the donated part of holder's growth above the high-water mark out of their ledger, in kind.
+// Closing positions start again from zero
+fn take_donation(deps: DepsMut, env: &Env, holder: &str, etf_name: &str, closing: bool) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut donation = match DONATIONS.may_load(deps.storage, (holder, etf_name))? {
+        Some(donation) => donation,
+        None => return Ok(vec![]),
+    };
+    let mut msgs = vec![];
+    // beneficiaries removed by the owner don't get anything anymore
+    let whitelisted = DONATION_BENEFICIARIES.may_load(deps.storage, etf_name)?.unwrap_or_default().contains(&donation.beneficiary);
+    let nav = position_value(deps.as_ref(), env, holder, etf_name)?;
+    if whitelisted && nav > donation.high_water_mark {
+        let donated_value = (nav - donation.high_water_mark) * donation.percent;
+        let mut ledger = LEDGER.load(deps.storage, (holder, etf_name))?;
+        let mut donated: Vec<Coin> = vec![];
+        for c in ledger.iter_mut() {
+            let amount = c.amount.multiply_ratio(donated_value, nav);
+            if amount.is_zero() {
+                continue
+            }
+            c.amount -= amount;
+            donated.push(coin(amount.u128(), c.denom.to_owned()));
+            DONATED.update(deps.storage, (&donation.beneficiary, &c.denom), |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_add(amount)?)
+            })?;
+        }
+        if !donated.is_empty() {
+            ledger.retain(|c| !c.amount.is_zero());
+            LEDGER.save(deps.storage, (holder, etf_name), &ledger)?;
+            let swap_addr = SWAP_CONTRACT.load(deps.storage)?;
+            msgs.push(create_msg_send_tokens_back(&swap_addr, donated, donation.beneficiary.to_owned())?);
+        }
+        donation.high_water_mark = nav - donated_value;
+    }
+    if closing {
+        donation.high_water_mark = Uint128::zero();
     }
+    DONATIONS.save(deps.storage, (holder, etf_name), &donation)?;
+    Ok(msgs)
 }
 
-fn query_get_tokens(deps: Deps, sender: String, etf_type: String) -> StdResult<GetTokensResponse> {
-    let res = LEDGER.load(deps.storage, (&sender, &etf_type))?;
-    Ok(GetTokensResponse { tokens_per_etf: res })
+fn etf_exists(deps: Deps, etf_name: &str) -> bool {
+    MINT_CONTRACTS.has(deps.storage, etf_name) || NATIVE_DENOMS.has(deps.storage, etf_name)
 }
 
-fn query_get_initial_swap(deps: Deps, sender: String) -> StdResult<GetInitialSwapResponse> {
-    let res = INITIAL_SWAP.load(deps.storage, &sender)?;
-    Ok(GetInitialSwapResponse {initial_swap: res})
+enum Operation {
+    Buys,
+    Redemptions,
 }
 
-fn query_balance(deps: Deps, sender: &String, etf_type: String) -> StdResult<GetBalanceResponse> {
-    let res = BALANCE.load(deps.storage, (&sender, &etf_type))?;
-    Ok(GetBalanceResponse {  balance: res })
+fn ensure_owner(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    // contracts migrated from 0.1.0 get their owner set by the migration
+    if OWNER.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
 }
 
-// ----------------------------------- HELPER FUNCTIONS
-// ##############################################################################
-
-fn create_msg_execute_swap(contract: String, 
-    pool_id: u64, 
-    token_out_denom: String, 
-    token_in: Coin,
-    ) -> cosmwasm_std::WasmMsg {
-    let execute_message = WasmMsg::Execute {
-        contract_addr: contract.clone(),
-        funds: vec![],
-        msg: to_binary(&osmo_swap::msg::ExecuteMsg::ExecuteSwapExactAmountIn {
-            routes: vec![SwapAmountInRoute {
-                pool_id: pool_id, 
-                token_out_denom: token_out_denom
-            }],
-            token_in: Some(coin(
-        token_in.amount.into(), 
-        token_in.denom.clone()).into()
-        ),
-        // TODO add twap query in order to estimate token_out_min_amount
-        token_out_min_amount: "1".to_string()
-          }).unwrap()
-
-    };
-    execute_message   
+fn ensure_not_paused(deps: Deps, operation: Operation) -> Result<(), ContractError> {
+    let paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    match operation {
+        Operation::Buys if paused.buys => Err(ContractError::Paused { val: "buys".to_string() }),
+        Operation::Redemptions if paused.redemptions => Err(ContractError::Paused { val: "redemptions".to_string() }),
+        _ => Ok(()),
+    }
 }
 
+fn ensure_recovered_from_downtime(deps: Deps) -> Result<(), ContractError> {
+    if !recovered_from_downtime(deps)? {
+        return Err(ContractError::DowntimeRecovery {});
+    }
+    Ok(())
+}
 
-fn create_msg_execute_mint_tokens(        
-    recipient: String,
-    amount_to_mint: Uint128,
-    mint_contract_address: String,
-) -> WasmMsg { 
+// TWAPs and pool prices can't be trusted right after the chain has been down
+fn recovered_from_downtime(deps: Deps) -> StdResult<bool> {
+    match DOWNTIME_BREAKER.may_load(deps.storage)? {
+        Some(breaker) => Ok(DowntimedetectorQuerier::new(&deps.querier)
+            .recovered_since_downtime_of_length(
+                breaker.downtime,
+                Some(Duration { seconds: breaker.recovery_seconds, nanos: 0 }),
+            )?
+            .succesfully_recovered),
+        None => Ok(true),
+    }
+}
 
-    WasmMsg::Execute {
-        contract_addr: mint_contract_address.to_string(),
-        funds: vec![],
-        msg: to_binary(&cw20_base::msg::ExecuteMsg::Mint {
-            recipient: recipient.to_owned(),
-            amount: amount_to_mint,
-        }).unwrap()
+fn ensure_within_etf_limits(deps: Deps, env: &Env, sender: &str, etf_name: &str, deposit: &Coin) -> Result<(), ContractError> {
+    let limits = match ETF_LIMITS.may_load(deps.storage, etf_name)? {
+        Some(limits) => limits,
+        None => return Ok(()),
+    };
+    // priced over the same window as nav orders, so a single block can't move deposits past the limits
+    let twap_start = env.block.time.minus_seconds(nav_twap_window(deps)?);
+    let deposit_value = value_in_quote_denom(deps, &limits, std::slice::from_ref(deposit), Some(twap_start))?;
+    if let Some(min) = limits.min_deposit {
+        if deposit_value < min {
+            return Err(ContractError::DepositTooSmall { val: deposit_value, min });
+        }
+    }
+    if let Some(max) = limits.max_deposit {
+        if deposit_value > max {
+            return Err(ContractError::DepositTooLarge { val: deposit_value, max });
+        }
     }
+    if let Some(cap) = limits.max_per_user {
+        let balance: Vec<Coin> = BALANCE.may_load(deps.storage, (sender, etf_name))?.into_iter().collect();
+        let user_value = value_in_quote_denom(deps, &limits, &balance, Some(twap_start))?.checked_add(deposit_value).map_err(StdError::from)?;
+        if user_value > cap {
+            return Err(ContractError::UserCapExceeded { val: user_value, cap });
+        }
+    }
+    if let Some(cap) = limits.max_total {
+        let total_deposits = load_etf_deposits(deps, etf_name)?;
+        let total_value = value_in_quote_denom(deps, &limits, &total_deposits, Some(twap_start))?.checked_add(deposit_value).map_err(StdError::from)?;
+        if total_value > cap {
+            return Err(ContractError::TotalCapExceeded { val: total_value, cap });
+        }
+    }
+    Ok(())
 }
 
-fn create_msg_burn_tokens(
-    mint_contract_address: &String, 
-    amount: Uint128
-) -> StdResult<CosmosMsg> {
-    Ok(WasmMsg::Execute {
-        contract_addr: mint_contract_address.to_string(),
-        funds: vec![],
-        msg: to_binary(&cw20_base::msg::ExecuteMsg::Burn {
-            amount,
-        }).unwrap(),
-    }.into())
+fn load_etf_deposits(deps: Deps, etf_name: &str) -> StdResult<Vec<Coin>> {
+    ETF_DEPOSITS
+        .prefix(etf_name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| coin(amount.u128(), denom)))
+        .filter(|item| item.as_ref().map_or(true, |c| !c.amount.is_zero()))
+        .collect()
 }
 
-fn create_msg_send_tokens_back(
-    swap_addr: &Addr, 
-    tokens_back: Vec<Coin>,
-    recipient: String
-) -> StdResult<CosmosMsg> {
-    Ok(WasmMsg::Execute {
-        contract_addr: swap_addr.to_string(),
-        funds: vec![],
-        msg: to_binary(&osmo_swap::msg::ExecuteMsg::SendTokensBack { tokens: tokens_back, recipient: recipient }).unwrap(),
-    }.into())
+// with spot prices or TWAPs since twap_start, like ledgers
+fn value_in_quote_denom(deps: Deps, limits: &EtfLimits, coins: &[Coin], twap_start: Option<Timestamp>) -> Result<Uint128, ContractError> {
+    let mut value = Uint128::zero();
+    for c in coins.iter() {
+        let coin_value = if c.denom == limits.quote_denom {
+            c.amount
+        } else {
+            let price_pool = limits.price_pools.iter()
+                .find(|p| p.denom == c.denom)
+                .ok_or_else(|| ContractError::PricePoolNotFound { val: c.denom.clone() })?;
+            let pool = PoolInfo::query(&deps.querier, price_pool.pool_id)?;
+            let price = pool_price(deps, &pool, &c.denom, &limits.quote_denom, twap_start)?;
+            checked_mul_dec(c.amount, price)?
+        };
+        value = value.checked_add(coin_value).map_err(StdError::from)?;
+    }
+    Ok(value)
+}
+
+fn ensure_older_version(stored: &ContractVersion, target_version: &str) -> Result<(), ContractError> {
+    let stored_version: Version = stored.version.parse()?;
+    let target: Version = target_version.parse()?;
+    if stored.contract != CONTRACT_NAME || stored_version >= target {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract.to_owned(),
+            previous_version: stored.version.to_owned(),
+            target_version: target_version.to_string(),
+        });
+    }
+    Ok(())
 }
 
 fn split_result_no_regex(coin_str: String) -> (String, String) {
@@ -598,15 +3454,143 @@ fn get_initial_route_params(deposit_denom: &String) -> Result<(String, u64), Con
     Ok((deposit_token_out_denom, pool_id))
 }
 
+// balancer and stableswap pools are both accepted, as are pair contracts routed in their place
+fn validate_routes(deps: Deps, swap_addr: &Addr, routes: &[Route]) -> Result<(), ContractError> {
+    for route in routes.iter() {
+        let query = match PAIR_ROUTES.may_load(deps.storage, route.pool_id)? {
+            Some(pair) => swap_msg::QueryMsg::DexPoolInfo { dex: Dex::Pair { contract: pair.into_string() } },
+            None => swap_msg::QueryMsg::QueryPoolInfo { pool_id: route.pool_id },
+        };
+        let pool: PoolInfo = deps.querier.query_wasm_smart(swap_addr.to_owned(), &query)?;
+        if !pool.has_denom(&route.token_out_denom) && pool.total_shares.denom != route.token_out_denom {
+            return Err(ContractError::PoolTokenNotFound { val: route.token_out_denom.to_owned() })
+        }
+    }
+    Ok(())
+}
+
+// pool id of gamm/pool/N share denoms
+fn pool_share_id(denom: &str) -> Option<u64> {
+    denom.strip_prefix("gamm/pool/")?.parse().ok()
+}
+
+fn parse_wasm_attribute(msg: &Reply, key: &str) -> Result<String, ContractError> {
+    msg.result.clone()
+        .into_result()
+        .map_err(|val| ContractError::CustomError { val })?
+        .events.iter()
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.to_owned())
+        .ok_or_else(|| ContractError::NotFound { val: key.to_string() })
+}
+
+// sums up tokens coming back to the intermediate denom during redemption
+fn add_to_revert_swap_cache(storage: &mut dyn Storage, tokens: Coin) -> Result<Uint128, ContractError> {
+    let updated = match REVERT_SWAP_CACHE.may_load(storage)? {
+        Some(mut rev_coin) => {
+            rev_coin.coin_to_revert.amount = rev_coin.coin_to_revert.amount.checked_add(tokens.amount).map_err(StdError::from)?;
+            REVERT_SWAP_CACHE.save(storage, &rev_coin)?;
+            rev_coin.coin_to_revert.amount
+        },
+        None => {
+            REVERT_SWAP_CACHE.save(storage, &SwapCache { coin_to_revert: tokens.to_owned() })?;
+            tokens.amount
+        },
+    };
+    Ok(updated)
+}
+
+// refunds are dispatched in cache order, so each reply takes the first one left
+fn handle_ibc_refund_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res: MsgTransferResponse = msg.result.try_into()?;
+    let mut pending = IBC_REFUND_CACHE.load(deps.storage)?;
+    let refund = pending.remove(0);
+    if pending.is_empty() {
+        IBC_REFUND_CACHE.remove(deps.storage);
+    } else {
+        IBC_REFUND_CACHE.save(deps.storage, &pending)?;
+    }
+    IBC_REFUNDS.save(deps.storage, (&refund.channel, res.sequence), &refund)?;
+
+    Ok(Response::new()
+        .add_attribute("ibc_refund_channel", refund.channel)
+        .add_attribute("ibc_refund_sequence", res.sequence.to_string()))
+}
+
+// a failed purchase is rolled back, its deposit goes back to the owner and the plan is paused until they resume it
+fn handle_dca_purchase_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let mut pending = DCA_PURCHASE_CACHE.load(deps.storage)?;
+    let purchase = pending.remove(0);
+    if pending.is_empty() {
+        DCA_PURCHASE_CACHE.remove(deps.storage);
+    } else {
+        DCA_PURCHASE_CACHE.save(deps.storage, &pending)?;
+    }
+    let err = match msg.result {
+        SubMsgResult::Ok(_) => {
+            let bounty_msg = (!purchase.bounty.amount.is_zero())
+                .then(|| BankMsg::Send { to_address: purchase.keeper.to_owned(), amount: vec![purchase.bounty] });
+            return Ok(Response::new().add_messages(bounty_msg))
+        },
+        SubMsgResult::Err(err) => err,
+    };
+
+    let key = (purchase.owner.as_str(), purchase.plan_id);
+    if let Some(mut plan) = DCA_PLANS.may_load(deps.storage, key)? {
+        unschedule_dca_plan(deps.storage, &plan);
+        plan.paused = true;
+        DCA_PLANS.save(deps.storage, key, &plan)?;
+    }
+
+    // the keeper's bounty goes back with the deposit
+    let mut refund = vec![purchase.deposit];
+    add_to_coins(&mut refund, purchase.bounty)?;
+    Ok(Response::new()
+        .add_message(BankMsg::Send { to_address: purchase.owner.to_owned(), amount: refund })
+        .add_attribute("dca_purchase_failed", purchase.plan_id.to_string())
+        .add_attribute("error", err))
+}
+
 fn parse_swap_reply(msg: &Reply) -> String {
     msg.result.clone()
     .unwrap()
     .events.iter()
-    .filter(|event| event.ty == "token_swapped" && event.attributes[4].key == "tokens_out")
-    .map(|p| p.attributes[4].value.clone())
+    .filter(|event| TokenSwapped::is(event))
+    .filter_map(|event| event.attributes.iter().find(|a| a.key == TOKENS_OUT))
+    .map(|a| a.value.clone())
     .collect()
 }
 
+// (tokens_in, tokens_out) of every pool swap in the reply, in execution order
+fn parse_swapped_tokens(msg: &Reply) -> Result<Vec<(Coin, Coin)>, ContractError> {
+    let events = msg.result.clone().into_result().map_err(|val| ContractError::CustomError { val })?.events;
+    events.iter()
+        .filter(|event| TokenSwapped::is(event))
+        .map(|event| {
+            let find_coin = |key: &str| -> Result<Coin, ContractError> {
+                let value = event.attributes.iter()
+                    .find(|a| a.key == key)
+                    .ok_or_else(|| ContractError::DenomParsingError { val: key.to_string() })?
+                    .value.to_owned();
+                let (amount, denom) = split_result_no_regex(value);
+                let amount = amount.parse::<u128>().map_err(|_| ContractError::DenomParsingError { val: amount })?;
+                Ok(coin(amount, denom))
+            };
+            Ok((find_coin(TOKENS_IN)?, find_coin(TOKENS_OUT)?))
+        })
+        .collect()
+}
+
+fn add_to_coins(coins: &mut Vec<Coin>, token: Coin) -> StdResult<()> {
+    match coins.iter_mut().find(|c| c.denom == token.denom) {
+        Some(c) => c.amount = c.amount.checked_add(token.amount)?,
+        None => coins.push(token),
+    }
+    Ok(())
+}
+
 fn update_ledger(deps: &DepsMut, depo_key: (&str, &str), amount_swapped: String, denom_swapped: String) -> Vec<Coin> {
     let mut new_ledger: Vec<Coin> = vec![];
     let mut found = false;
@@ -626,4 +3610,512 @@ fn update_ledger(deps: &DepsMut, depo_key: (&str, &str), amount_swapped: String,
         new_ledger.push(coin(amount_swapped.parse::<u128>().unwrap(), denom_swapped.to_owned()));
     }
     new_ledger
-}
\ No newline at end of file
+}
+#[cfg(test)]
+pub(crate) mod tests {
+    use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{coins, from_slice, ContractResult, OwnedDeps, Querier, QueryRequest, SystemResult};
+    use osmosis_std::types::osmosis::downtimedetector::v1beta1::RecoveredSinceDowntimeOfLengthResponse;
+    use osmosis_std::shim::Any;
+    use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+    use osmosis_std::types::osmosis::gamm::v1beta1::{
+        Pool as BalancerPool, PoolAsset, PoolParams, QueryPoolRequest, QueryPoolResponse, QueryPoolTypeResponse,
+    };
+    use osmosis_std::types::osmosis::twap::v1beta1::{ArithmeticTwapRequest, ArithmeticTwapResponse};
+    use prost::Message;
+    use std::marker::PhantomData;
+
+    use super::*;
+    use crate::msg::IndexComponent;
+
+    // answers downtimedetector queries with a fixed recovery state, everything else goes to the mock querier
+    struct DowntimeQuerier {
+        base: MockQuerier,
+        recovered: bool,
+    }
+
+    impl Querier for DowntimeQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> cosmwasm_std::QuerierResult {
+            match from_slice::<QueryRequest<Empty>>(bin_request) {
+                Ok(QueryRequest::Stargate { path, .. })
+                    if path == "/osmosis.downtimedetector.v1beta1.Query/RecoveredSinceDowntimeOfLength" =>
+                {
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&RecoveredSinceDowntimeOfLengthResponse { succesfully_recovered: self.recovered }).unwrap(),
+                    ))
+                }
+                _ => self.base.raw_query(bin_request),
+            }
+        }
+    }
+
+    // prices every pool as if it held all denoms, at their ratio of uosmo values
+    pub(crate) struct PoolQuerier {
+        base: DowntimeQuerier,
+        osmo_values: Vec<(&'static str, Decimal)>,
+    }
+
+    impl PoolQuerier {
+        fn value(&self, denom: &str) -> Decimal {
+            self.osmo_values.iter().find(|(d, _)| *d == denom).unwrap().1
+        }
+    }
+
+    impl Querier for PoolQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> cosmwasm_std::QuerierResult {
+            let res = match from_slice::<QueryRequest<Empty>>(bin_request) {
+                Ok(QueryRequest::Stargate { path, .. }) if path == "/osmosis.gamm.v1beta1.Query/PoolType" =>
+                    to_binary(&QueryPoolTypeResponse { pool_type: "Balancer".to_string() }),
+                Ok(QueryRequest::Stargate { path, data }) if path == "/osmosis.gamm.v1beta1.Query/Pool" => {
+                    let id = QueryPoolRequest::try_from(data).unwrap().pool_id;
+                    let pool = BalancerPool {
+                        id,
+                        pool_params: Some(PoolParams { swap_fee: "0".to_string(), exit_fee: "0".to_string(), ..Default::default() }),
+                        total_shares: Some(ProtoCoin { denom: format!("gamm/pool/{}", id), amount: "1".to_string() }),
+                        pool_assets: self.osmo_values.iter()
+                            .map(|(denom, _)| PoolAsset {
+                                token: Some(ProtoCoin { denom: denom.to_string(), amount: "1".to_string() }),
+                                weight: "1".to_string(),
+                            })
+                            .collect(),
+                        ..Default::default()
+                    };
+                    to_binary(&QueryPoolResponse { pool: Some(Any { type_url: BalancerPool::TYPE_URL.to_string(), value: pool.encode_to_vec() }) })
+                },
+                Ok(QueryRequest::Stargate { path, data }) if path == "/osmosis.twap.v1beta1.Query/ArithmeticTwap" => {
+                    let req = ArithmeticTwapRequest::try_from(data).unwrap();
+                    let price = self.value(&req.base_asset) / self.value(&req.quote_asset);
+                    to_binary(&ArithmeticTwapResponse { arithmetic_twap: price.atomics().to_string() })
+                },
+                _ => return self.base.raw_query(bin_request),
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        }
+    }
+
+    pub(crate) fn mock_dependencies_with_prices(osmo_values: Vec<(&'static str, Decimal)>) -> OwnedDeps<MockStorage, MockApi, PoolQuerier> {
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: PoolQuerier { base: DowntimeQuerier { base: MockQuerier::new(&[]), recovered: true }, osmo_values },
+            custom_query_type: PhantomData,
+        }
+    }
+
+    fn setup_with_prices(osmo_values: Vec<(&'static str, Decimal)>) -> OwnedDeps<MockStorage, MockApi, PoolQuerier> {
+        let mut deps = mock_dependencies_with_prices(osmo_values);
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), InstantiateMsg {}).unwrap();
+        deps
+    }
+
+    fn setup_with_downtime(recovered: bool) -> OwnedDeps<MockStorage, MockApi, DowntimeQuerier> {
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: DowntimeQuerier { base: MockQuerier::new(&[]), recovered },
+            custom_query_type: PhantomData,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), InstantiateMsg {}).unwrap();
+        let breaker = DowntimeBreaker { downtime: Downtime::Duration30m as i32, recovery_seconds: 600 };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::SetDowntimeBreaker { breaker: Some(breaker) })
+            .unwrap();
+        deps
+    }
+
+    #[test]
+    fn buys_are_rejected_while_recovering_from_downtime() {
+        let mut deps = setup_with_downtime(false);
+        let swap = ExecuteMsg::SwapTokens {
+            initial_balance: coin(1_000, "uosmo"),
+            etf_swap_routes: EtfSwapRoutes {
+                name: "etf".to_string(),
+                routes: vec![Route { pool_id: 1, token_out_denom: "uatom".to_string() }],
+                ratios: vec![Uint128::new(100)],
+            },
+            on_behalf_of: None,
+            ibc_sender: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &coins(1_000, "uosmo")), swap).unwrap_err();
+        assert!(matches!(err, ContractError::DowntimeRecovery {}));
+
+        // the contract's own callback is held back as well
+        let env = mock_env();
+        let callback = ExecuteMsg::Callback { operands: vec![] };
+        let err = execute(deps.as_mut(), env.to_owned(), mock_info(env.contract.address.as_str(), &[]), callback).unwrap_err();
+        assert!(matches!(err, ContractError::DowntimeRecovery {}));
+
+        let mut deps = setup_with_downtime(true);
+        let callback = ExecuteMsg::Callback { operands: vec![] };
+        execute(deps.as_mut(), env.to_owned(), mock_info(env.contract.address.as_str(), &[]), callback).unwrap();
+    }
+
+    fn save_dca_plan(deps: DepsMut, owner: &str, id: u64, next_execution: Timestamp) {
+        let plan = DcaPlan {
+            id,
+            etf_swap_routes: EtfSwapRoutes {
+                name: "etf".to_string(),
+                routes: vec![Route { pool_id: 1, token_out_denom: "uatom".to_string() }],
+                ratios: vec![Uint128::new(100)],
+            },
+            amount_per_period: coin(1_000, "uosmo"),
+            period: 3600,
+            periods_left: 3,
+            next_execution,
+            paused: false,
+        };
+        DCA_PLANS.save(deps.storage, (owner, id), &plan).unwrap();
+        schedule_dca_plan(deps.storage, owner, &plan).unwrap();
+    }
+
+    #[test]
+    fn execute_due_only_goes_through_due_plans() {
+        let mut deps = setup_with_downtime(true);
+        let env = mock_env();
+        for id in 1..=12 {
+            save_dca_plan(deps.as_mut(), "alice", id, env.block.time.minus_seconds(id));
+        }
+        save_dca_plan(deps.as_mut(), "bob", 13, env.block.time.plus_seconds(1));
+
+        let res = execute(deps.as_mut(), env.to_owned(), mock_info("keeper", &[]), ExecuteMsg::ExecuteDue {}).unwrap();
+        assert_eq!(res.messages.len(), MAX_DUE_PURCHASES);
+        assert!(res.attributes.contains(&attr("executed", MAX_DUE_PURCHASES.to_string())));
+        // longest overdue plans go first
+        let purchased: Vec<u64> = DCA_PURCHASE_CACHE.load(deps.as_ref().storage).unwrap().iter().map(|p| p.plan_id).collect();
+        assert_eq!(purchased, (3..=12).rev().collect::<Vec<u64>>());
+        let next = DCA_PLANS.load(deps.as_ref().storage, ("alice", 12)).unwrap().next_execution;
+        assert_eq!(next, env.block.time.minus_seconds(12).plus_seconds(3600));
+
+        // what's left is the two plans over the limit, the one that isn't due and the next periods
+        let scheduled: Vec<u64> = DCA_SCHEDULE
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .map(|item| item.unwrap().0.1)
+            .collect();
+        assert_eq!(scheduled[..3], [2, 1, 13]);
+        assert_eq!(scheduled.len(), 13);
+    }
+
+    #[test]
+    fn failed_dca_purchase_pauses_its_plan_and_refunds_the_deposit() {
+        let mut deps = setup_with_downtime(true);
+        let env = mock_env();
+        save_dca_plan(deps.as_mut(), "alice", 1, env.block.time);
+        save_dca_plan(deps.as_mut(), "bob", 2, env.block.time);
+        let res = execute(deps.as_mut(), env.to_owned(), mock_info("keeper", &[]), ExecuteMsg::ExecuteDue {}).unwrap();
+        assert_eq!(res.messages[0].reply_on, cosmwasm_std::ReplyOn::Always);
+
+        // no bounty is paid before the purchases went through
+        assert!(res.messages.iter().all(|m| matches!(m.msg, CosmosMsg::Wasm(_))));
+
+        // alice's purchase goes through and pays the keeper, bob's fails without reverting it
+        let res = reply(deps.as_mut(), env.to_owned(), Reply {
+            id: DCA_PURCHASE_REPLY_ID,
+            result: SubMsgResult::Ok(cosmwasm_std::SubMsgResponse { events: vec![], data: None }),
+        })
+        .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send { to_address: "keeper".to_string(), amount: coins(1, "uosmo") })]);
+        let res = reply(deps.as_mut(), env.to_owned(), Reply {
+            id: DCA_PURCHASE_REPLY_ID,
+            result: SubMsgResult::Err("swap failed".to_string()),
+        })
+        .unwrap();
+        assert_eq!(res.messages, vec![SubMsg::new(BankMsg::Send { to_address: "bob".to_string(), amount: coins(1000, "uosmo") })]);
+        assert!(!DCA_PURCHASE_CACHE.exists(deps.as_ref().storage));
+
+        let bob_plan = DCA_PLANS.load(deps.as_ref().storage, ("bob", 2)).unwrap();
+        assert!(bob_plan.paused);
+        assert!(!DCA_PLANS.load(deps.as_ref().storage, ("alice", 1)).unwrap().paused);
+        let scheduled: Vec<u64> = DCA_SCHEDULE
+            .range(deps.as_ref().storage, None, None, Order::Ascending)
+            .map(|item| item.unwrap().0.1)
+            .collect();
+        assert_eq!(scheduled, vec![1]);
+    }
+
+    #[test]
+    fn only_the_owner_instantiates_contracts() {
+        let mut deps = setup_with_downtime(true);
+        let instantiate_swap = ExecuteMsg::InstantiateSwap { code_id: 1, debug: false };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), instantiate_swap.to_owned()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let instantiate_cw20 = ExecuteMsg::InstantiateCw20 { code_id: 1, etf_name: "etf".to_string(), etf_symbol: "etf".to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), instantiate_cw20.to_owned()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // neither is a buy, pausing buys doesn't hold them back
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Pause { buys: true, redemptions: false }).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_swap).unwrap();
+        assert!(matches!(&res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Instantiate { admin: Some(admin), .. }) if admin == "owner"));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), instantiate_cw20).unwrap();
+        assert!(matches!(&res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Instantiate { admin: Some(admin), .. }) if admin == "owner"));
+    }
+
+    #[test]
+    fn claims_and_harvests_are_paused_with_redemptions() {
+        let mut deps = setup_with_downtime(true);
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Pause { buys: false, redemptions: true }).unwrap();
+        let msgs = vec![
+            ExecuteMsg::ClaimBatch { etf_name: "etf".to_string() },
+            ExecuteMsg::HarvestDonation { holder: "alice".to_string(), etf_name: "etf".to_string() },
+            ExecuteMsg::ClaimWindDown { etf_name: "etf".to_string() },
+        ];
+        for msg in msgs.into_iter() {
+            let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+            assert!(matches!(err, ContractError::Paused { val } if val == "redemptions"));
+        }
+
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Unpause { buys: false, redemptions: true }).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), ExecuteMsg::Pause { buys: true, redemptions: false }).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), ExecuteMsg::ClaimBatch { etf_name: "etf".to_string() })
+            .unwrap_err();
+        assert!(matches!(err, ContractError::Paused { val } if val == "buys"));
+    }
+
+    #[test]
+    fn callback_only_accepts_the_contract_itself() {
+        let mut deps = setup_with_downtime(true);
+        let operands = vec![SubMsg::new(BankMsg::Send { to_address: "alice".to_string(), amount: coins(1_000, "uosmo") })];
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), ExecuteMsg::Callback { operands }).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn exact_output_follows_batch_and_index_settings() {
+        let mut deps = setup_with_downtime(true);
+        MINT_CONTRACTS.save(deps.as_mut().storage, "etf", &"cw20".to_string()).unwrap();
+        let route = Route { pool_id: 1, token_out_denom: "uatom".to_string() };
+        let swap = ExecuteMsg::SwapTokensExactAmountOut {
+            max_initial_balance: coin(1_000, "uosmo"),
+            etf_swap_routes: EtfSwapRoutes { name: "etf".to_string(), routes: vec![route.clone()], ratios: vec![Uint128::new(100)] },
+            shares: Uint128::new(10),
+            max_slippage: Decimal::percent(1),
+        };
+
+        let batch = BatchConfig { deposit_denom: "uosmo".to_string(), routes: vec![route.clone()], ratios: vec![Uint128::new(100)] };
+        BATCH_CONFIGS.save(deps.as_mut().storage, "etf", &batch).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &coins(1_000, "uosmo")), swap.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CustomError { .. }));
+
+        // indexed etfs are bought along their index weights, not routes of the caller
+        BATCH_CONFIGS.remove(deps.as_mut().storage, "etf");
+        let methodology = IndexMethodology {
+            weighting: Weighting::Equal,
+            deposit_denom: "uosmo".to_string(),
+            components: vec![IndexComponent { route, cap: None }],
+            rebalance_period: 86_400,
+        };
+        INDEX_METHODOLOGIES.save(deps.as_mut().storage, "etf", &methodology).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &coins(1_000, "uosmo")), swap).unwrap_err();
+        assert!(matches!(err, ContractError::IndexedEtf { .. }));
+    }
+
+    #[test]
+    fn exact_output_mints_the_bought_shares_and_refunds_the_rest() {
+        let mut deps = setup_with_downtime(true);
+        let env = mock_env();
+        MINT_CONTRACTS.save(deps.as_mut().storage, "etf", &"cw20".to_string()).unwrap();
+        SWAP_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("swap")).unwrap();
+        let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        EXACT_OUT_CACHE.save(deps.as_mut().storage, &ExactOutCache {
+            sender: "alice".to_string(),
+            etf_name: "etf".to_string(),
+            max_deposit: coin(1_000, "uosmo"),
+            spent: Uint128::zero(),
+            initial_swap: coin(0, atom),
+            swaps_left: 2,
+            shares: Uint128::new(150),
+        }).unwrap();
+        let swapped = |tokens_in: Coin, tokens_out: Coin| Reply {
+            id: EXECUTE_SWAP_EXACT_OUT_REPLY_ID,
+            result: SubMsgResult::Ok(cosmwasm_std::SubMsgResponse {
+                events: vec![TokenSwapped {
+                    module: "gamm".to_string(),
+                    sender: "swap".to_string(),
+                    pool_id: "1".to_string(),
+                    tokens_in,
+                    tokens_out,
+                }.into()],
+                data: None,
+            }),
+        };
+
+        reply(deps.as_mut(), env.to_owned(), swapped(coin(80, "uosmo"), coin(75, atom))).unwrap();
+        let res = reply(deps.as_mut(), env.to_owned(), swapped(coin(79, "uosmo"), coin(75, atom))).unwrap();
+
+        // the shares asked for are minted whatever the swaps cost, the unspent deposit goes back
+        assert_eq!(res.messages.iter().map(|m| m.msg.to_owned()).collect::<Vec<_>>(), vec![
+            create_msg_execute_mint_tokens(env.contract.address.to_string(), Uint128::new(150), "cw20".to_string()).into(),
+            create_msg_send_tokens_back(&Addr::unchecked("swap"), coins(841, "uosmo"), "alice".to_string()).unwrap(),
+        ]);
+        assert_eq!(SHARES.load(deps.as_ref().storage, ("etf", "alice")).unwrap(), Uint128::new(150));
+        assert_eq!(BALANCE.load(deps.as_ref().storage, ("alice", "etf")).unwrap(), coin(159, "uosmo"));
+        assert_eq!(LEDGER.load(deps.as_ref().storage, ("alice", "etf")).unwrap(), coins(150, atom));
+        assert!(!EXACT_OUT_CACHE.exists(deps.as_ref().storage));
+    }
+
+    // alice deposited 100uosmo, bob 10usdc worth 300uosmo
+    fn save_positions(deps: DepsMut, env: &Env) {
+        LEDGER.save(deps.storage, ("alice", "etf"), &coins(100, "uion")).unwrap();
+        save_deposit(deps.storage, env.block.height, "alice", "etf", &coin(100, "uosmo"), Uint128::new(100)).unwrap();
+        LEDGER.save(deps.storage, ("bob", "etf"), &coins(300, "uion")).unwrap();
+        save_deposit(deps.storage, env.block.height, "bob", "etf", &coin(10, "usdc"), Uint128::new(300)).unwrap();
+    }
+
+    #[test]
+    fn nav_per_share_counts_positions_of_every_deposit_denom() {
+        let mut deps = setup_with_prices(vec![
+            ("uosmo", Decimal::one()),
+            ("usdc", Decimal::from_ratio(25u128, 1u128)),
+            ("uion", Decimal::one()),
+            ("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2", Decimal::from_ratio(10u128, 1u128)),
+        ]);
+        let env = mock_env();
+        MINT_CONTRACTS.save(deps.as_mut().storage, "etf", &"cw20".to_string()).unwrap();
+        SWAP_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("swap")).unwrap();
+        ETF_POOLS.save(deps.as_mut().storage, "uion", &3).unwrap();
+        save_positions(deps.as_mut(), &env);
+        LEDGER.save(deps.as_mut().storage, ("alice", "etf"), &coins(200, "uion")).unwrap();
+
+        // alice's 200 uosmo and bob's 300 uosmo worth of uion back 400 shares
+        let nav = query_nav_per_share(deps.as_ref(), env.to_owned(), "etf".to_string(), "uosmo".to_string()).unwrap();
+        assert_eq!(nav.nav_per_share, Decimal::from_ratio(5u128, 4u128));
+
+        // bob deposited usdc, his order is checked against the nav of alice's position as well
+        let place = ExecuteMsg::PlaceNavOrder {
+            etf_name: "etf".to_string(),
+            kind: NavOrderKind::Redeem {},
+            nav_per_share: Decimal::from_ratio(5u128, 100u128),
+            expires_at: env.block.time.plus_seconds(60),
+        };
+        execute(deps.as_mut(), env.to_owned(), mock_info("bob", &[]), place).unwrap();
+        let order = NAV_ORDERS.load(deps.as_ref().storage, ("bob", 1)).unwrap();
+        assert_eq!(order.denom, "usdc");
+        let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), ExecuteMsg::ExecuteNavOrder { owner: "bob".to_string(), order_id: 1 })
+            .unwrap();
+        assert!(res.attributes.contains(&attr("nav_per_share", "0.05")));
+    }
+
+    #[test]
+    fn outflow_window_slides_over_the_window_boundary() {
+        let mut deps = setup_with_downtime(true);
+        let mut env = mock_env();
+        save_positions(deps.as_mut(), &env);
+        OUTFLOW_LIMITS.save(deps.as_mut().storage, "etf", &OutflowLimit { max_percent: Decimal::percent(50), window: 100 }).unwrap();
+        let start = env.block.time;
+
+        assert!(try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::new(200)).unwrap());
+        assert!(!try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::one()).unwrap());
+
+        // right after the boundary the whole previous window is still within the last 100 seconds
+        env.block.time = start.plus_seconds(100);
+        assert!(!try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::one()).unwrap());
+
+        // halfway through, half of it is
+        env.block.time = start.plus_seconds(150);
+        assert!(!try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::new(101)).unwrap());
+        assert!(try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::new(100)).unwrap());
+        let capacity = query_outflow_capacity(deps.as_ref(), env.to_owned(), "etf".to_string()).unwrap();
+        assert_eq!(capacity.remaining, Some(Uint128::zero()));
+
+        // the 100 shares redeemed halfway through carry over into the next window
+        env.block.time = start.plus_seconds(250);
+        let capacity = query_outflow_capacity(deps.as_ref(), env.to_owned(), "etf".to_string()).unwrap();
+        assert_eq!(capacity.remaining, Some(Uint128::new(150)));
+
+        // two windows without redemptions, above the capacity still fits into an empty one
+        env.block.time = start.plus_seconds(400);
+        assert!(try_consume_outflow(deps.as_mut().storage, &env, "etf", Uint128::new(300)).unwrap());
+    }
+
+    #[test]
+    fn native_shares_vote_with_who_holds_them() {
+        let mut deps = setup_with_downtime(true);
+        let mut env = mock_env();
+        let denom = "factory/manager/etf";
+        NATIVE_DENOMS.save(deps.as_mut().storage, "etf", &denom.to_string()).unwrap();
+        save_positions(deps.as_mut(), &env);
+        env.block.height += 1;
+        let config = GovConfig { quorum: Decimal::percent(50), threshold: Decimal::percent(50), voting_period: 20 };
+        execute(deps.as_mut(), env.to_owned(), mock_info("owner", &[]), ExecuteMsg::SetGovConfig { config: Some(config) }).unwrap();
+
+        // alice sent her shares to carol before the proposal was created
+        deps.querier.base.update_balance("carol", coins(100, denom));
+        deps.querier.base.update_balance("bob", coins(300, denom));
+        let propose = ExecuteMsg::Propose {
+            etf_name: "etf".to_string(),
+            action: EtfAdminMsg::SetOutflowLimit { limit: None },
+        };
+        let err = execute(deps.as_mut(), env.to_owned(), mock_info("alice", &[]), propose.to_owned()).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower { .. }));
+        execute(deps.as_mut(), env.to_owned(), mock_info("carol", &[]), propose).unwrap();
+        let power = query_voting_power(deps.as_ref(), "etf".to_string(), "carol".to_string(), Some(env.block.height)).unwrap();
+        assert_eq!(power, VotingPowerResponse { shares: Uint128::new(100), total_shares: Uint128::new(400) });
+
+        let vote = |option: VoteOption| ExecuteMsg::Vote { proposal_id: 1, vote: option };
+        let err = execute(deps.as_mut(), env.to_owned(), mock_info("alice", &[]), vote(VoteOption::Yes)).unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower { .. }));
+        let res = execute(deps.as_mut(), env.to_owned(), mock_info("carol", &coins(100, denom)), vote(VoteOption::Yes)).unwrap();
+        assert!(res.attributes.contains(&attr("weight", "100")));
+        execute(deps.as_mut(), env.to_owned(), mock_info("bob", &coins(300, denom)), vote(VoteOption::No)).unwrap();
+
+        // the escrowed shares go back with the outcome
+        env.block.time = env.block.time.plus_seconds(20);
+        let res = execute(deps.as_mut(), env, mock_info("alice", &[]), ExecuteMsg::ExecuteProposal { proposal_id: 1 }).unwrap();
+        assert!(res.attributes.contains(&attr("passed", "false")));
+        assert_eq!(res.messages, vec![
+            SubMsg::new(BankMsg::Send { to_address: "bob".to_string(), amount: coins(300, denom) }),
+            SubMsg::new(BankMsg::Send { to_address: "carol".to_string(), amount: coins(100, denom) }),
+        ]);
+    }
+
+    #[test]
+    fn wind_down_is_split_by_shares_not_deposited_amounts() {
+        let mut deps = setup_with_downtime(true);
+        let env = mock_env();
+        MINT_CONTRACTS.save(deps.as_mut().storage, "etf", &"cw20".to_string()).unwrap();
+        SWAP_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("swap")).unwrap();
+        save_positions(deps.as_mut(), &env);
+        assert_eq!(TOTAL_SHARES.load(deps.as_ref().storage, "etf").unwrap(), Uint128::new(400));
+        assert_eq!(ETF_DEPOSITS.load(deps.as_ref().storage, ("etf", "usdc")).unwrap(), Uint128::new(10));
+
+        execute(deps.as_mut(), env.to_owned(), mock_info("owner", &[]), ExecuteMsg::WindDown { etf_name: "etf".to_string() }).unwrap();
+        let claim = query_wind_down_claim(deps.as_ref(), "etf".to_string(), "alice".to_string()).unwrap();
+        assert_eq!(claim.claimable, coins(100, "uion"));
+
+        let res = execute(deps.as_mut(), env, mock_info("bob", &[]), ExecuteMsg::ClaimWindDown { etf_name: "etf".to_string() }).unwrap();
+        assert!(res.attributes.contains(&attr("shares_burnt", "300")));
+        assert_eq!(WIND_DOWN_CLAIMS.load(deps.as_ref().storage, ("etf", "bob")).unwrap(), coins(300, "uion"));
+        assert_eq!(TOTAL_SHARES.load(deps.as_ref().storage, "etf").unwrap(), Uint128::new(100));
+    }
+
+    #[test]
+    fn native_shares_redeem_their_part_of_every_position() {
+        let mut deps = setup_with_downtime(true);
+        let env = mock_env();
+        NATIVE_DENOMS.save(deps.as_mut().storage, "etf", &"factory/manager/etf".to_string()).unwrap();
+        SWAP_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("swap")).unwrap();
+        save_positions(deps.as_mut(), &env);
+
+        // carol got her shares by transfer and redeems half of all shares
+        let redeem = ExecuteMsg::RedeemTokens { etf_name: "etf".to_string() };
+        let err = execute(deps.as_mut(), env.to_owned(), mock_info("carol", &[]), redeem.to_owned()).unwrap_err();
+        assert!(matches!(err, ContractError::SharesNotReturned { .. }));
+        let res = execute(deps.as_mut(), env.to_owned(), mock_info("carol", &coins(200, "factory/manager/etf")), redeem).unwrap();
+        assert!(res.attributes.contains(&attr("shares_burnt", "200")));
+        let send_back = create_msg_send_tokens_back(&Addr::unchecked("swap"), coins(200, "uion"), "carol".to_string()).unwrap();
+        assert_eq!(res.messages[1].msg, send_back);
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(LEDGER.load(storage, ("alice", "etf")).unwrap(), coins(50, "uion"));
+        assert_eq!(LEDGER.load(storage, ("bob", "etf")).unwrap(), coins(150, "uion"));
+        assert_eq!(SHARES.load(storage, ("etf", "bob")).unwrap(), Uint128::new(150));
+        assert_eq!(BALANCE.load(storage, ("bob", "etf")).unwrap(), coin(5, "usdc"));
+        assert_eq!(ETF_DEPOSITS.load(storage, ("etf", "uosmo")).unwrap(), Uint128::new(50));
+        assert_eq!(TOTAL_SHARES.load(storage, "etf").unwrap(), Uint128::new(200));
+    }
+
+    #[test]
+    fn checked_mul_dec_rounds_down_and_errors_on_overflow() {
+        assert_eq!(checked_mul_dec(Uint128::new(1_001), Decimal::percent(101)).unwrap(), Uint128::new(1_011));
+        assert!(checked_mul_dec(Uint128::MAX, Decimal::percent(101)).is_err());
+    }
+}
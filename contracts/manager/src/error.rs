@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128, Decimal, Timestamp};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -36,8 +36,119 @@ pub enum ContractError {
     #[error("Provided initial deposit does not match user's funds")]
     DepositNotFound {},
 
+    #[error("Deposit doesn't cover the maximum estimated input of {required:?}")]
+    InsufficientDeposit {required: Uint128},
+
+    #[error("Etf {val:?} already exists")]
+    EtfAlreadyExists {val: String},
+
+    #[error("Redeeming requires sending back the shares to redeem: {val:?}")]
+    SharesNotReturned {val: String},
+
+    #[error("Batching is not enabled for etf {val:?}")]
+    BatchingDisabled {val: String},
+
+    #[error("Batch of etf {val:?} has no queued requests")]
+    EmptyBatch {val: String},
+
+    #[error("Batch of etf {val:?} has queued requests")]
+    BatchNotEmpty {val: String},
+
+    #[error("Dca plan {val:?} not found")]
+    DcaPlanNotFound {val: u64},
+
+    #[error("Nav order {val:?} not found")]
+    NavOrderNotFound {val: u64},
+
+    #[error("Nav order {val:?} has expired")]
+    NavOrderExpired {val: u64},
+
+    #[error("Nav per share of {nav:?} doesn't meet the order's {limit:?}")]
+    NavConditionNotMet {nav: Decimal, limit: Decimal},
+
+    #[error("Contract is paused for {val:?}")]
+    Paused {val: String},
+
+    #[error("Swaps are disabled while the chain recovers from downtime")]
+    DowntimeRecovery {},
+
+    #[error("Invalid downtime duration: {val:?}")]
+    InvalidDowntime {val: i32},
+
+    #[error("Deposit worth {val:?} is below the minimum of {min:?}")]
+    DepositTooSmall {val: Uint128, min: Uint128},
+
+    #[error("Deposit worth {val:?} is above the maximum of {max:?}")]
+    DepositTooLarge {val: Uint128, max: Uint128},
+
+    #[error("Deposit would bring user's deposits to {val:?}, above the per-user cap of {cap:?}")]
+    UserCapExceeded {val: Uint128, cap: Uint128},
+
+    #[error("Deposit would bring etf's deposits to {val:?}, above the total cap of {cap:?}")]
+    TotalCapExceeded {val: Uint128, cap: Uint128},
+
+    #[error("No price pool to value {val:?} in the quote denom")]
+    PricePoolNotFound {val: String},
+
+    #[error("Cannot migrate from {previous_contract:?} {previous_version:?} to {target_version:?}")]
+    CannotMigrate {previous_contract: String, previous_version: String, target_version: String},
+
+    #[error("Semver parsing error: {val:?}")]
+    SemVer {val: String},
+
+    #[error("Holder governance is disabled")]
+    GovernanceDisabled {},
+
+    #[error("Proposal {val:?} not found")]
+    ProposalNotFound {val: u64},
+
+    #[error("Voting on proposal {val:?} is closed")]
+    VotingClosed {val: u64},
+
+    #[error("Voting on proposal {val:?} is still open")]
+    VotingOpen {val: u64},
+
+    #[error("{val:?} already voted on this proposal")]
+    AlreadyVoted {val: String},
+
+    #[error("{val:?} held no shares of the etf at the proposal's snapshot")]
+    NoVotingPower {val: String},
+
+    #[error("Etf {val:?} can't be rebalanced before {next}")]
+    RebalanceNotDue {val: String, next: Timestamp},
+
+    #[error("Routes and ratios of etf {val:?} are set by its index methodology")]
+    IndexedEtf {val: String},
+
+    #[error("Sender {val:?} isn't the ibc-hooks intermediate address of the ibc sender")]
+    IbcSenderMismatch {val: String},
+
+    #[error("No ibc refund pending for sequence {sequence} on {channel}")]
+    IbcRefundNotFound {channel: String, sequence: u64},
+
+    #[error("Outflow limit of etf {val:?} is reached for the current window")]
+    OutflowLimitReached {val: String},
+
+    #[error("Position in etf {val:?} has a queued redemption")]
+    RedemptionQueued {val: String},
+
+    #[error("No redemptions of etf {val:?} are queued")]
+    EmptyRedemptionQueue {val: String},
+
+    #[error("Etf {val:?} is wound down")]
+    WoundDown {val: String},
+
+    #[error("Etf {val:?} isn't wound down")]
+    NotWoundDown {val: String},
+
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer {val: err.to_string()}
+    }
+}
@@ -1,47 +1,543 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::{
-    to_binary, Addr, CosmosMsg, StdResult, WasmMsg,
+    coin, to_binary, Addr, Coin, CosmosMsg, Decimal, QuerierWrapper, StdError, StdResult, Timestamp, Uint128, WasmMsg,
 };
 
-use crate::msg::{ExecuteMsg};
+use crate::msg::{
+    BatchClaim, BatchConfig, BatchStateResponse, DcaPlansResponse, DonationBeneficiariesResponse, DonationResponse,
+    DonationSettings, DonationTotalsResponse, DowntimeBreaker, EtfAdminMsg, EtfLimits,
+    EtfLimitsResponse, EtfSwapRoutes, ExecuteMsg, GetBalanceResponse, GetInitialSwapResponse, GetNavResponse,
+    GetPauseStateResponse, GetShareTokenResponse, GetTokensResponse, GovConfig, GovConfigResponse, IbcRefundResponse,
+    IbcSender, IndexMethodology, IndexResponse, NavOrderKind, NavOrdersResponse, NavPerShareResponse,
+    OutflowCapacityResponse, OutflowLimit, PairRouteResponse, ProposalResponse, ProposalsResponse, QueryMsg, Route,
+    VoteOption, VoteResponse, VotingPowerResponse, WindDownClaimResponse, WindDownResponse,
+};
 
-/// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
-/// for working with this.
+/// ManagerContract is a wrapper around Addr that provides typed queries and messages
+/// for working with the manager, from other contracts or from tests.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ManagerContract(pub Addr);
 
+/// Tokens held for a position and the deposit its shares were minted for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    pub tokens: Vec<Coin>,
+    pub deposit: Coin,
+}
+
 impl ManagerContract {
     pub fn addr(&self) -> Addr {
         self.0.clone()
     }
 
     pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    pub fn call_with_funds<T: Into<ExecuteMsg>>(&self, msg: T, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
         let msg = to_binary(&msg.into())?;
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
             msg,
-            funds: vec![],
+            funds,
         }
         .into())
     }
 
-    // Get Count
-    // pub fn get_contracts<Q, T, CQ>(&self, querier: &Q) -> StdResult<GetContractsResponse>
-    // where
-    //     Q: Querier,
-    //     T: Into<String>,
-    //     CQ: CustomQuery,
-    // {
-    //     let msg = QueryMsg::GetContracts {};
-    //     let query = WasmQuery::Smart {
-    //         contract_addr: self.addr().into(),
-    //         msg: to_binary(&msg)?,
-    //     }
-    //     .into();
-    //     let res: GetContractsResponse = QuerierWrapper::<CQ>::new(querier).query(&query)?;
-    //     Ok(res)
-    // }
+    pub fn query<T: DeserializeOwned>(&self, querier: &QuerierWrapper, msg: &QueryMsg) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    // ----------------------------------- MESSAGES
+    // `Callback` and `DcaPurchase` are only ever dispatched by the manager itself, `call` covers them
+
+    pub fn instantiate_swap(&self, code_id: u64, debug: bool) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::InstantiateSwap { code_id, debug })
+    }
+
+    pub fn instantiate_cw20(
+        &self,
+        code_id: u64,
+        etf_name: impl Into<String>,
+        etf_symbol: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::InstantiateCw20 { code_id, etf_name: etf_name.into(), etf_symbol: etf_symbol.into() })
+    }
+
+    /// `fee` has to cover tokenfactory's denom creation fee
+    pub fn create_native_denom(
+        &self,
+        etf_name: impl Into<String>,
+        etf_symbol: impl Into<String>,
+        fee: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_with_funds(
+            ExecuteMsg::CreateNativeDenom { etf_name: etf_name.into(), etf_symbol: etf_symbol.into() },
+            fee,
+        )
+    }
+
+    pub fn swap_tokens(
+        &self,
+        initial_balance: Coin,
+        etf_swap_routes: EtfSwapRoutes,
+        on_behalf_of: Option<String>,
+        ibc_sender: Option<IbcSender>,
+    ) -> StdResult<CosmosMsg> {
+        let funds = vec![initial_balance.clone()];
+        self.call_with_funds(ExecuteMsg::SwapTokens { initial_balance, etf_swap_routes, on_behalf_of, ibc_sender }, funds)
+    }
+
+    pub fn swap_tokens_exact_amount_out(
+        &self,
+        max_initial_balance: Coin,
+        etf_swap_routes: EtfSwapRoutes,
+        shares: Uint128,
+        max_slippage: Decimal,
+    ) -> StdResult<CosmosMsg> {
+        let funds = vec![max_initial_balance.clone()];
+        self.call_with_funds(
+            ExecuteMsg::SwapTokensExactAmountOut { max_initial_balance, etf_swap_routes, shares, max_slippage },
+            funds,
+        )
+    }
+
+    /// `shares` are the position's native shares, None for etfs with cw20 shares
+    pub fn redeem_tokens(&self, etf_name: impl Into<String>, shares: Option<Coin>) -> StdResult<CosmosMsg> {
+        self.call_with_funds(ExecuteMsg::RedeemTokens { etf_name: etf_name.into() }, shares.into_iter().collect())
+    }
+
+    pub fn pause(&self, buys: bool, redemptions: bool) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Pause { buys, redemptions })
+    }
+
+    pub fn unpause(&self, buys: bool, redemptions: bool) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Unpause { buys, redemptions })
+    }
+
+    pub fn set_downtime_breaker(&self, breaker: Option<DowntimeBreaker>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetDowntimeBreaker { breaker })
+    }
+
+    pub fn set_etf_limits(&self, etf_name: impl Into<String>, limits: Option<EtfLimits>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetEtfLimits { etf_name: etf_name.into(), limits })
+    }
+
+    pub fn set_batch_mode(&self, etf_name: impl Into<String>, batch: Option<BatchConfig>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetBatchMode { etf_name: etf_name.into(), batch })
+    }
+
+    pub fn queue_deposit(&self, etf_name: impl Into<String>, deposit: Coin) -> StdResult<CosmosMsg> {
+        self.call_with_funds(ExecuteMsg::QueueDeposit { etf_name: etf_name.into() }, vec![deposit])
+    }
+
+    /// `shares` are the position's native shares, None for etfs with cw20 shares
+    pub fn queue_redemption(&self, etf_name: impl Into<String>, shares: Option<Coin>) -> StdResult<CosmosMsg> {
+        self.call_with_funds(ExecuteMsg::QueueRedemption { etf_name: etf_name.into() }, shares.into_iter().collect())
+    }
+
+    pub fn settle_batch(&self, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SettleBatch { etf_name: etf_name.into() })
+    }
+
+    pub fn claim_batch(&self, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ClaimBatch { etf_name: etf_name.into() })
+    }
+
+    /// escrows `amount_per_period` for all periods
+    pub fn create_dca_plan(
+        &self,
+        etf_name: impl Into<String>,
+        amount_per_period: Coin,
+        period: u64,
+        num_periods: u32,
+        routes: Vec<Route>,
+        ratios: Vec<Uint128>,
+    ) -> StdResult<CosmosMsg> {
+        let escrow = amount_per_period.amount.checked_mul(num_periods.into()).map_err(StdError::from)?;
+        let funds = vec![coin(escrow.u128(), amount_per_period.denom.to_owned())];
+        self.call_with_funds(
+            ExecuteMsg::CreateDcaPlan { etf_name: etf_name.into(), amount_per_period, period, num_periods, routes, ratios },
+            funds,
+        )
+    }
+
+    pub fn execute_due(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteDue {})
+    }
+
+    pub fn pause_dca_plan(&self, plan_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::PauseDcaPlan { plan_id })
+    }
+
+    pub fn resume_dca_plan(&self, plan_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ResumeDcaPlan { plan_id })
+    }
+
+    pub fn cancel_dca_plan(&self, plan_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CancelDcaPlan { plan_id })
+    }
+
+    pub fn set_nav_twap_window(&self, seconds: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetNavTwapWindow { seconds })
+    }
+
+    /// buys escrow their deposit, redemptions `shares` of etfs with native shares
+    pub fn place_nav_order(
+        &self,
+        etf_name: impl Into<String>,
+        kind: NavOrderKind,
+        nav_per_share: Decimal,
+        expires_at: Timestamp,
+        shares: Option<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let funds = match &kind {
+            NavOrderKind::Buy { deposit, .. } => vec![deposit.to_owned()],
+            NavOrderKind::Redeem {} => shares.into_iter().collect(),
+        };
+        self.call_with_funds(
+            ExecuteMsg::PlaceNavOrder { etf_name: etf_name.into(), kind, nav_per_share, expires_at },
+            funds,
+        )
+    }
+
+    pub fn execute_nav_order(&self, owner: impl Into<String>, order_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteNavOrder { owner: owner.into(), order_id })
+    }
+
+    pub fn cancel_nav_order(&self, order_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CancelNavOrder { order_id })
+    }
+
+    pub fn set_donation_beneficiaries(
+        &self,
+        etf_name: impl Into<String>,
+        beneficiaries: Vec<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetDonationBeneficiaries { etf_name: etf_name.into(), beneficiaries })
+    }
+
+    pub fn set_donation(&self, etf_name: impl Into<String>, donation: Option<DonationSettings>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetDonation { etf_name: etf_name.into(), donation })
+    }
+
+    pub fn harvest_donation(&self, holder: impl Into<String>, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::HarvestDonation { holder: holder.into(), etf_name: etf_name.into() })
+    }
+
+    pub fn set_gov_config(&self, config: Option<GovConfig>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetGovConfig { config })
+    }
+
+    pub fn propose(&self, etf_name: impl Into<String>, action: EtfAdminMsg) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Propose { etf_name: etf_name.into(), action })
+    }
+
+    pub fn vote(&self, proposal_id: u64, vote: VoteOption) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Vote { proposal_id, vote })
+    }
+
+    pub fn execute_proposal(&self, proposal_id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteProposal { proposal_id })
+    }
+
+    pub fn set_index_methodology(
+        &self,
+        etf_name: impl Into<String>,
+        methodology: Option<IndexMethodology>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetIndexMethodology { etf_name: etf_name.into(), methodology })
+    }
+
+    pub fn rebalance(&self, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::Rebalance { etf_name: etf_name.into() })
+    }
+
+    pub fn set_pair_route(&self, pool_id: u64, pair: Option<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetPairRoute { pool_id, pair })
+    }
+
+    pub fn set_outflow_limit(&self, etf_name: impl Into<String>, limit: Option<OutflowLimit>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SetOutflowLimit { etf_name: etf_name.into(), limit })
+    }
+
+    pub fn process_queued_redemption(&self, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ProcessQueuedRedemption { etf_name: etf_name.into() })
+    }
+
+    pub fn wind_down(&self, etf_name: impl Into<String>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::WindDown { etf_name: etf_name.into() })
+    }
+
+    /// `shares` are the native shares claimed with, None for etfs with cw20 shares
+    pub fn claim_wind_down(&self, etf_name: impl Into<String>, shares: Option<Coin>) -> StdResult<CosmosMsg> {
+        self.call_with_funds(ExecuteMsg::ClaimWindDown { etf_name: etf_name.into() }, shares.into_iter().collect())
+    }
+
+    // ----------------------------------- QUERIES
+
+    pub fn tokens(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<GetTokensResponse> {
+        self.query(querier, &QueryMsg::GetTokens { sender: sender.into(), etf_type: etf_name.into() })
+    }
+
+    pub fn initial_swap(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<GetInitialSwapResponse> {
+        self.query(querier, &QueryMsg::GetInitialSwap { sender: sender.into(), etf_type: etf_name.into() })
+    }
+
+    /// empty for redeemed positions
+    pub fn balance(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<GetBalanceResponse> {
+        self.query(querier, &QueryMsg::GetBalance { sender: sender.into(), etf_type: etf_name.into() })
+    }
+
+    /// fails for positions that don't exist
+    pub fn position(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<Position> {
+        let (sender, etf_name) = (sender.into(), etf_name.into());
+        let tokens = self.tokens(querier, sender.to_owned(), etf_name.to_owned())?.tokens_per_etf;
+        let deposit = self.balance(querier, sender, etf_name)?.balance;
+        Ok(Position { tokens, deposit })
+    }
+
+    pub fn pause_state(&self, querier: &QuerierWrapper) -> StdResult<GetPauseStateResponse> {
+        self.query(querier, &QueryMsg::GetPauseState {})
+    }
+
+    pub fn etf_limits(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<EtfLimitsResponse> {
+        self.query(querier, &QueryMsg::EtfLimits { etf_name: etf_name.into() })
+    }
+
+    pub fn share_token(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<GetShareTokenResponse> {
+        self.query(querier, &QueryMsg::GetShareToken { etf_name: etf_name.into() })
+    }
+
+    /// position's value in its deposit denom, at spot prices
+    pub fn nav(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<GetNavResponse> {
+        self.query(querier, &QueryMsg::GetNav { sender: sender.into(), etf_type: etf_name.into() })
+    }
+
+    pub fn nav_per_share(
+        &self,
+        querier: &QuerierWrapper,
+        etf_name: impl Into<String>,
+        denom: impl Into<String>,
+    ) -> StdResult<NavPerShareResponse> {
+        self.query(querier, &QueryMsg::NavPerShare { etf_name: etf_name.into(), denom: denom.into() })
+    }
+
+    pub fn batch_state(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<BatchStateResponse> {
+        self.query(querier, &QueryMsg::BatchState { etf_name: etf_name.into() })
+    }
+
+    pub fn batch_claim(
+        &self,
+        querier: &QuerierWrapper,
+        sender: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<BatchClaim> {
+        self.query(querier, &QueryMsg::BatchClaim { sender: sender.into(), etf_name: etf_name.into() })
+    }
+
+    pub fn dca_plans(&self, querier: &QuerierWrapper, owner: impl Into<String>) -> StdResult<DcaPlansResponse> {
+        self.query(querier, &QueryMsg::DcaPlans { owner: owner.into() })
+    }
+
+    pub fn nav_orders(&self, querier: &QuerierWrapper, owner: impl Into<String>) -> StdResult<NavOrdersResponse> {
+        self.query(querier, &QueryMsg::NavOrders { owner: owner.into() })
+    }
+
+    pub fn donation(
+        &self,
+        querier: &QuerierWrapper,
+        holder: impl Into<String>,
+        etf_name: impl Into<String>,
+    ) -> StdResult<DonationResponse> {
+        self.query(querier, &QueryMsg::Donation { holder: holder.into(), etf_name: etf_name.into() })
+    }
+
+    pub fn donation_beneficiaries(
+        &self,
+        querier: &QuerierWrapper,
+        etf_name: impl Into<String>,
+    ) -> StdResult<DonationBeneficiariesResponse> {
+        self.query(querier, &QueryMsg::DonationBeneficiaries { etf_name: etf_name.into() })
+    }
+
+    pub fn donation_totals(
+        &self,
+        querier: &QuerierWrapper,
+        beneficiary: impl Into<String>,
+    ) -> StdResult<DonationTotalsResponse> {
+        self.query(querier, &QueryMsg::DonationTotals { beneficiary: beneficiary.into() })
+    }
+
+    pub fn gov_config(&self, querier: &QuerierWrapper) -> StdResult<GovConfigResponse> {
+        self.query(querier, &QueryMsg::GovConfig {})
+    }
+
+    pub fn proposal(&self, querier: &QuerierWrapper, proposal_id: u64) -> StdResult<ProposalResponse> {
+        self.query(querier, &QueryMsg::Proposal { proposal_id })
+    }
+
+    pub fn proposals(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<ProposalsResponse> {
+        self.query(querier, &QueryMsg::Proposals { etf_name: etf_name.into() })
+    }
+
+    pub fn vote_of(&self, querier: &QuerierWrapper, proposal_id: u64, voter: impl Into<String>) -> StdResult<VoteResponse> {
+        self.query(querier, &QueryMsg::Vote { proposal_id, voter: voter.into() })
+    }
+
+    /// current shares if no height is given
+    pub fn voting_power(
+        &self,
+        querier: &QuerierWrapper,
+        etf_name: impl Into<String>,
+        holder: impl Into<String>,
+        height: Option<u64>,
+    ) -> StdResult<VotingPowerResponse> {
+        self.query(querier, &QueryMsg::VotingPower { etf_name: etf_name.into(), holder: holder.into(), height })
+    }
+
+    pub fn index(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<IndexResponse> {
+        self.query(querier, &QueryMsg::Index { etf_name: etf_name.into() })
+    }
+
+    pub fn pair_route(&self, querier: &QuerierWrapper, pool_id: u64) -> StdResult<PairRouteResponse> {
+        self.query(querier, &QueryMsg::PairRoute { pool_id })
+    }
+
+    pub fn ibc_refund(
+        &self,
+        querier: &QuerierWrapper,
+        channel: impl Into<String>,
+        sequence: u64,
+    ) -> StdResult<IbcRefundResponse> {
+        self.query(querier, &QueryMsg::IbcRefund { channel: channel.into(), sequence })
+    }
+
+    pub fn outflow_capacity(
+        &self,
+        querier: &QuerierWrapper,
+        etf_name: impl Into<String>,
+    ) -> StdResult<OutflowCapacityResponse> {
+        self.query(querier, &QueryMsg::OutflowCapacity { etf_name: etf_name.into() })
+    }
+
+    pub fn wind_down_state(&self, querier: &QuerierWrapper, etf_name: impl Into<String>) -> StdResult<WindDownResponse> {
+        self.query(querier, &QueryMsg::WindDown { etf_name: etf_name.into() })
+    }
+
+    pub fn wind_down_claim(
+        &self,
+        querier: &QuerierWrapper,
+        etf_name: impl Into<String>,
+        holder: impl Into<String>,
+    ) -> StdResult<WindDownClaimResponse> {
+        self.query(querier, &QueryMsg::WindDownClaim { etf_name: etf_name.into(), holder: holder.into() })
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{coins, from_binary, ContractResult, OwnedDeps, SystemError, SystemResult, WasmQuery};
+
+    use super::*;
+    use crate::contract::{instantiate, query};
+    use crate::msg::InstantiateMsg;
+    use crate::state::{BALANCE, LEDGER};
+
+    // queries of `ManagerContract` are answered by the contract's own query entry point
+    fn with_manager() -> (ManagerContract, OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let mut manager = mock_dependencies();
+        instantiate(manager.as_mut(), mock_env(), mock_info("owner", &[]), InstantiateMsg {}).unwrap();
+        LEDGER.save(manager.as_mut().storage, ("alice", "etf"), &coins(90, "uion")).unwrap();
+        BALANCE.save(manager.as_mut().storage, ("alice", "etf"), &coin(100, "uosmo")).unwrap();
+
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(move |request| match request {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "manager" => {
+                SystemResult::Ok(ContractResult::from(query(manager.as_ref(), mock_env(), from_binary(msg).unwrap())))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+        (ManagerContract(Addr::unchecked("manager")), deps)
+    }
+
+    #[test]
+    fn typed_queries() {
+        let (manager, deps) = with_manager();
+        let querier = deps.as_ref().querier;
+
+        let position = manager.position(&querier, "alice", "etf").unwrap();
+        assert_eq!(position, Position { tokens: coins(90, "uion"), deposit: coin(100, "uosmo") });
+        assert!(manager.position(&querier, "bob", "etf").is_err());
+        assert_eq!(manager.balance(&querier, "bob", "etf").unwrap().balance, Coin::default());
+
+        let pause_state = manager.pause_state(&querier).unwrap();
+        assert!(!pause_state.buys_paused && !pause_state.redemptions_paused);
+        let capacity = manager.outflow_capacity(&querier, "etf").unwrap();
+        assert_eq!(capacity.limit, None);
+        assert!(capacity.queue.is_empty());
+        assert_eq!(manager.wind_down_state(&querier, "etf").unwrap().wind_down, None);
+    }
+
+    #[test]
+    fn messages_carry_their_funds() {
+        let manager = ManagerContract(Addr::unchecked("manager"));
+        let funds_of = |msg: CosmosMsg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, funds }) => {
+                assert_eq!(contract_addr, "manager");
+                (from_binary::<ExecuteMsg>(&msg).unwrap(), funds)
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        let routes = vec![Route { pool_id: 1, token_out_denom: "uion".to_string() }];
+        let ratios = vec![Uint128::new(100)];
+
+        let etf_swap_routes = EtfSwapRoutes { name: "etf".to_string(), routes: routes.to_owned(), ratios: ratios.to_owned() };
+        let (msg, funds) = funds_of(manager.swap_tokens(coin(100, "uosmo"), etf_swap_routes, None, None).unwrap());
+        assert!(matches!(msg, ExecuteMsg::SwapTokens { .. }));
+        assert_eq!(funds, coins(100, "uosmo"));
+
+        let (msg, funds) = funds_of(manager.create_dca_plan("etf", coin(10, "usdc"), 60, 5, routes.to_owned(), ratios.to_owned()).unwrap());
+        assert!(matches!(msg, ExecuteMsg::CreateDcaPlan { num_periods: 5, .. }));
+        assert_eq!(funds, coins(50, "usdc"));
+
+        let buy = NavOrderKind::Buy { deposit: coin(20, "uosmo"), routes, ratios };
+        let (_, funds) = funds_of(manager.place_nav_order("etf", buy, Decimal::one(), Timestamp::from_seconds(1), None).unwrap());
+        assert_eq!(funds, coins(20, "uosmo"));
+
+        let (msg, funds) = funds_of(manager.redeem_tokens("etf", None).unwrap());
+        assert_eq!(msg, ExecuteMsg::RedeemTokens { etf_name: "etf".to_string() });
+        assert!(funds.is_empty());
+        let (_, funds) = funds_of(manager.claim_wind_down("etf", Some(coin(5, "factory/manager/etf"))).unwrap());
+        assert_eq!(funds, coins(5, "factory/manager/etf"));
+    }
+}
@@ -0,0 +1,237 @@
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::{StdError, StdResult};
+use sha2::{Digest, Sha256};
+
+// ibc-hooks' `SenderPrefix`
+const INTERMEDIATE_SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
+
+/// Address ibc-hooks executes wasm memos from, for transfers of `original_sender` received on `channel`.
+/// Same as the sdk's `address.Hash(prefix, "channel/original_sender")`
+pub fn derive_intermediate_sender(channel: &str, original_sender: &str, bech32_prefix: &str) -> StdResult<String> {
+    let prefix_hash = Sha256::digest(INTERMEDIATE_SENDER_PREFIX.as_bytes());
+    let hash = Sha256::new()
+        .chain_update(prefix_hash)
+        .chain_update(format!("{}/{}", channel, original_sender).as_bytes())
+        .finalize();
+    bech32::encode(bech32_prefix, hash.to_base32(), Variant::Bech32)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+pub fn bech32_prefix(address: &str) -> StdResult<String> {
+    let (prefix, _, _) = bech32::decode(address).map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::FromBase32;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coin, coins, from_binary, from_slice, Addr, BankMsg, CosmosMsg, Env, OwnedDeps, Reply, SubMsgResponse,
+        SubMsgResult, Uint128,
+    };
+    use osmosis_std::types::ibc::applications::transfer::v1::{MsgTransfer, MsgTransferResponse};
+    use prost::Message;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::contract::{execute, instantiate, query, reply, sudo};
+    use crate::msg::{
+        EtfSwapRoutes, ExecuteMsg, IbcLifecycleComplete, IbcRefund, IbcRefundResponse, InstantiateMsg,
+        QueryMsg, Route, SudoMsg,
+    };
+    use crate::state::{ETF_CACHE, IBC_REFUNDS, MINT_CONTRACTS, SWAP_CONTRACT};
+    use crate::ContractError;
+
+    const CHANNEL: &str = "channel-0";
+    const REMOTE_SENDER: &str = "cosmos1remotesender";
+
+    // the memo a user on the counterparty chain attaches to their transfer
+    #[derive(Deserialize)]
+    struct HookMemo {
+        wasm: WasmHook,
+    }
+
+    #[derive(Deserialize)]
+    struct WasmHook {
+        contract: String,
+        msg: ExecuteMsg,
+    }
+
+    fn hook_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(bech32::encode("osmo", [7u8; 32].to_base32(), Variant::Bech32).unwrap());
+        env
+    }
+
+    fn setup(env: &Env) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        instantiate(deps.as_mut(), env.to_owned(), mock_info("owner", &[]), InstantiateMsg {}).unwrap();
+        SWAP_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("swap")).unwrap();
+        MINT_CONTRACTS.save(deps.as_mut().storage, "etf", &"mint".to_string()).unwrap();
+        deps
+    }
+
+    fn memo(contract: &str) -> String {
+        format!(
+            r#"{{"wasm":{{"contract":"{}","msg":{{"swap_tokens":{{
+                "initial_balance":{{"denom":"uosmo","amount":"1000"}},
+                "etf_swap_routes":{{"name":"etf","routes":[{{"pool_id":3,"token_out_denom":"uion"}}],"ratios":["100"]}},
+                "on_behalf_of":"alice",
+                "ibc_sender":{{"channel":"{}","sender":"{}"}}
+            }}}}}}}}"#,
+            contract, CHANNEL, REMOTE_SENDER
+        )
+    }
+
+    #[test]
+    fn intermediate_sender_is_a_32_byte_address() {
+        let sender = derive_intermediate_sender(CHANNEL, REMOTE_SENDER, "osmo").unwrap();
+        let (prefix, data, _) = bech32::decode(&sender).unwrap();
+        assert_eq!(prefix, "osmo");
+        assert_eq!(Vec::<u8>::from_base32(&data).unwrap().len(), 32);
+        assert_eq!(sender, derive_intermediate_sender(CHANNEL, REMOTE_SENDER, "osmo").unwrap());
+        assert_ne!(sender, derive_intermediate_sender("channel-1", REMOTE_SENDER, "osmo").unwrap());
+        assert_eq!(bech32_prefix(&sender).unwrap(), "osmo");
+    }
+
+    #[test]
+    fn deposit_through_memo_refunds_excess_over_ibc() {
+        let env = hook_env();
+        let mut deps = setup(&env);
+        let hook: HookMemo = from_slice(memo(env.contract.address.as_str()).as_bytes()).unwrap();
+        assert_eq!(hook.wasm.contract, env.contract.address.as_str());
+
+        // ibc-hooks sends the transferred funds along, from the intermediate sender
+        let intermediate = derive_intermediate_sender(CHANNEL, REMOTE_SENDER, "osmo").unwrap();
+        let res = execute(deps.as_mut(), env.to_owned(), mock_info(&intermediate, &coins(1_200, "uosmo")), hook.wasm.msg)
+            .unwrap();
+        assert_eq!(ETF_CACHE.load(deps.as_ref().storage).unwrap().sender, "alice");
+
+        let refund = &res.messages[1];
+        let transfer = match &refund.msg {
+            CosmosMsg::Stargate { type_url, value } if type_url == "/ibc.applications.transfer.v1.MsgTransfer" => {
+                MsgTransfer::decode(value.as_slice()).unwrap()
+            }
+            msg => panic!("unexpected refund message {:?}", msg),
+        };
+        assert_eq!(transfer.source_channel, CHANNEL);
+        assert_eq!(transfer.receiver, REMOTE_SENDER);
+        assert_eq!(transfer.token, Some(coin(200, "uosmo").into()));
+        assert_eq!(transfer.memo, format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address));
+        assert_eq!(transfer.timeout_timestamp, env.block.time.plus_seconds(600).nanos());
+
+        reply(deps.as_mut(), env.to_owned(), Reply {
+            id: refund.id,
+            result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: Some(MsgTransferResponse { sequence: 7 }.into()) }),
+        })
+        .unwrap();
+        let pending: IbcRefundResponse = from_binary(
+            &query(deps.as_ref(), env.to_owned(), QueryMsg::IbcRefund { channel: CHANNEL.to_string(), sequence: 7 }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pending.refund, Some(IbcRefund {
+            channel: CHANNEL.to_string(),
+            receiver: REMOTE_SENDER.to_string(),
+            amount: coin(200, "uosmo"),
+            recovery_addr: "alice".to_string(),
+        }));
+
+        // the refund couldn't be delivered, it's recovered on this chain
+        let ack = |success: bool| SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: CHANNEL.to_string(),
+            sequence: 7,
+            ack: String::new(),
+            success,
+        });
+        let res = sudo(deps.as_mut(), env.to_owned(), ack(false)).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(200, "uosmo"),
+        }));
+        let err = sudo(deps.as_mut(), env, ack(false)).unwrap_err();
+        assert!(matches!(err, ContractError::IbcRefundNotFound { sequence: 7, .. }));
+    }
+
+    #[test]
+    fn deposit_of_ibc_voucher_fails_for_ibc_to_refund() {
+        let env = hook_env();
+        let mut deps = setup(&env);
+        let voucher = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let hook: HookMemo = from_slice(memo(env.contract.address.as_str()).replace("uosmo", voucher).as_bytes()).unwrap();
+
+        // the failed memo makes ibc-hooks ack with an error, nothing is kept or refunded by the contract itself
+        let intermediate = derive_intermediate_sender(CHANNEL, REMOTE_SENDER, "osmo").unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(&intermediate, &coins(1_000, voucher)), hook.wasm.msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidDepositDenom { val } if val == voucher));
+        assert!(!ETF_CACHE.exists(deps.as_ref().storage));
+    }
+
+    #[test]
+    fn deposit_from_other_sender_is_rejected() {
+        let env = hook_env();
+        let mut deps = setup(&env);
+        let hook: HookMemo = from_slice(memo(env.contract.address.as_str()).as_bytes()).unwrap();
+
+        let other = derive_intermediate_sender("channel-1", REMOTE_SENDER, "osmo").unwrap();
+        let err = execute(deps.as_mut(), env, mock_info(&other, &coins(1_000, "uosmo")), hook.wasm.msg).unwrap_err();
+        assert!(matches!(err, ContractError::IbcSenderMismatch { .. }));
+    }
+
+    #[test]
+    fn refund_ack_and_timeout() {
+        let env = hook_env();
+        let mut deps = setup(&env);
+        let refund = IbcRefund {
+            channel: CHANNEL.to_string(),
+            receiver: REMOTE_SENDER.to_string(),
+            amount: coin(5, "usdc"),
+            recovery_addr: "alice".to_string(),
+        };
+        IBC_REFUNDS.save(deps.as_mut().storage, (CHANNEL, 1), &refund).unwrap();
+        IBC_REFUNDS.save(deps.as_mut().storage, (CHANNEL, 2), &refund).unwrap();
+
+        let res = sudo(deps.as_mut(), env.to_owned(), SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: CHANNEL.to_string(),
+            sequence: 1,
+            ack: "AQ==".to_string(),
+            success: true,
+        }))
+        .unwrap();
+        assert!(res.messages.is_empty());
+        assert!(!IBC_REFUNDS.has(deps.as_ref().storage, (CHANNEL, 1)));
+
+        let res = sudo(deps.as_mut(), env, SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: CHANNEL.to_string(),
+            sequence: 2,
+        }))
+        .unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(5, "usdc"),
+        }));
+        assert!(!IBC_REFUNDS.has(deps.as_ref().storage, (CHANNEL, 2)));
+    }
+
+    #[test]
+    fn plain_deposit_on_behalf_of_refunds_sender() {
+        let env = hook_env();
+        let mut deps = setup(&env);
+        let msg = ExecuteMsg::SwapTokens {
+            initial_balance: coin(1_000, "uosmo"),
+            etf_swap_routes: EtfSwapRoutes {
+                name: "etf".to_string(),
+                routes: vec![Route { pool_id: 3, token_out_denom: "uion".to_string() }],
+                ratios: vec![Uint128::new(100)],
+            },
+            on_behalf_of: Some("alice".to_string()),
+            ibc_sender: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("bob", &coins(1_100, "uosmo")), msg).unwrap();
+        assert_eq!(res.messages[1].msg, CosmosMsg::Bank(BankMsg::Send {
+            to_address: "bob".to_string(),
+            amount: coins(100, "uosmo"),
+        }));
+        assert_eq!(ETF_CACHE.load(deps.as_ref().storage).unwrap().sender, "alice");
+    }
+}
@@ -1,13 +1,16 @@
 #[cfg(test)]
 mod tests {
     use crate::helpers::ManagerContract;
-    use crate::msg::{ExecuteMsg, GetTokensResponse, QueryMsg, EtfSwapRoutes, InstantiateMsg, Route, GetInitialSwapResponse, GetBalanceResponse};
-    use cosmwasm_std::{Coin, Uint128};
+    use crate::msg::{ExecuteMsg, GetTokensResponse, QueryMsg, EtfSwapRoutes, InstantiateMsg, Route, GetInitialSwapResponse, GetBalanceResponse, MigrateMsg, GetPauseStateResponse, DowntimeBreaker, EtfLimits, EtfLimitsResponse, PricePool, GetShareTokenResponse, GetNavResponse, BatchConfig, BatchStateResponse, BatchClaim, DcaPlansResponse, NavOrderKind, NavOrdersResponse, NavPerShareResponse, DonationSettings, DonationResponse, DonationTotalsResponse, DonationBeneficiariesResponse, GovConfig, EtfAdminMsg, VoteOption, ProposalStatus, ProposalResponse, ProposalsResponse, VotingPowerResponse, IndexMethodology, IndexComponent, IndexResponse, Weighting, PairRouteResponse, OutflowLimit, OutflowCapacityResponse, WindDownResponse, WindDownClaimResponse};
+    use cosmwasm_std::{Coin, Uint128, Decimal, Timestamp};
     use cw_multi_test::{App};
-    use cosmrs::proto::cosmos::bank::v1beta1::QueryAllBalancesRequest;
+    use cosmrs::proto::cosmos::bank::v1beta1::{QueryAllBalancesRequest, QueryBalanceRequest, MsgSend, MsgSendResponse};
+    use cosmrs::proto::cosmos::base::v1beta1::Coin as ProtoCoin;
     use osmosis_testing::cosmrs::proto::cosmwasm::wasm::v1::MsgExecuteContractResponse;
-    use osmosis_testing::{Gamm, Module, OsmosisTestApp, SigningAccount, Wasm, ExecuteResponse, Account, Bank, cosmrs};
-    use cw20_base;
+    use osmosis_testing::{Gamm, Module, OsmosisTestApp, SigningAccount, Wasm, ExecuteResponse, Account, Bank, Runner, cosmrs};
+    use etf_interface::share_token;
+    use etf_interface::swap::MigrateMsg as SwapMigrateMsg;
+    use osmosis_std::types::osmosis::downtimedetector::v1beta1::Downtime;
     use std::path::PathBuf;
 
 
@@ -125,7 +128,10 @@ mod tests {
                 name: etf_name.to_owned(),
                 routes: routes,
                 ratios: ratios
-            } }, 
+            },
+            on_behalf_of: None,
+            ibc_sender: None
+        }, 
             &vec![init_balance], &signer)
         .unwrap();
         swap_resp
@@ -163,7 +169,7 @@ mod tests {
                 .collect();
                 
             let res_query_get_initial_swap: GetInitialSwapResponse = wasm
-                .query(&manager_contract_addr, &QueryMsg::GetInitialSwap {  sender: signer.address() })
+                .query(&manager_contract_addr, &QueryMsg::GetInitialSwap {  sender: signer.address(), etf_type: etf_name.to_owned() })
                 .unwrap();
             
             // assert that the initial swap amount has been properly saved
@@ -193,7 +199,7 @@ mod tests {
             let managers_minted_balance: cw20::BalanceResponse = wasm
                 .query(
                     &mint_contract_addr,
-                    &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                    &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
                 )
                 .unwrap();
 
@@ -226,6 +232,9 @@ mod tests {
             let pool_id_2 = setup_pool(app, &signer, "uosmo", "usdc");
             let pool_id_3 = setup_pool(app, &signer, "uosmo", "uion");
             let pool_id_4 = setup_pool(app, &signer, "uosmo", "uiou");
+            // usdc deposits are turned into shares at TWAPs, pools of the test are younger than the default window
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
 
             let etf_name = "WladziooEtf_First".to_string();
             let initial_coin = Coin::new(30, "usdc");
@@ -275,7 +284,7 @@ mod tests {
             let manager_minted_balance: cw20::BalanceResponse = wasm
                 .query(
                     &mint_contract_addr,
-                    &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                    &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
                 )
                 .unwrap();
             let users_depo_balance: GetBalanceResponse = wasm
@@ -452,12 +461,12 @@ mod tests {
             let manager_minted_balance: cw20::BalanceResponse = wasm
                 .query(
                 &mint_contract_addr,
-                &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
             ).unwrap();
             let manager_minted_balance2: cw20::BalanceResponse = wasm
                 .query(
                 &mint_contract_addr2,
-                &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
             ).unwrap();
 
             // assert that the amount of minted tokens is equal to users' balances
@@ -494,12 +503,12 @@ mod tests {
             let manager_minted_balance: cw20::BalanceResponse = wasm
                 .query(
                 &mint_contract_addr,
-                &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
                 ).unwrap();
             let manager_minted_balance2: cw20::BalanceResponse = wasm
                 .query(
                 &mint_contract_addr2,
-                &cw20_base::msg::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
+                &share_token::QueryMsg::Balance { address: manager_contract_addr.to_owned() }
                 ).unwrap();
 
             // now minted balances should be equal to 2 coins out of initial 3
@@ -559,4 +568,1449 @@ mod tests {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_migrate_from_v0_1_0() {
+        let app = OsmosisTestApp::default();
+        let wasm = Wasm::new(&app);
+        let signer = app
+            .init_account(&[
+                Coin::new(100_000_000_000, "uosmo"),
+                Coin::new(100_000_000_000, "uion"),
+                Coin::new(100_000_000_000, "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"),
+            ])
+            .unwrap();
+
+        // store and instantiate the previous release, with signer as admin so it can be migrated
+        let old_code_id = wasm
+            .store_code(&get_wasm_byte_code("osmo_swap_manager_v0_1_0.wasm"), None, &signer)
+            .unwrap()
+            .data
+            .code_id;
+        let manager_contract_addr = wasm
+            .instantiate(old_code_id, &InstantiateMsg { }, Some(&signer.address()), None, &[], &signer)
+            .unwrap()
+            .data
+            .address;
+        let mint_code_id = wasm
+            .store_code(&get_wasm_byte_code("cw20_base.wasm"), None, &signer)
+            .unwrap()
+            .data
+            .code_id;
+        let etf_name = "WladziooEtf_First".to_string();
+        wasm.execute(&manager_contract_addr, &ExecuteMsg::InstantiateCw20 {
+                etf_name: etf_name.to_owned(),
+                etf_symbol: "wetfone".to_string(),
+                code_id: mint_code_id
+            }, &[], &signer)
+            .unwrap();
+        let swap_code_id = wasm
+            .store_code(&get_wasm_byte_code("osmo_swap_v0_1_0.wasm"), None, &signer)
+            .unwrap()
+            .data
+            .code_id;
+        wasm.execute(&manager_contract_addr, &ExecuteMsg::InstantiateSwap { code_id: swap_code_id, debug: false },
+                &[], &signer)
+            .unwrap();
+
+        // populate the 0.1.0 storage layout
+        let pool_id = setup_pool(&app, &signer, "uosmo", "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+        let swap_resp = execute_swap(
+            &wasm, manager_contract_addr.to_owned(), &signer, Coin::new(11, "uosmo"), &etf_name,
+            vec![Route{
+                pool_id: pool_id,
+                token_out_denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string()
+                }],
+            vec![Uint128::from(100u128)]
+        );
+        let inital_swap_received_amount: String = swap_resp.events.iter()
+            .filter(|event| event.ty == "wasm" && event.attributes[1].key == "initial_swap_received_amount")
+            .map(|p| p.attributes[1].value.clone())
+            .collect();
+        let tokens_before: GetTokensResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer.address(), etf_type: etf_name.to_owned() })
+            .unwrap();
+
+        // migrate to the current release
+        let new_code_id = wasm
+            .store_code(&get_wasm_byte_code("osmo_swap_manager.wasm"), None, &signer)
+            .unwrap()
+            .data
+            .code_id;
+        let migrate_resp = wasm
+            .migrate(&manager_contract_addr, new_code_id, &MigrateMsg::V0_2_0 { owner: signer.address() }, &signer)
+            .unwrap();
+        assert!(migrate_resp.events.iter().any(|event| event.ty == "wasm"
+            && event.attributes.iter().any(|a| a.key == "initial_swaps_moved" && a.value == "1")));
+
+        // initial swap is now kept per etf, the rest of the position is untouched
+        let initial_swap: GetInitialSwapResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::GetInitialSwap { sender: signer.address(), etf_type: etf_name.to_owned() })
+            .unwrap();
+        assert_eq!(initial_swap.initial_swap.amount.u128(), inital_swap_received_amount.parse::<u128>().unwrap());
+        let tokens_after: GetTokensResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer.address(), etf_type: etf_name.to_owned() })
+            .unwrap();
+        assert_eq!(tokens_before, tokens_after);
+        let balance: GetBalanceResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer.address(), etf_type: etf_name.to_owned() })
+            .unwrap();
+        assert_eq!(balance.balance, Coin::new(11, "uosmo"));
+        let power: VotingPowerResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::VotingPower { etf_name: etf_name.to_owned(), holder: signer.address(), height: None })
+            .unwrap();
+        assert_eq!(power, VotingPowerResponse { shares: Uint128::new(11), total_shares: Uint128::new(11) });
+
+        // the same migration can't be run twice
+        assert!(wasm
+            .migrate(&manager_contract_addr, new_code_id, &MigrateMsg::V0_2_0 { owner: signer.address() }, &signer)
+            .is_err());
+
+        // the position from before the migration can be redeemed with its shares
+        wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer)
+            .unwrap();
+        let balance = wasm.query::<QueryMsg, GetBalanceResponse>(
+            &manager_contract_addr, &QueryMsg::GetBalance { sender: signer.address(), etf_type: etf_name.to_owned() });
+        assert!(balance.is_err());
+        let power: VotingPowerResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::VotingPower { etf_name: etf_name.to_owned(), holder: signer.address(), height: None })
+            .unwrap();
+        assert_eq!(power.total_shares, Uint128::zero());
+    }
+
+    #[test]
+    fn test_migrate_manager_and_swap_from_v0_1_0() {
+        let app = OsmosisTestApp::default();
+        let wasm = Wasm::new(&app);
+        let signer = app
+            .init_account(&[
+                Coin::new(100_000_000_000, "uosmo"),
+                Coin::new(100_000_000_000, "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"),
+            ])
+            .unwrap();
+        let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+        let etf_name = "WladziooEtf_First".to_string();
+        let store = |filename: &str| wasm
+            .store_code(&get_wasm_byte_code(filename), None, &signer)
+            .unwrap()
+            .data
+            .code_id;
+
+        let old_swap_code_id = store("osmo_swap_v0_1_0.wasm");
+        let manager_contract_addr = wasm
+            .instantiate(store("osmo_swap_manager_v0_1_0.wasm"), &InstantiateMsg { }, Some(&signer.address()), None, &[], &signer)
+            .unwrap()
+            .data
+            .address;
+        wasm.execute(&manager_contract_addr, &ExecuteMsg::InstantiateCw20 {
+                etf_name: etf_name.to_owned(),
+                etf_symbol: "wetfone".to_string(),
+                code_id: store("cw20_base.wasm")
+            }, &[], &signer)
+            .unwrap();
+        wasm.execute(&manager_contract_addr, &ExecuteMsg::InstantiateSwap { code_id: old_swap_code_id, debug: false }, &[], &signer)
+            .unwrap();
+        let pool_id = setup_pool(&app, &signer, "uosmo", atom);
+        let routes = vec![Route { pool_id, token_out_denom: atom.to_string() }];
+        let swap_tokens = ExecuteMsg::SwapTokens {
+            initial_balance: Coin::new(11, "uosmo"),
+            etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes, ratios: vec![Uint128::from(100u128)] },
+            on_behalf_of: None,
+            ibc_sender: None
+        };
+
+        // the migrated manager queries pools through the swap contract, which 0.1.0 can't answer
+        let new_code_id = store("osmo_swap_manager.wasm");
+        wasm.migrate(&manager_contract_addr, new_code_id, &MigrateMsg::V0_2_0 { owner: signer.address() }, &signer)
+            .unwrap();
+        assert!(wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer).is_err());
+
+        // 0.1.0 instantiated its swap contract without an admin. Swap contracts instantiated from now on
+        // have the owner as admin, so they're migrated together with the manager
+        let swap_resp = wasm
+            .execute(&manager_contract_addr, &ExecuteMsg::InstantiateSwap { code_id: old_swap_code_id, debug: false }, &[], &signer)
+            .unwrap();
+        let swap_contract_addr = parse_init_response(swap_resp);
+        assert!(wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer).is_err());
+        wasm.migrate(&swap_contract_addr, store("osmo_swap.wasm"), &SwapMigrateMsg::V0_2_0 { debug: None }, &signer)
+            .unwrap();
+
+        wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer)
+            .unwrap();
+        let balance: GetBalanceResponse = wasm
+            .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer.address(), etf_type: etf_name.to_owned() })
+            .unwrap();
+        assert_eq!(balance.balance, Coin::new(11, "uosmo"));
+    }
+
+    #[test]
+    fn test_pause_and_downtime_breaker() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let pool_id = setup_pool(app, &signer, "uosmo", "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+            let etf_name = "WladziooEtf_First".to_string();
+            let swap_tokens = ExecuteMsg::SwapTokens {
+                initial_balance: Coin::new(11, "uosmo"),
+                etf_swap_routes: EtfSwapRoutes {
+                    name: etf_name.to_owned(),
+                    routes: vec![Route{
+                        pool_id: pool_id,
+                        token_out_denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string()
+                    }],
+                    ratios: vec![Uint128::from(100u128)]
+                },
+                on_behalf_of: None,
+                ibc_sender: None
+            };
+
+            // only the owner can pause
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::Pause { buys: true, redemptions: false }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Pause { buys: true, redemptions: false }, &[], &signer)
+                .unwrap();
+            let pause_state: GetPauseStateResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetPauseState {})
+                .unwrap();
+            assert!(pause_state.buys_paused);
+            assert!(!pause_state.redemptions_paused);
+
+            let err = wasm
+                .execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Contract is paused for \"buys\""));
+
+            // buying works again after unpausing
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Unpause { buys: true, redemptions: false }, &[], &signer)
+                .unwrap();
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer2)
+                .unwrap();
+
+            // pausing redemptions doesn't affect buys
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Pause { buys: false, redemptions: true }, &[], &signer)
+                .unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Contract is paused for \"redemptions\""));
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer2)
+                .unwrap();
+
+            // test chain has never been down, so the breaker lets swaps through
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetDowntimeBreaker { breaker: Some(DowntimeBreaker {
+                    downtime: Downtime::Duration30s as i32,
+                    recovery_seconds: 600
+                }) }, &[], &signer)
+                .unwrap();
+            let pause_state: GetPauseStateResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetPauseState {})
+                .unwrap();
+            assert!(pause_state.recovered_from_downtime);
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(11, "uosmo")], &signer2)
+                .unwrap();
+
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetDowntimeBreaker { breaker: Some(DowntimeBreaker {
+                    downtime: 100,
+                    recovery_seconds: 600
+                }) }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("Invalid downtime duration"));
+            });
+    }
+
+    #[test]
+    fn test_etf_limits() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let pool_id = setup_pool(app, &signer, "uosmo", "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+            let price_pool_id = setup_pool(app, &signer, "uosmo", "usdc");
+            let etf_name = "WladziooEtf_First".to_string();
+            // deposits are valued at TWAPs, pools of the test are younger than the default window
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
+            let routes = vec![Route{
+                pool_id: pool_id,
+                token_out_denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string()
+            }];
+            let limits = EtfLimits {
+                quote_denom: "usdc".to_string(),
+                price_pools: vec![PricePool { denom: "uosmo".to_string(), pool_id: price_pool_id }],
+                min_deposit: Some(Uint128::new(5)),
+                max_deposit: Some(Uint128::new(1000)),
+                max_per_user: Some(Uint128::new(30)),
+                max_total: Some(Uint128::new(40)),
+            };
+
+            // only the owner can set limits, and they need to be able to value every deposit denom
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetEtfLimits { etf_name: etf_name.to_owned(), limits: Some(limits.clone()) }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetEtfLimits { etf_name: etf_name.to_owned(), limits: Some(EtfLimits {
+                    price_pools: vec![],
+                    ..limits.clone()
+                }) }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("No price pool to value \"uosmo\""));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetEtfLimits { etf_name: etf_name.to_owned(), limits: Some(EtfLimits {
+                    min_deposit: Some(Uint128::new(2000)),
+                    ..limits.clone()
+                }) }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("min_deposit can't be above max_deposit"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetEtfLimits { etf_name: etf_name.to_owned(), limits: Some(limits.clone()) }, &[], &signer)
+                .unwrap();
+            let etf_limits: EtfLimitsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::EtfLimits { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(etf_limits, EtfLimitsResponse { limits: Some(limits), total_deposits: vec![], total_deposits_value: Some(Uint128::zero()) });
+
+            // uosmo/usdc pool is balanced, so deposits are worth their uosmo amount
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(3, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes: routes.clone(), ratios: vec![Uint128::from(100u128)] },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(3, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("below the minimum"));
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(11, "uosmo"), &etf_name, routes.clone(), vec![Uint128::from(100u128)]);
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(11, "uosmo"), &etf_name, routes.clone(), vec![Uint128::from(100u128)]);
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(11, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes: routes.clone(), ratios: vec![Uint128::from(100u128)] },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(11, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("above the per-user cap"));
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(11, "uosmo"), &etf_name, routes.clone(), vec![Uint128::from(100u128)]);
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(11, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes: routes.clone(), ratios: vec![Uint128::from(100u128)] },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(11, "uosmo")], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("above the total cap"));
+
+            let etf_limits: EtfLimitsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::EtfLimits { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(etf_limits.total_deposits, vec![Coin::new(33, "uosmo")]);
+            assert_eq!(etf_limits.total_deposits_value, Some(Uint128::new(33)));
+
+            // redeemed deposits free up the total cap
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let etf_limits: EtfLimitsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::EtfLimits { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(etf_limits.total_deposits, vec![Coin::new(11, "uosmo")]);
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(11, "uosmo"), &etf_name, routes, vec![Uint128::from(100u128)]);
+            });
+    }
+
+    #[test]
+    fn test_native_share_denom() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let pool_id = setup_pool(app, &signer, "uosmo", "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+            let etf_name = "WladziooEtf_Native".to_string();
+            let share_denom = format!("factory/{}/wetfnative", manager_contract_addr);
+
+            // covers tokenfactory's denom creation fee
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::CreateNativeDenom {
+                    etf_name: etf_name.to_owned(),
+                    etf_symbol: "wetfnative".to_string()
+                }, &[Coin::new(10_000_000, "uosmo")], &signer)
+                .unwrap();
+            let share_token: GetShareTokenResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetShareToken { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(share_token, GetShareTokenResponse { cw20_contract: None, native_denom: Some(share_denom.to_owned()) });
+
+            // names are shared between cw20 and native etfs
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::CreateNativeDenom {
+                    etf_name: "WladziooEtf_First".to_string(),
+                    etf_symbol: "wetfother".to_string()
+                }, &[Coin::new(10_000_000, "uosmo")], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("already exists"));
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                vec![Route{
+                    pool_id: pool_id,
+                    token_out_denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string()
+                }],
+                vec![Uint128::from(100u128)]);
+            let share_balance = |address: String| -> u128 {
+                Bank::new(app)
+                    .query_all_balances(&QueryAllBalancesRequest { address, pagination: None })
+                    .unwrap().balances.into_iter().find(|c| c.denom == share_denom)
+                    .map_or(0, |c| c.amount.parse::<u128>().unwrap())
+            };
+            assert_eq!(share_balance(signer2.address()), 1000);
+            assert_eq!(share_balance(manager_contract_addr.to_owned()), 0);
+
+            // shares have to be sent along
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Redeeming requires sending back the shares to redeem"));
+
+            // shares handed over redeem their part of the etf, in kind
+            app.execute::<_, MsgSendResponse>(MsgSend {
+                    from_address: signer2.address(),
+                    to_address: signer.address(),
+                    amount: vec![ProtoCoin { denom: share_denom.to_owned(), amount: "400".to_string() }],
+                }, "/cosmos.bank.v1beta1.MsgSend", &signer2)
+                .unwrap();
+            let atom_balance = |address: String| -> u128 {
+                Bank::new(app)
+                    .query_balance(&QueryBalanceRequest { address, denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string() })
+                    .unwrap().balance.map_or(0, |c| c.amount.parse::<u128>().unwrap())
+            };
+            let held: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            let atom_before = atom_balance(signer.address());
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() },
+                    &[Coin::new(400, &share_denom)], &signer)
+                .unwrap();
+            assert_eq!(atom_balance(signer.address()) - atom_before, held.tokens_per_etf[0].amount.u128() * 400 / 1000);
+            assert_eq!(share_balance(signer.address()), 0);
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() },
+                    &[Coin::new(600, &share_denom)], &signer2)
+                .unwrap();
+            assert_eq!(share_balance(signer2.address()), 0);
+            assert_eq!(share_balance(manager_contract_addr.to_owned()), 0);
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert!(balance.balance.amount.is_zero());
+            });
+    }
+
+    #[test]
+    fn test_swap_exact_amount_out() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id_1 = setup_pool(app, &signer, "uosmo", atom);
+            let pool_id_2 = setup_pool(app, &signer, atom, "uion");
+            let etf_name = "WladziooEtf_First".to_string();
+            let swap_exact_out = |max_initial_balance: Coin| ExecuteMsg::SwapTokensExactAmountOut {
+                max_initial_balance,
+                etf_swap_routes: EtfSwapRoutes {
+                    name: etf_name.to_owned(),
+                    routes: vec![
+                        Route { pool_id: pool_id_1, token_out_denom: atom.to_string() },
+                        Route { pool_id: pool_id_2, token_out_denom: "uion".to_string() },
+                    ],
+                    ratios: vec![Uint128::new(50), Uint128::new(50)],
+                },
+                shares: Uint128::new(150),
+                max_slippage: Decimal::percent(5),
+            };
+
+            // the first shares are worth a uosmo each, roughly 155uosmo are needed, bounded by 5% on top
+            let err = wasm
+                .execute(&manager_contract_addr, &swap_exact_out(Coin::new(100, "uosmo")), &[Coin::new(100, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Deposit doesn't cover the maximum estimated input"));
+
+            let res = wasm
+                .execute(&manager_contract_addr, &swap_exact_out(Coin::new(1000, "uosmo")), &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap();
+            let shares_minted = res.events.iter()
+                .flat_map(|event| event.attributes.iter())
+                .find(|attr| attr.key == "shares_minted")
+                .unwrap();
+            assert_eq!(shares_minted.value, "150");
+
+            // 75uosmo worth of each token
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(tokens.tokens_per_etf, vec![Coin::new(75, atom), Coin::new(75, "uion")]);
+
+            // only what was spent counts as the deposit, the rest is back with the user
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance.denom, "uosmo");
+            assert!(balance.balance.amount > Uint128::new(150) && balance.balance.amount < Uint128::new(200));
+            let swap_contract_osmo = Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: swap_contract_addr, pagination: None })
+                .unwrap().balances.into_iter().find(|c| c.denom == "uosmo");
+            assert!(swap_contract_osmo.is_none());
+
+            // the position can be redeemed like one bought with exact input
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            });
+    }
+
+    #[test]
+    fn test_swap_tokens_refunds_excess_funds() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let swap_tokens = ExecuteMsg::SwapTokens {
+                initial_balance: Coin::new(1000, "uosmo"),
+                etf_swap_routes: EtfSwapRoutes {
+                    name: etf_name.to_owned(),
+                    routes: vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                    ratios: vec![Uint128::from(100u128)]
+                },
+                on_behalf_of: None,
+                ibc_sender: None
+            };
+            let swap_contract_balances = || Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: swap_contract_addr.to_owned(), pagination: None })
+                .unwrap().balances;
+
+            // over-funded: only the initial balance gets swapped and accounted for
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(1500, "uosmo")], &signer2)
+                .unwrap();
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(1000, "uosmo"));
+            assert!(swap_contract_balances().iter().all(|c| c.denom == atom));
+
+            // multi-denom: coins other than the deposit are sent back
+            let uion_before = Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: signer2.address(), pagination: None })
+                .unwrap().balances.into_iter().find(|c| c.denom == "uion").unwrap().amount;
+            wasm.execute(&manager_contract_addr, &swap_tokens,
+                    &[Coin::new(1000, "uion"), Coin::new(1000, "uosmo"), Coin::new(300, "usdc")], &signer2)
+                .unwrap();
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(2000, "uosmo"));
+            assert!(swap_contract_balances().iter().all(|c| c.denom == atom));
+            let uion_after = Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: signer2.address(), pagination: None })
+                .unwrap().balances.into_iter().find(|c| c.denom == "uion").unwrap().amount;
+            assert_eq!(uion_before, uion_after);
+            let manager_balances = Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: manager_contract_addr.to_owned(), pagination: None })
+                .unwrap().balances;
+            assert!(manager_balances.is_empty());
+            });
+    }
+
+    #[test]
+    fn test_stableswap_route_and_nav() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let stableswap_pool_id = Gamm::new(app)
+                .create_basic_stableswap_pool(&[Coin::new(10_000, atom), Coin::new(10_000, "usdc")], &signer)
+                .unwrap()
+                .data
+                .pool_id;
+            let etf_name = "WladziooEtf_First".to_string();
+
+            // a stableswap token that isn't in the pool is still caught by validation
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(1000, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes {
+                        name: etf_name.to_owned(),
+                        routes: vec![Route { pool_id: stableswap_pool_id, token_out_denom: "uion".to_string() }],
+                        ratios: vec![Uint128::from(100u128)]
+                    },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Token \"uion\" not found in pool"));
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                vec![
+                    Route { pool_id, token_out_denom: atom.to_string() },
+                    Route { pool_id: stableswap_pool_id, token_out_denom: "usdc".to_string() },
+                ],
+                vec![Uint128::from(50u128), Uint128::from(50u128)]);
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(tokens.tokens_per_etf.len(), 2);
+
+            // swap fees make the position worth slightly less than the deposit
+            let nav: GetNavResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetNav { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(nav.nav.denom, "uosmo");
+            assert!(nav.nav.amount > Uint128::new(900) && nav.nav.amount < Uint128::new(1000));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            });
+    }
+
+    #[test]
+    fn test_pool_share_etf() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let lp_pool_id = setup_pool(app, &signer, atom, "usdc");
+            let shares_denom = format!("gamm/pool/{}", lp_pool_id);
+            let etf_name = "WladziooEtf_First".to_string();
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                vec![
+                    Route { pool_id, token_out_denom: atom.to_string() },
+                    Route { pool_id: lp_pool_id, token_out_denom: shares_denom.to_owned() },
+                ],
+                vec![Uint128::from(50u128), Uint128::from(50u128)]);
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert!(tokens.tokens_per_etf.iter().any(|c| c.denom == shares_denom && !c.amount.is_zero()));
+
+            // shares are valued by the tokens they'd exit into
+            let nav: GetNavResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetNav { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(nav.nav.denom, "uosmo");
+            assert!(nav.nav.amount > Uint128::new(800) && nav.nav.amount < Uint128::new(1000));
+
+            // pool shares can't be bought with exact output
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokensExactAmountOut {
+                    max_initial_balance: Coin::new(1000, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes {
+                        name: etf_name.to_owned(),
+                        routes: vec![Route { pool_id: lp_pool_id, token_out_denom: shares_denom.to_owned() }],
+                        ratios: vec![Uint128::new(100)],
+                    },
+                    shares: Uint128::new(100),
+                    max_slippage: Decimal::percent(5),
+                }, &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("pool shares can't be bought with exact output"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            // the whole basket, pool shares included, has been sold back
+            wasm.query::<QueryMsg, GetTokensResponse>(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap_err();
+            });
+    }
+
+    #[test]
+    fn test_batch_settlement() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let usdc_pool_id = setup_pool(app, &signer, atom, "usdc");
+            let etf_name = "WladziooEtf_First".to_string();
+            // redemptions are settled at TWAPs, pools of the test are younger than the default window
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
+            let batch = BatchConfig {
+                deposit_denom: "uosmo".to_string(),
+                routes: vec![
+                    Route { pool_id, token_out_denom: atom.to_string() },
+                    Route { pool_id: usdc_pool_id, token_out_denom: "usdc".to_string() },
+                ],
+                ratios: vec![Uint128::from(50u128), Uint128::from(50u128)]
+            };
+
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::QueueDeposit { etf_name: etf_name.to_owned() },
+                    &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Batching is not enabled"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetBatchMode { etf_name: etf_name.to_owned(), batch: Some(batch.to_owned()) },
+                    &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetBatchMode { etf_name: etf_name.to_owned(), batch: Some(batch.to_owned()) },
+                    &[], &signer)
+                .unwrap();
+
+            // first batch only buys
+            for user in [&signer, &signer2] {
+                wasm.execute(&manager_contract_addr, &ExecuteMsg::QueueDeposit { etf_name: etf_name.to_owned() },
+                        &[Coin::new(1000, "uosmo")], user)
+                    .unwrap();
+            }
+            let state: BatchStateResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::BatchState { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(state, BatchStateResponse { config: Some(batch.to_owned()), queued_deposits: Uint128::new(2000), queued_redemptions: 0 });
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetBatchMode { etf_name: etf_name.to_owned(), batch: None }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("has queued requests"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SettleBatch { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let first_tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            let second_tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(first_tokens.tokens_per_etf.len(), 2);
+            for (first, second) in first_tokens.tokens_per_etf.iter().zip(second_tokens.tokens_per_etf.iter()) {
+                assert_eq!(first.denom, second.denom);
+                assert!(first.amount.abs_diff(second.amount) <= Uint128::one());
+            }
+            let claim: BatchClaim = wasm
+                .query(&manager_contract_addr, &QueryMsg::BatchClaim { sender: signer2.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(claim, BatchClaim { shares: Uint128::new(1000), proceeds: vec![] });
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ClaimBatch { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ClaimBatch { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("batch claim"));
+
+            // second batch: the smaller deposit is fully netted against the redemption
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::QueueRedemption { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            wasm.query::<QueryMsg, GetTokensResponse>(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap_err();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::QueueDeposit { etf_name: etf_name.to_owned() },
+                    &[Coin::new(400, "uosmo")], &signer)
+                .unwrap();
+            let settle_resp = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SettleBatch { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+            let redemptions_value: u128 = settle_resp.events.iter()
+                .flat_map(|event| event.attributes.iter())
+                .find(|attr| attr.key == "redemptions_value")
+                .unwrap().value.parse().unwrap();
+            assert!(redemptions_value > 400);
+
+            let claim: BatchClaim = wasm
+                .query(&manager_contract_addr, &QueryMsg::BatchClaim { sender: signer.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(claim.shares, Uint128::new(1400));
+            let claim: BatchClaim = wasm
+                .query(&manager_contract_addr, &QueryMsg::BatchClaim { sender: signer2.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert!(claim.shares.is_zero());
+            assert_eq!(claim.proceeds[0].denom, "uosmo");
+            assert!(claim.proceeds[0].amount > Uint128::new(900) && claim.proceeds[0].amount < Uint128::new(1000));
+
+            // the depositor took over part of the redeemed tokens on top of the first batch
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            for (before, after) in first_tokens.tokens_per_etf.iter().zip(tokens.tokens_per_etf.iter()) {
+                assert!(after.amount > before.amount);
+            }
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(1400, "uosmo"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ClaimBatch { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let claim: BatchClaim = wasm
+                .query(&manager_contract_addr, &QueryMsg::BatchClaim { sender: signer2.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(claim, BatchClaim::default());
+            });
+    }
+
+    #[test]
+    fn test_dca_plan() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let create_plan = ExecuteMsg::CreateDcaPlan {
+                etf_name: etf_name.to_owned(),
+                amount_per_period: Coin::new(1000, "uosmo"),
+                period: 3600,
+                num_periods: 3,
+                routes: vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                ratios: vec![Uint128::from(100u128)]
+            };
+
+            // escrow has to cover all periods
+            let err = wasm
+                .execute(&manager_contract_addr, &create_plan, &[Coin::new(2999, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("does not match user's funds"));
+            wasm.execute(&manager_contract_addr, &create_plan, &[Coin::new(3000, "uosmo")], &signer2)
+                .unwrap();
+
+            let due_resp = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteDue {}, &[], &signer)
+                .unwrap();
+            assert!(due_resp.events.iter().flat_map(|event| event.attributes.iter())
+                .any(|attr| attr.key == "executed" && attr.value == "1"));
+            // keeper's bounty is taken out of the purchase
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(999, "uosmo"));
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(tokens.tokens_per_etf[0].denom, atom);
+
+            let plans: DcaPlansResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DcaPlans { owner: signer2.address() })
+                .unwrap();
+            assert_eq!(plans.plans.len(), 1);
+            assert_eq!(plans.plans[0].periods_left, 2);
+            let plan_id = plans.plans[0].id;
+
+            // next period isn't due yet
+            let due_resp = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteDue {}, &[], &signer)
+                .unwrap();
+            assert!(due_resp.events.iter().flat_map(|event| event.attributes.iter())
+                .any(|attr| attr.key == "executed" && attr.value == "0"));
+
+            // only the owner manages the plan
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::PauseDcaPlan { plan_id }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("not found"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::PauseDcaPlan { plan_id }, &[], &signer2)
+                .unwrap();
+            let plans: DcaPlansResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DcaPlans { owner: signer2.address() })
+                .unwrap();
+            assert!(plans.plans[0].paused);
+
+            let cancel_resp = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::CancelDcaPlan { plan_id }, &[], &signer2)
+                .unwrap();
+            assert!(cancel_resp.events.iter().flat_map(|event| event.attributes.iter())
+                .any(|attr| attr.key == "refund" && attr.value == "2000uosmo"));
+            let plans: DcaPlansResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DcaPlans { owner: signer2.address() })
+                .unwrap();
+            assert!(plans.plans.is_empty());
+            });
+    }
+
+    #[test]
+    fn test_nav_orders() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let expires_at = Timestamp::from_seconds(4_000_000_000);
+            // pools of the test are younger than the default window
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                vec![Uint128::from(100u128)]);
+            let nav: NavPerShareResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::NavPerShare { etf_name: etf_name.to_owned(), denom: "uosmo".to_string() })
+                .unwrap();
+            assert!(nav.nav_per_share > Decimal::percent(90) && nav.nav_per_share < Decimal::one());
+
+            let buy = NavOrderKind::Buy {
+                deposit: Coin::new(500, "uosmo"),
+                routes: vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                ratios: vec![Uint128::from(100u128)]
+            };
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::PlaceNavOrder {
+                    etf_name: etf_name.to_owned(), kind: buy.to_owned(), nav_per_share: Decimal::one(), expires_at: Timestamp::from_seconds(1)
+                }, &[Coin::new(500, "uosmo")], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("expires_at has to be in the future"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::PlaceNavOrder {
+                    etf_name: etf_name.to_owned(), kind: buy, nav_per_share: Decimal::one(), expires_at
+                }, &[Coin::new(500, "uosmo")], &signer)
+                .unwrap();
+            let orders: NavOrdersResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::NavOrders { owner: signer.address() })
+                .unwrap();
+            assert_eq!(orders.orders.len(), 1);
+            assert_eq!(orders.orders[0].escrow, vec![Coin::new(500, "uosmo")]);
+
+            // nav is below the limit, any keeper can execute
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ExecuteNavOrder { owner: signer.address(), order_id: orders.orders[0].id }, &[], &signer2)
+                .unwrap();
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(500, "uosmo"));
+            let orders: NavOrdersResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::NavOrders { owner: signer.address() })
+                .unwrap();
+            assert!(orders.orders.is_empty());
+
+            // redemption waits for nav to double
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::PlaceNavOrder {
+                    etf_name: etf_name.to_owned(), kind: NavOrderKind::Redeem {}, nav_per_share: Decimal::percent(200), expires_at
+                }, &[], &signer2)
+                .unwrap();
+            let orders: NavOrdersResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::NavOrders { owner: signer2.address() })
+                .unwrap();
+            let order_id = orders.orders[0].id;
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteNavOrder { owner: signer2.address(), order_id }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("doesn't meet the order's"));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::CancelNavOrder { order_id }, &[], &signer2)
+                .unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteNavOrder { owner: signer2.address(), order_id }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("not found"));
+            });
+    }
+
+    #[test]
+    fn test_donations() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let beneficiary = app.init_account(&[Coin::new(1_000_000, "uosmo")]).unwrap();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                vec![Uint128::from(100u128)]);
+
+            let set_donation = ExecuteMsg::SetDonation {
+                etf_name: etf_name.to_owned(),
+                donation: Some(DonationSettings { beneficiary: beneficiary.address(), percent: Decimal::percent(50) })
+            };
+            let err = wasm.execute(&manager_contract_addr, &set_donation, &[], &signer2).unwrap_err();
+            assert!(err.to_string().contains("is not a beneficiary"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetDonationBeneficiaries {
+                    etf_name: etf_name.to_owned(), beneficiaries: vec![beneficiary.address()]
+                }, &[], &signer)
+                .unwrap();
+            wasm.execute(&manager_contract_addr, &set_donation, &[], &signer2).unwrap();
+            let donation: DonationResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::Donation { holder: signer2.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            let high_water_mark = donation.donation.unwrap().high_water_mark;
+            assert!(high_water_mark > Uint128::new(900) && high_water_mark < Uint128::new(1000));
+
+            // someone else's big purchase pushes atom up, the position grows
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(5000, "uosmo"), &etf_name,
+                vec![Route { pool_id, token_out_denom: atom.to_string() }],
+                vec![Uint128::from(100u128)]);
+            let tokens_before: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::HarvestDonation { holder: signer2.address(), etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+
+            let totals: DonationTotalsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DonationTotals { beneficiary: beneficiary.address() })
+                .unwrap();
+            assert_eq!(totals.donated.len(), 1);
+            assert_eq!(totals.donated[0].denom, atom);
+            let tokens_after: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(tokens_after.tokens_per_etf[0].amount + totals.donated[0].amount, tokens_before.tokens_per_etf[0].amount);
+            let donated_balance = Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address: beneficiary.address(), pagination: None })
+                .unwrap().balances.into_iter().find(|c| c.denom == atom).unwrap();
+            assert_eq!(donated_balance.amount, totals.donated[0].amount.to_string());
+
+            // the mark moved up, so harvesting again right away has nothing to donate
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::HarvestDonation { holder: signer2.address(), etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+            let totals_again: DonationTotalsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DonationTotals { beneficiary: beneficiary.address() })
+                .unwrap();
+            assert_eq!(totals_again, totals);
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let donation: DonationResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::Donation { holder: signer2.address(), etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert!(donation.donation.unwrap().high_water_mark.is_zero());
+            });
+    }
+
+    #[test]
+    fn test_holder_governance() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let routes = vec![Route { pool_id, token_out_denom: atom.to_string() }];
+            let beneficiary = app.init_account(&[Coin::new(1_000_000, "uosmo")]).unwrap();
+            let late_holder = app.init_account(&[Coin::new(1_000_000_000, "uosmo")]).unwrap();
+            let config = GovConfig { quorum: Decimal::percent(50), threshold: Decimal::percent(50), voting_period: 20 };
+            let action = EtfAdminMsg::SetDonationBeneficiaries { beneficiaries: vec![beneficiary.address()] };
+            let propose = ExecuteMsg::Propose { etf_name: etf_name.to_owned(), action: action.clone() };
+
+            let err = wasm.execute(&manager_contract_addr, &propose, &[], &signer2).unwrap_err();
+            assert!(err.to_string().contains("Holder governance is disabled"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetGovConfig { config: Some(config.clone()) }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetGovConfig { config: Some(config.clone()) }, &[], &signer)
+                .unwrap();
+            let err = wasm.execute(&manager_contract_addr, &propose, &[], &signer2).unwrap_err();
+            assert!(err.to_string().contains("held no shares"));
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                routes.clone(), vec![Uint128::from(100u128)]);
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(3000, "uosmo"), &etf_name,
+                routes.clone(), vec![Uint128::from(100u128)]);
+            let power: VotingPowerResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::VotingPower { etf_name: etf_name.to_owned(), holder: signer2.address(), height: None })
+                .unwrap();
+            assert_eq!(power, VotingPowerResponse { shares: Uint128::new(1000), total_shares: Uint128::new(4000) });
+
+            // each block moves time by 5 seconds
+            let pass_voting_period = || {
+                for _ in 0..4 {
+                    wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 600 }, &[], &signer)
+                        .unwrap();
+                }
+            };
+
+            // the bigger holder votes the first proposal down
+            wasm.execute(&manager_contract_addr, &propose, &[], &signer2).unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteProposal { proposal_id: 1 }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("is still open"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 1, vote: VoteOption::Yes }, &[], &signer2)
+                .unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 1, vote: VoteOption::No }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("already voted"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 1, vote: VoteOption::No }, &[], &signer)
+                .unwrap();
+            pass_voting_period();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 1, vote: VoteOption::Yes }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("is closed"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ExecuteProposal { proposal_id: 1 }, &[], &signer2)
+                .unwrap();
+            let proposal: ProposalResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::Proposal { proposal_id: 1 })
+                .unwrap();
+            assert_eq!(proposal.proposal.status, ProposalStatus::Rejected);
+            assert_eq!((proposal.proposal.yes, proposal.proposal.no), (Uint128::new(1000), Uint128::new(3000)));
+
+            // shares bought after the snapshot don't vote, abstaining counts towards quorum only
+            wasm.execute(&manager_contract_addr, &propose, &[], &signer2).unwrap();
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &late_holder, Coin::new(100_000, "uosmo"), &etf_name,
+                routes.clone(), vec![Uint128::from(100u128)]);
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 2, vote: VoteOption::No }, &[], &late_holder)
+                .unwrap_err();
+            assert!(err.to_string().contains("held no shares"));
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 2, vote: VoteOption::Yes }, &[], &signer2)
+                .unwrap();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Vote { proposal_id: 2, vote: VoteOption::Abstain }, &[], &signer)
+                .unwrap();
+            pass_voting_period();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ExecuteProposal { proposal_id: 2 }, &[], &late_holder)
+                .unwrap();
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ExecuteProposal { proposal_id: 2 }, &[], &late_holder)
+                .unwrap_err();
+            assert!(err.to_string().contains("is closed"));
+
+            let beneficiaries: DonationBeneficiariesResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::DonationBeneficiaries { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(beneficiaries.beneficiaries, vec![beneficiary.address()]);
+            let proposals: ProposalsResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::Proposals { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(proposals.proposals.iter().map(|p| p.status).collect::<Vec<_>>(), vec![ProposalStatus::Rejected, ProposalStatus::Executed]);
+            assert_eq!(proposals.proposals[1].action, action);
+
+            // redeemed positions lose their voting power
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let power: VotingPowerResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::VotingPower { etf_name: etf_name.to_owned(), holder: signer2.address(), height: None })
+                .unwrap();
+            assert_eq!(power, VotingPowerResponse { shares: Uint128::zero(), total_shares: Uint128::new(103_000) });
+            });
+    }
+
+    #[test]
+    fn test_index_methodology() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            setup_pool(app, &signer, "uosmo", atom);
+            let ion_pool_id = setup_pool(app, &signer, atom, "uion");
+            // three times the liquidity of the uion pool
+            let iou_pool_id = Gamm::new(app)
+                .create_basic_pool(&[Coin::new(30_000, atom), Coin::new(30_000, "uiou")], &signer)
+                .unwrap()
+                .data
+                .pool_id;
+            let etf_name = "WladziooEtf_First".to_string();
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                .unwrap();
+
+            let methodology = |weighting: Weighting, iou_cap: Option<Decimal>| IndexMethodology {
+                weighting,
+                deposit_denom: "uosmo".to_string(),
+                components: vec![
+                    IndexComponent { route: Route { pool_id: ion_pool_id, token_out_denom: "uion".to_string() }, cap: None },
+                    IndexComponent { route: Route { pool_id: iou_pool_id, token_out_denom: "uiou".to_string() }, cap: iou_cap },
+                ],
+                rebalance_period: 10,
+            };
+            let set_methodology = |methodology: IndexMethodology| {
+                wasm.execute(&manager_contract_addr, &ExecuteMsg::SetIndexMethodology {
+                    etf_name: etf_name.to_owned(), methodology: Some(methodology)
+                }, &[], &signer)
+            };
+            let query_ratios = || {
+                let index: IndexResponse = wasm
+                    .query(&manager_contract_addr, &QueryMsg::Index { etf_name: etf_name.to_owned() })
+                    .unwrap();
+                index.weights.unwrap().ratios
+            };
+
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetIndexMethodology {
+                    etf_name: etf_name.to_owned(), methodology: Some(methodology(Weighting::Equal, None))
+                }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            let err = set_methodology(methodology(Weighting::Equal, Some(Decimal::percent(40)))).unwrap_err();
+            assert!(err.to_string().contains("caps have to add up to at least 1"));
+
+            set_methodology(methodology(Weighting::Equal, None)).unwrap();
+            assert_eq!(query_ratios(), vec![Uint128::new(50), Uint128::new(50)]);
+            set_methodology(methodology(Weighting::Liquidity, None)).unwrap();
+            assert_eq!(query_ratios(), vec![Uint128::new(25), Uint128::new(75)]);
+            // the excess above the cap goes to the other component
+            set_methodology(methodology(Weighting::Liquidity, Some(Decimal::percent(60)))).unwrap();
+            assert_eq!(query_ratios(), vec![Uint128::new(40), Uint128::new(60)]);
+            set_methodology(methodology(Weighting::MarketCap, Some(Decimal::percent(60)))).unwrap();
+            let ratios = query_ratios();
+            assert_eq!(ratios.iter().sum::<Uint128>(), Uint128::new(100));
+            assert!(ratios[1] <= Uint128::new(60));
+
+            // buyers leave routes and ratios to the index
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(1000, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes {
+                        name: etf_name.to_owned(),
+                        routes: vec![Route { pool_id: ion_pool_id, token_out_denom: "uion".to_string() }],
+                        ratios: vec![Uint128::from(100u128)]
+                    },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("are set by its index methodology"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(1000, "usdc"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes: vec![], ratios: vec![] },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(1000, "usdc")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("usdc"));
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name, vec![], vec![]);
+            let tokens: GetTokensResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetTokens { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            let denoms: Vec<String> = tokens.tokens_per_etf.iter().map(|c| c.denom.to_owned()).collect();
+            assert_eq!(denoms, vec!["uion".to_string(), "uiou".to_string()]);
+
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::Rebalance { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("can't be rebalanced before"));
+            // each block moves time by 5 seconds
+            for _ in 0..2 {
+                wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 5 }, &[], &signer)
+                    .unwrap();
+            }
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::Rebalance { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let ratios = query_ratios();
+            assert_eq!(ratios.iter().sum::<Uint128>(), Uint128::new(100));
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetIndexMethodology { etf_name: etf_name.to_owned(), methodology: None }, &[], &signer)
+                .unwrap();
+            let index: IndexResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::Index { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(index, IndexResponse { methodology: None, weights: None });
+            });
+    }
+
+    #[test]
+    fn test_pair_routes() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            setup_pool(app, &signer, "uosmo", atom);
+            let ion_pool_id = setup_pool(app, &signer, atom, "uion");
+            let etf_name = "WladziooEtf_First".to_string();
+            let query_pair = || {
+                let res: PairRouteResponse = wasm
+                    .query(&manager_contract_addr, &QueryMsg::PairRoute { pool_id: ion_pool_id })
+                    .unwrap();
+                res.pair
+            };
+
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SetPairRoute {
+                    pool_id: ion_pool_id, pair: Some(mint_contract_addr.to_owned())
+                }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+
+            // a contract without the pair interface fails route validation
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetPairRoute {
+                pool_id: ion_pool_id, pair: Some(mint_contract_addr.to_owned())
+            }, &[], &signer)
+            .unwrap();
+            assert_eq!(query_pair().unwrap().to_string(), mint_contract_addr);
+            let swap_tokens = ExecuteMsg::SwapTokens {
+                initial_balance: Coin::new(1000, "uosmo"),
+                etf_swap_routes: EtfSwapRoutes {
+                    name: etf_name.to_owned(),
+                    routes: vec![Route { pool_id: ion_pool_id, token_out_denom: "uion".to_string() }],
+                    ratios: vec![Uint128::from(100u128)]
+                },
+                on_behalf_of: None,
+                ibc_sender: None
+            };
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap_err();
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::SetPairRoute { pool_id: ion_pool_id, pair: None }, &[], &signer)
+                .unwrap();
+            assert_eq!(query_pair(), None);
+            wasm.execute(&manager_contract_addr, &swap_tokens, &[Coin::new(1000, "uosmo")], &signer2)
+                .unwrap();
+            });
+    }
+
+    #[test]
+    fn test_outflow_limits() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let routes = vec![Route { pool_id, token_out_denom: atom.to_string() }];
+            let ratios = vec![Uint128::from(100u128)];
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(400, "uosmo"), &etf_name,
+                routes.to_owned(), ratios.to_owned());
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(1000, "uosmo"), &etf_name,
+                routes.to_owned(), ratios.to_owned());
+
+            let limit = OutflowLimit { max_percent: Decimal::percent(50), window: 20 };
+            let set_limit = ExecuteMsg::SetOutflowLimit { etf_name: etf_name.to_owned(), limit: Some(limit.to_owned()) };
+            let err = wasm.execute(&manager_contract_addr, &set_limit, &[], &signer2).unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            wasm.execute(&manager_contract_addr, &set_limit, &[], &signer).unwrap();
+            let capacity: OutflowCapacityResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::OutflowCapacity { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(capacity.limit, Some(limit));
+            assert_eq!(capacity.remaining, Some(Uint128::new(700)));
+            assert!(capacity.queue.is_empty());
+
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+            let capacity: OutflowCapacityResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::OutflowCapacity { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(capacity.remaining, Some(Uint128::new(300)));
+
+            // over what's left of the window, the position waits in the queue
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            let capacity: OutflowCapacityResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::OutflowCapacity { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(capacity.queue.len(), 1);
+            assert_eq!(capacity.queue[0].sender, signer2.address());
+            assert_eq!(capacity.queue[0].shares, Uint128::new(1000));
+            let balance: GetBalanceResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() })
+                .unwrap();
+            assert_eq!(balance.balance, Coin::new(1000, "uosmo"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("has a queued redemption"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(100, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes, ratios },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(100, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("has a queued redemption"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ProcessQueuedRedemption { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("Outflow limit"));
+
+            // each block moves time by 5 seconds. A window only has capacity for 500 shares, the position
+            // goes through once nothing was redeemed over the last 20 seconds
+            for _ in 0..8 {
+                wasm.execute(&manager_contract_addr, &ExecuteMsg::SetNavTwapWindow { seconds: 600 }, &[], &signer)
+                    .unwrap();
+            }
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ProcessQueuedRedemption { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+            let balance = wasm.query::<QueryMsg, GetBalanceResponse>(
+                &manager_contract_addr, &QueryMsg::GetBalance { sender: signer2.address(), etf_type: etf_name.to_owned() });
+            assert!(balance.is_err());
+            let capacity: OutflowCapacityResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::OutflowCapacity { etf_name: etf_name.to_owned() })
+                .unwrap();
+            assert!(capacity.queue.is_empty());
+            assert_eq!(capacity.outflow.unwrap().outflow, Uint128::new(1000));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ProcessQueuedRedemption { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap_err();
+            assert!(err.to_string().contains("No redemptions"));
+            });
+    }
+
+    #[test]
+    fn test_wind_down() {
+        with_env_setup(
+            |app, wasm, signer, signer2, manager_contract_addr, _swap_contract_addr, _mint_contract_addr| {
+            let atom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+            let pool_id = setup_pool(app, &signer, "uosmo", atom);
+            let etf_name = "WladziooEtf_First".to_string();
+            let routes = vec![Route { pool_id, token_out_denom: atom.to_string() }];
+            let ratios = vec![Uint128::from(100u128)];
+            let atom_balance = |address: String| Bank::new(app)
+                .query_all_balances(&QueryAllBalancesRequest { address, pagination: None })
+                .unwrap().balances.into_iter().find(|c| c.denom == atom)
+                .unwrap().amount.parse::<u128>().unwrap();
+
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer, Coin::new(1000, "uosmo"), &etf_name,
+                routes.to_owned(), ratios.to_owned());
+            execute_swap(&wasm, manager_contract_addr.to_owned(), &signer2, Coin::new(500, "uosmo"), &etf_name,
+                routes.to_owned(), ratios.to_owned());
+            let held: u128 = [signer.address(), signer2.address()].into_iter()
+                .map(|sender| wasm
+                    .query::<QueryMsg, GetTokensResponse>(&manager_contract_addr, &QueryMsg::GetTokens { sender, etf_type: etf_name.to_owned() })
+                    .unwrap().tokens_per_etf[0].amount.u128())
+                .sum();
+
+            let wind_down = ExecuteMsg::WindDown { etf_name: etf_name.to_owned() };
+            let err = wasm.execute(&manager_contract_addr, &wind_down, &[], &signer2).unwrap_err();
+            assert!(err.to_string().contains("Unauthorized"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ClaimWindDown { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("isn't wound down"));
+            wasm.execute(&manager_contract_addr, &wind_down, &[], &signer).unwrap();
+            let res: WindDownResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::WindDown { etf_name: etf_name.to_owned() })
+                .unwrap();
+            let snapshot = res.wind_down.unwrap();
+            assert_eq!(snapshot.basket, vec![Coin::new(held, atom)]);
+            assert_eq!(snapshot.total_shares, Uint128::new(1500));
+
+            // no more buys, swaps or redemptions
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::SwapTokens {
+                    initial_balance: Coin::new(100, "uosmo"),
+                    etf_swap_routes: EtfSwapRoutes { name: etf_name.to_owned(), routes, ratios },
+                    on_behalf_of: None,
+                    ibc_sender: None
+                }, &[Coin::new(100, "uosmo")], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("is wound down"));
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::RedeemTokens { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("is wound down"));
+            let err = wasm.execute(&manager_contract_addr, &wind_down, &[], &signer).unwrap_err();
+            assert!(err.to_string().contains("is wound down"));
+
+            let claim: WindDownClaimResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::WindDownClaim { etf_name: etf_name.to_owned(), holder: signer2.address() })
+                .unwrap();
+            assert_eq!(claim.claimable, vec![Coin::new(held / 3, atom)]);
+            assert!(claim.claimed.is_empty());
+            let before = atom_balance(signer2.address());
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ClaimWindDown { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap();
+            assert_eq!(atom_balance(signer2.address()), before + held / 3);
+            let claim: WindDownClaimResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::WindDownClaim { etf_name: etf_name.to_owned(), holder: signer2.address() })
+                .unwrap();
+            assert!(claim.claimable.is_empty());
+            assert_eq!(claim.claimed, vec![Coin::new(held / 3, atom)]);
+            let err = wasm
+                .execute(&manager_contract_addr, &ExecuteMsg::ClaimWindDown { etf_name: etf_name.to_owned() }, &[], &signer2)
+                .unwrap_err();
+            assert!(err.to_string().contains("to claim with"));
+
+            // the last claim takes the rest of the basket
+            let before = atom_balance(signer.address());
+            wasm.execute(&manager_contract_addr, &ExecuteMsg::ClaimWindDown { etf_name: etf_name.to_owned() }, &[], &signer)
+                .unwrap();
+            assert_eq!(atom_balance(signer.address()), before + held - held / 3);
+            let res: WindDownResponse = wasm
+                .query(&manager_contract_addr, &QueryMsg::WindDown { etf_name: etf_name.to_owned() })
+                .unwrap();
+            let snapshot = res.wind_down.unwrap();
+            assert_eq!(snapshot.claimed, snapshot.basket);
+            assert_eq!(snapshot.claimed_shares, Uint128::new(1500));
+            });
+    }
+}
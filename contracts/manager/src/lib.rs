@@ -1,7 +1,9 @@
 pub mod contract;
 mod error;
 pub mod helpers;
+pub mod ibc_hooks;
 pub mod integration_tests;
+pub mod migrations;
 pub mod msg;
 pub mod state;
 pub use crate::error::ContractError;
@@ -0,0 +1,199 @@
+// Storage migrations between manager releases.
+// Each release that changes the storage layout gets its own module holding the
+// legacy definitions it reads, so `state.rs` only ever describes the current layout.
+
+pub mod v0_2_0 {
+    use std::collections::BTreeMap;
+
+    use cosmwasm_std::{attr, Addr, Attribute, Coin, CosmosMsg, DepsMut, Env, Order, StdResult, Uint128};
+    use cw_storage_plus::{Item, Map};
+
+    use crate::contract::{create_msg_burn_shares, create_msgs_mint_shares, shares_for_deposit};
+    use crate::state::{EtfCache, MintCache, SwapCache, EtfNameCache, PauseState, BALANCE, ETF_DEPOSITS, INITIAL_SWAP, LEDGER, MANAGER_CONTRACT, OWNER, PAUSED, SHARES, TOTAL_SHARES};
+
+    // 0.1.0 layout
+    //sender
+    pub const INITIAL_SWAP_V0_1_0: Map<&str, Coin> = Map::new("initial_swap");
+    pub const ETF_CACHE_V0_1_0: Item<EtfCache> = Item::new("cache");
+    pub const MINT_CACHE_V0_1_0: Item<MintCache> = Item::new("mint_cache");
+    pub const INITIAL_DEPOSIT_CACHE_V0_1_0: Item<Coin> = Item::new("initial_deposit_cache");
+    pub const REVERT_SWAP_CACHE_V0_1_0: Item<SwapCache> = Item::new("revert_swap_cache");
+    pub const ETF_NAME_CACHE_V0_1_0: Item<EtfNameCache> = Item::new("etf_name");
+
+    /// - `INITIAL_SWAP` was keyed by sender only and got overwritten by swaps into other ETFs.
+    ///   Entries are moved under (sender, etf) when the sender holds exactly one ETF,
+    ///   otherwise it is unknown which ETF they belong to and they are dropped.
+    /// - `BALANCE` entries zeroed by a redemption are removed, as redemption does now.
+    /// - `ETF_DEPOSITS` totals, checked against etf limits, are built from the remaining `BALANCE` entries.
+    /// - `SHARES` and `TOTAL_SHARES` didn't exist. Every position gets the shares its `BALANCE` is worth in uosmo,
+    ///   as a deposit gets now. 0.1.0 minted one cw20 token per deposited unit to the manager, the difference
+    ///   is minted or burnt so that redemptions can burn the shares of a position.
+    /// - Reply caches are only meaningful inside the transaction that wrote them.
+    /// - 0.1.0 had no owner and could not be paused.
+    pub fn migrate(deps: DepsMut, env: &Env, owner: &Addr) -> StdResult<(Vec<Attribute>, Vec<CosmosMsg>)> {
+        OWNER.save(deps.storage, owner)?;
+        PAUSED.save(deps.storage, &PauseState::default())?;
+
+        let initial_swaps = INITIAL_SWAP_V0_1_0
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut initial_swaps_moved = 0u64;
+        for (sender, initial_swap) in initial_swaps.iter() {
+            let etf_names = LEDGER
+                .prefix(sender)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<String>>>()?;
+            if let [etf_name] = etf_names.as_slice() {
+                INITIAL_SWAP.save(deps.storage, (sender, etf_name), initial_swap)?;
+                initial_swaps_moved += 1;
+            }
+            INITIAL_SWAP_V0_1_0.remove(deps.storage, sender);
+        }
+
+        let redeemed = BALANCE
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|item| item.as_ref().map_or(true, |(_, balance)| balance.amount.is_zero()))
+            .map(|item| item.map(|(key, _)| key))
+            .collect::<StdResult<Vec<(String, String)>>>()?;
+        for (sender, etf_name) in redeemed.iter() {
+            BALANCE.remove(deps.storage, (sender, etf_name));
+        }
+
+        let balances = BALANCE
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        //etf - (cw20 tokens minted by 0.1.0, shares)
+        let mut minted: BTreeMap<String, (Uint128, Uint128)> = BTreeMap::new();
+        for ((sender, etf_name), balance) in balances.iter() {
+            ETF_DEPOSITS.update(deps.storage, (etf_name, &balance.denom), |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_add(balance.amount)?)
+            })?;
+            let shares = shares_for_deposit(deps.as_ref(), env, balance)?;
+            SHARES.save(deps.storage, (etf_name, sender), &shares, env.block.height)?;
+            TOTAL_SHARES.update(deps.storage, etf_name, env.block.height, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_add(shares)?)
+            })?;
+            let (tokens, etf_shares) = minted.entry(etf_name.to_owned()).or_default();
+            *tokens = tokens.checked_add(balance.amount)?;
+            *etf_shares = etf_shares.checked_add(shares)?;
+        }
+
+        let mut messages: Vec<CosmosMsg> = vec![];
+        for (etf_name, (tokens, shares)) in minted.iter() {
+            if shares > tokens {
+                let manager_addr = MANAGER_CONTRACT.load(deps.storage)?;
+                messages.extend(create_msgs_mint_shares(deps.as_ref(), etf_name, manager_addr.as_str(), shares - tokens)?);
+            } else if tokens > shares {
+                messages.push(create_msg_burn_shares(deps.as_ref(), etf_name, tokens - shares)?);
+            }
+        }
+
+        ETF_CACHE_V0_1_0.remove(deps.storage);
+        MINT_CACHE_V0_1_0.remove(deps.storage);
+        INITIAL_DEPOSIT_CACHE_V0_1_0.remove(deps.storage);
+        REVERT_SWAP_CACHE_V0_1_0.remove(deps.storage);
+        ETF_NAME_CACHE_V0_1_0.remove(deps.storage);
+
+        Ok((vec![
+            attr("owner", owner),
+            attr("initial_swaps_moved", initial_swaps_moved.to_string()),
+            attr("initial_swaps_dropped", (initial_swaps.len() as u64 - initial_swaps_moved).to_string()),
+            attr("redeemed_balances_removed", redeemed.len().to_string()),
+            attr("positions_with_shares", balances.len().to_string()),
+        ], messages))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{coin, to_binary, Addr, Decimal, SubMsg, Uint128, WasmMsg};
+    use etf_interface::share_token;
+    use cw2::{get_contract_version, set_contract_version};
+
+    use super::v0_2_0::*;
+    use crate::contract::migrate;
+    use crate::contract::tests::mock_dependencies_with_prices;
+    use crate::msg::{EtfSwapRoutes, MigrateMsg};
+    use crate::state::{
+        EtfCache, PauseState, BALANCE, ETF_DEPOSITS, INITIAL_SWAP, LEDGER, MANAGER_CONTRACT, MINT_CONTRACTS, OWNER, PAUSED, SHARES,
+        TOTAL_SHARES,
+    };
+    use crate::ContractError;
+
+    const CONTRACT_NAME: &str = "crates.io:counter_manager";
+
+    #[test]
+    fn migrate_v0_1_0_state() {
+        let mut deps = mock_dependencies_with_prices(vec![("uosmo", Decimal::one()), ("usdc", Decimal::from_ratio(25u128, 1u128))]);
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        MANAGER_CONTRACT.save(deps.as_mut().storage, &Addr::unchecked("manager")).unwrap();
+        MINT_CONTRACTS.save(deps.as_mut().storage, "first", &"cw20".to_string()).unwrap();
+
+        // alice holds a single etf, bob holds two and has redeemed a third one
+        LEDGER.save(deps.as_mut().storage, ("alice", "first"), &vec![coin(10, "uion")]).unwrap();
+        BALANCE.save(deps.as_mut().storage, ("alice", "first"), &coin(11, "uosmo")).unwrap();
+        INITIAL_SWAP_V0_1_0.save(deps.as_mut().storage, "alice", &coin(9, "uatom")).unwrap();
+        LEDGER.save(deps.as_mut().storage, ("bob", "first"), &vec![coin(20, "uion")]).unwrap();
+        LEDGER.save(deps.as_mut().storage, ("bob", "second"), &vec![coin(30, "uiou")]).unwrap();
+        BALANCE.save(deps.as_mut().storage, ("bob", "first"), &coin(21, "uosmo")).unwrap();
+        BALANCE.save(deps.as_mut().storage, ("bob", "second"), &coin(31, "uosmo")).unwrap();
+        BALANCE.save(deps.as_mut().storage, ("bob", "third"), &coin(0, "uosmo")).unwrap();
+        INITIAL_SWAP_V0_1_0.save(deps.as_mut().storage, "bob", &coin(19, "uatom")).unwrap();
+        // carol deposited usdc, she got a cw20 token per usdc
+        LEDGER.save(deps.as_mut().storage, ("carol", "first"), &vec![coin(40, "uion")]).unwrap();
+        BALANCE.save(deps.as_mut().storage, ("carol", "first"), &coin(2, "usdc")).unwrap();
+        ETF_CACHE_V0_1_0.save(deps.as_mut().storage, &EtfCache {
+            sender: "bob".to_string(),
+            etf_swap_routes: EtfSwapRoutes { name: "second".to_string(), routes: vec![], ratios: vec![] },
+            shares: Uint128::zero(),
+        }).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::V0_2_0 { owner: "admin".to_string() }).unwrap();
+        assert!(res.attributes.contains(&cosmwasm_std::attr("initial_swaps_moved", "1")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("initial_swaps_dropped", "1")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("redeemed_balances_removed", "1")));
+        assert!(res.attributes.contains(&cosmwasm_std::attr("positions_with_shares", "4")));
+        // 32 cw20 tokens for uosmo and 2 for usdc were minted, 82 shares back the positions of the first etf
+        let mint = WasmMsg::Execute {
+            contract_addr: "cw20".to_string(),
+            funds: vec![],
+            msg: to_binary(&share_token::ExecuteMsg::Mint { recipient: "manager".to_string(), amount: Uint128::new(48) }).unwrap(),
+        };
+        assert_eq!(res.messages, vec![SubMsg::new(mint)]);
+
+        let storage = deps.as_ref().storage;
+        assert_eq!(INITIAL_SWAP.load(storage, ("alice", "first")).unwrap(), coin(9, "uatom"));
+        assert!(!INITIAL_SWAP.has(storage, ("bob", "first")));
+        assert!(!INITIAL_SWAP_V0_1_0.has(storage, "alice"));
+        assert!(!INITIAL_SWAP_V0_1_0.has(storage, "bob"));
+        assert!(!BALANCE.has(storage, ("bob", "third")));
+        assert_eq!(BALANCE.load(storage, ("bob", "second")).unwrap().amount, Uint128::new(31));
+        assert_eq!(LEDGER.load(storage, ("bob", "second")).unwrap(), vec![coin(30, "uiou")]);
+        assert_eq!(ETF_DEPOSITS.load(storage, ("first", "uosmo")).unwrap(), Uint128::new(32));
+        assert_eq!(ETF_DEPOSITS.load(storage, ("second", "uosmo")).unwrap(), Uint128::new(31));
+        assert!(!ETF_DEPOSITS.has(storage, ("third", "uosmo")));
+        assert_eq!(ETF_DEPOSITS.load(storage, ("first", "usdc")).unwrap(), Uint128::new(2));
+        assert_eq!(SHARES.load(storage, ("first", "alice")).unwrap(), Uint128::new(11));
+        assert_eq!(SHARES.load(storage, ("first", "carol")).unwrap(), Uint128::new(50));
+        assert_eq!(TOTAL_SHARES.load(storage, "first").unwrap(), Uint128::new(82));
+        assert_eq!(TOTAL_SHARES.load(storage, "second").unwrap(), Uint128::new(31));
+        assert!(SHARES.may_load(storage, ("third", "bob")).unwrap().is_none());
+        assert!(ETF_CACHE_V0_1_0.may_load(storage).unwrap().is_none());
+        assert_eq!(OWNER.load(storage).unwrap(), "admin");
+        assert_eq!(PAUSED.load(storage).unwrap(), PauseState::default());
+        assert_eq!(get_contract_version(storage).unwrap().version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn migrate_rejects_same_or_newer_version() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.2.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::V0_2_0 { owner: "admin".to_string() }).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.0.1").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::V0_2_0 { owner: "admin".to_string() }).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+    }
+}
@@ -1,10 +1,10 @@
-use cosmwasm_std::{Coin, Addr};
+use cosmwasm_std::{Coin, Addr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::{Map, Item};
+use cw_storage_plus::{Map, Item, SnapshotMap, Strategy};
 
-use crate::msg::EtfSwapRoutes;
+use crate::msg::{EtfSwapRoutes, DowntimeBreaker, EtfLimits, BatchConfig, BatchClaim, DcaPlan, NavOrder, Donation, GovConfig, Proposal, Ballot, IndexMethodology, IndexWeights, IbcRefund, OutflowLimit, Outflow, QueuedRedemption, WindDown};
 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -21,7 +21,9 @@ pub struct Ledger {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct EtfCache {
     pub sender: String,
-    pub etf_swap_routes: EtfSwapRoutes
+    pub etf_swap_routes: EtfSwapRoutes,
+    #[serde(default)]
+    pub shares: Uint128,        // minted once the swaps are through
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -41,21 +43,142 @@ pub struct EtfNameCache {
     pub etf_name: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExactOutCache {
+    pub sender: String,
+    pub etf_name: String,
+    pub max_deposit: Coin,
+    pub spent: Uint128,         // deposit used by the swaps replied so far
+    pub initial_swap: Coin,     // intermediate token bought with it
+    pub swaps_left: u64,
+    pub shares: Uint128,        // minted once all swaps are done
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DcaPurchaseCache {
+    pub owner: String,
+    pub plan_id: u64,
+    pub deposit: Coin,          // period's amount without the keeper bounty
+    pub bounty: Coin,
+    pub keeper: String,         // paid the bounty if the purchase goes through
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchCache {
+    pub etf_name: String,
+    pub config: BatchConfig,
+    pub deposits_total: Uint128,    // queued deposits, in config's deposit denom
+    pub redemptions: Vec<(String, Uint128)>,  // redeeming sender with the value of their position
+    pub buyers_tokens: Vec<Coin>,   // etf tokens split between depositors
+    pub intermediate: Uint128,      // intermediate token collected from sold etf tokens
+    pub proceeds: Uint128,          // deposit denom split between redeemers
+    pub swaps_left: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseState {
+    pub buys: bool,
+    pub redemptions: bool
+}
+
+pub const OWNER: Item<Addr> = Item::new("owner");
+pub const PAUSED: Item<PauseState> = Item::new("paused");
+pub const DOWNTIME_BREAKER: Item<DowntimeBreaker> = Item::new("downtime_breaker");
+
 // contracts
 pub const MANAGER_CONTRACT: Item<Addr> = Item::new("manager_contract");
 pub const SWAP_CONTRACT: Item<Addr> = Item::new("swap_contract");
 pub const MINT_CONTRACTS: Map<&str, String> = Map::new("mint_contracts");
+// type, denom - etfs issuing native tokenfactory shares instead of cw20 ones
+pub const NATIVE_DENOMS: Map<&str, String> = Map::new("native_denoms");
 
 //sender, type
 pub const LEDGER: Map<(&str, &str), Vec<Coin>> = Map::new("ledger");
 //sender, type
 pub const BALANCE: Map<(&str, &str), Coin> = Map::new("balance");
-pub const INITIAL_SWAP: Map<&str, Coin> = Map::new("initial_swap");
+//sender, type
+pub const INITIAL_SWAP: Map<(&str, &str), Coin> = Map::new("initial_swaps");
 
 pub const ETF_POOLS: Map<&str, u64> = Map::new("etf_pools");
+// route pool id - pair contract swapping in place of the gamm pool, ids outside gamm's range
+pub const PAIR_ROUTES: Map<u64, Addr> = Map::new("pair_routes");
+// refund transfers waiting for their sequence numbers, in dispatch order
+pub const IBC_REFUND_CACHE: Item<Vec<IbcRefund>> = Item::new("ibc_refund_cache");
+//channel, sequence - refund transfers waiting for their ack or timeout
+pub const IBC_REFUNDS: Map<(&str, u64), IbcRefund> = Map::new("ibc_refunds");
+
+pub const ETF_LIMITS: Map<&str, EtfLimits> = Map::new("etf_limits");
+//type, denom - sum of BALANCE entries of the etf
+pub const ETF_DEPOSITS: Map<(&str, &str), Uint128> = Map::new("etf_deposits");
+
+pub const BATCH_CONFIGS: Map<&str, BatchConfig> = Map::new("batch_configs");
+//type, sender
+pub const BATCH_DEPOSITS: Map<(&str, &str), Uint128> = Map::new("batch_deposits");
+//type, sender - tokens of queued positions, no longer in LEDGER
+pub const BATCH_REDEMPTIONS: Map<(&str, &str), Vec<Coin>> = Map::new("batch_redemptions");
+//sender, type
+pub const BATCH_CLAIMS: Map<(&str, &str), BatchClaim> = Map::new("batch_claims");
+
+//owner, plan id
+pub const DCA_PLANS: Map<(&str, u64), DcaPlan> = Map::new("dca_plans");
+pub const DCA_PLAN_COUNT: Item<u64> = Item::new("dca_plan_count");
+//next execution in nanos, plan id - owner of active plans, so due plans are found without going through all of them
+pub const DCA_SCHEDULE: Map<(u64, u64), String> = Map::new("dca_schedule");
+// purchases dispatched by `ExecuteDue`, in dispatch order
+pub const DCA_PURCHASE_CACHE: Item<Vec<DcaPurchaseCache>> = Item::new("dca_purchase_cache");
+
+//type
+pub const OUTFLOW_LIMITS: Map<&str, OutflowLimit> = Map::new("outflow_limits");
+//type - window redemptions are currently counted in
+pub const OUTFLOWS: Map<&str, Outflow> = Map::new("outflows");
+//type, id - redemptions over the limit, redeemed in order of their id
+pub const REDEMPTION_QUEUE: Map<(&str, u64), QueuedRedemption> = Map::new("redemption_queue");
+pub const REDEMPTION_QUEUE_COUNT: Item<u64> = Item::new("redemption_queue_count");
+//sender, type - id of the position's queued redemption
+pub const QUEUED_POSITIONS: Map<(&str, &str), u64> = Map::new("queued_positions");
+
+//type
+pub const WIND_DOWNS: Map<&str, WindDown> = Map::new("wind_downs");
+//type, holder - tokens claimed from the wound-down basket
+pub const WIND_DOWN_CLAIMS: Map<(&str, &str), Vec<Coin>> = Map::new("wind_down_claims");
+
+//owner, order id
+pub const NAV_ORDERS: Map<(&str, u64), NavOrder> = Map::new("nav_orders");
+pub const NAV_ORDER_COUNT: Item<u64> = Item::new("nav_order_count");
+pub const NAV_TWAP_WINDOW: Item<u64> = Item::new("nav_twap_window");
+
+pub const DONATION_BENEFICIARIES: Map<&str, Vec<String>> = Map::new("donation_beneficiaries");
+//holder, type
+pub const DONATIONS: Map<(&str, &str), Donation> = Map::new("donations");
+//beneficiary, denom
+pub const DONATED: Map<(&str, &str), Uint128> = Map::new("donated");
+
+//type, holder - shares (deposit amount of the position) kept per height for voting
+pub const SHARES: SnapshotMap<(&str, &str), Uint128> = SnapshotMap::new(
+    "shares",
+    "shares__checkpoints",
+    "shares__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SHARES: SnapshotMap<&str, Uint128> = SnapshotMap::new(
+    "total_shares",
+    "total_shares__checkpoints",
+    "total_shares__changelog",
+    Strategy::EveryBlock,
+);
+pub const GOV_CONFIG: Item<GovConfig> = Item::new("gov_config");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+//proposal id, voter
+pub const VOTES: Map<(u64, &str), Ballot> = Map::new("votes");
+
+pub const INDEX_METHODOLOGIES: Map<&str, IndexMethodology> = Map::new("index_methodologies");
+pub const INDEX_WEIGHTS: Map<&str, IndexWeights> = Map::new("index_weights");
 
 pub const ETF_CACHE: Item<EtfCache> = Item::new("cache");
 pub const MINT_CACHE: Item<MintCache> = Item::new("mint_cache");
 pub const INITIAL_DEPOSIT_CACHE: Item<Coin> = Item::new("initial_deposit_cache");
 pub const REVERT_SWAP_CACHE: Item<SwapCache> = Item::new("revert_swap_cache");
 pub const ETF_NAME_CACHE: Item<EtfNameCache> = Item::new("etf_name");
+pub const EXACT_OUT_CACHE: Item<ExactOutCache> = Item::new("exact_out_cache");
+pub const BATCH_CACHE: Item<BatchCache> = Item::new("batch_cache");
@@ -0,0 +1,276 @@
+use cosmwasm_std::{
+    coin, to_binary, Addr, Api, Coin, CosmosMsg, Decimal, QuerierWrapper, StdError, StdResult,
+    Uint128, WasmMsg,
+};
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    GammQuerier, MsgSwapExactAmountIn, MsgSwapExactAmountOut, SwapAmountInRoute, SwapAmountOutRoute,
+};
+
+use crate::pair::{
+    Asset, PairExecuteMsg, PairQueryMsg, PoolResponse, ReverseSimulationResponse,
+    SimulationResponse,
+};
+use crate::pool::{PoolInfo, PoolType};
+
+/// Swaps and prices within a single pool, whichever DEX it lives on
+pub trait DexAdapter {
+    /// Amount of `ask_denom` a swap of `offer` would currently return
+    fn quote(&self, querier: &QuerierWrapper, offer: &Coin, ask_denom: &str) -> StdResult<Uint128>;
+
+    fn swap_exact_in(
+        &self,
+        sender: &Addr,
+        offer: Coin,
+        ask_denom: &str,
+        min_out: Uint128,
+    ) -> StdResult<CosmosMsg>;
+
+    fn swap_exact_out(
+        &self,
+        querier: &QuerierWrapper,
+        sender: &Addr,
+        ask: Coin,
+        offer_denom: &str,
+        max_in: Uint128,
+    ) -> StdResult<CosmosMsg>;
+
+    fn pool_info(&self, querier: &QuerierWrapper) -> StdResult<PoolInfo>;
+
+    /// Spot price of `base_denom` in `quote_denom`
+    fn price(
+        &self,
+        querier: &QuerierWrapper,
+        base_denom: &str,
+        quote_denom: &str,
+    ) -> StdResult<Decimal>;
+}
+
+pub use etf_interface::swap::Dex;
+
+/// Adapter of the DEX a `Dex` points to
+pub trait ToAdapter {
+    fn adapter(&self, api: &dyn Api) -> StdResult<Box<dyn DexAdapter>>;
+}
+
+impl ToAdapter for Dex {
+    fn adapter(&self, api: &dyn Api) -> StdResult<Box<dyn DexAdapter>> {
+        Ok(match self {
+            Dex::Osmosis { pool_id } => Box::new(OsmosisGamm { pool_id: *pool_id }),
+            Dex::Pair { contract } => Box::new(AmmPair {
+                contract: api.addr_validate(contract)?,
+            }),
+        })
+    }
+}
+
+pub struct OsmosisGamm {
+    pub pool_id: u64,
+}
+
+impl DexAdapter for OsmosisGamm {
+    fn quote(&self, querier: &QuerierWrapper, offer: &Coin, ask_denom: &str) -> StdResult<Uint128> {
+        let token_out_amount = GammQuerier::new(querier)
+            .estimate_swap_exact_amount_in(
+                String::new(),
+                self.pool_id,
+                offer.to_string(),
+                vec![SwapAmountInRoute {
+                    pool_id: self.pool_id,
+                    token_out_denom: ask_denom.to_string(),
+                }],
+            )?
+            .token_out_amount;
+        token_out_amount.parse()
+    }
+
+    fn swap_exact_in(
+        &self,
+        sender: &Addr,
+        offer: Coin,
+        ask_denom: &str,
+        min_out: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        Ok(MsgSwapExactAmountIn {
+            sender: sender.to_string(),
+            routes: vec![SwapAmountInRoute {
+                pool_id: self.pool_id,
+                token_out_denom: ask_denom.to_string(),
+            }],
+            token_in: Some(offer.into()),
+            token_out_min_amount: min_out.to_string(),
+        }
+        .into())
+    }
+
+    fn swap_exact_out(
+        &self,
+        _querier: &QuerierWrapper,
+        sender: &Addr,
+        ask: Coin,
+        offer_denom: &str,
+        max_in: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        Ok(MsgSwapExactAmountOut {
+            sender: sender.to_string(),
+            routes: vec![SwapAmountOutRoute {
+                pool_id: self.pool_id,
+                token_in_denom: offer_denom.to_string(),
+            }],
+            token_in_max_amount: max_in.to_string(),
+            token_out: Some(ask.into()),
+        }
+        .into())
+    }
+
+    fn pool_info(&self, querier: &QuerierWrapper) -> StdResult<PoolInfo> {
+        PoolInfo::query(querier, self.pool_id)
+    }
+
+    fn price(
+        &self,
+        querier: &QuerierWrapper,
+        base_denom: &str,
+        quote_denom: &str,
+    ) -> StdResult<Decimal> {
+        self.pool_info(querier)?
+            .spot_price(querier, base_denom, quote_denom)
+    }
+}
+
+pub struct AmmPair {
+    pub contract: Addr,
+}
+
+impl AmmPair {
+    fn pool(&self, querier: &QuerierWrapper) -> StdResult<PoolResponse> {
+        querier.query_wasm_smart(&self.contract, &PairQueryMsg::Pool {})
+    }
+}
+
+impl DexAdapter for AmmPair {
+    fn quote(
+        &self,
+        querier: &QuerierWrapper,
+        offer: &Coin,
+        _ask_denom: &str,
+    ) -> StdResult<Uint128> {
+        let res: SimulationResponse = querier.query_wasm_smart(
+            &self.contract,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset::native(&offer.denom, offer.amount),
+            },
+        )?;
+        Ok(res.return_amount)
+    }
+
+    // pairs have no minimum output, a belief price with no spread allowed does the same
+    fn swap_exact_in(
+        &self,
+        _sender: &Addr,
+        offer: Coin,
+        _ask_denom: &str,
+        min_out: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        let belief_price = if min_out.is_zero() {
+            None
+        } else {
+            Some(Decimal::from_ratio(offer.amount, min_out))
+        };
+        Ok(WasmMsg::Execute {
+            contract_addr: self.contract.to_string(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset::native(&offer.denom, offer.amount),
+                belief_price,
+                max_spread: belief_price.map(|_| Decimal::zero()),
+                to: None,
+            })?,
+            funds: vec![offer],
+        }
+        .into())
+    }
+
+    // pairs only swap exact amounts in, so the offer is sized with a reverse simulation first
+    fn swap_exact_out(
+        &self,
+        querier: &QuerierWrapper,
+        sender: &Addr,
+        ask: Coin,
+        offer_denom: &str,
+        max_in: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        let res: ReverseSimulationResponse = querier.query_wasm_smart(
+            &self.contract,
+            &PairQueryMsg::ReverseSimulation {
+                ask_asset: Asset::native(&ask.denom, ask.amount),
+            },
+        )?;
+        if res.offer_amount > max_in {
+            return Err(StdError::generic_err(format!(
+                "Swap requires {}{}, above the maximum of {}",
+                res.offer_amount, offer_denom, max_in
+            )));
+        }
+        self.swap_exact_in(
+            sender,
+            coin(res.offer_amount.u128(), offer_denom),
+            &ask.denom,
+            ask.amount,
+        )
+    }
+
+    fn pool_info(&self, querier: &QuerierWrapper) -> StdResult<PoolInfo> {
+        let pool = self.pool(querier)?;
+        let assets = pool
+            .assets
+            .iter()
+            .map(|asset| {
+                let denom = asset.native_denom().ok_or_else(|| {
+                    StdError::generic_err("cw20 assets of pairs aren't supported")
+                })?;
+                Ok(coin(asset.amount.u128(), denom))
+            })
+            .collect::<StdResult<Vec<Coin>>>()?;
+        Ok(PoolInfo {
+            id: 0,
+            pool_type: PoolType::Pair,
+            assets,
+            weights: vec![],
+            scaling_factors: vec![],
+            // not part of the pair interface
+            swap_fee: Decimal::zero(),
+            exit_fee: Decimal::zero(),
+            // lp shares of pairs are cw20 tokens, denominated by the pair itself
+            total_shares: coin(pool.total_share.u128(), self.contract.as_str()),
+        })
+    }
+
+    // constant product pairs, so it's the ratio of reserves
+    fn price(
+        &self,
+        querier: &QuerierWrapper,
+        base_denom: &str,
+        quote_denom: &str,
+    ) -> StdResult<Decimal> {
+        let pool = self.pool_info(querier)?;
+        let reserve = |denom: &str| {
+            pool.assets
+                .iter()
+                .find(|c| c.denom == denom)
+                .map(|c| c.amount)
+                .ok_or_else(|| {
+                    StdError::generic_err(format!(
+                        "Token {} not found in pair {}",
+                        denom, self.contract
+                    ))
+                })
+        };
+        let base_reserve = reserve(base_denom)?;
+        if base_reserve.is_zero() {
+            return Err(StdError::generic_err(format!(
+                "Pair {} has no {}",
+                self.contract, base_denom
+            )));
+        }
+        Ok(Decimal::from_ratio(reserve(quote_denom)?, base_reserve))
+    }
+}
@@ -4,9 +4,11 @@ use std::fmt::Debug;
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_binary, to_vec, Binary, ContractResult, Deps, DepsMut, Empty, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, SystemResult, CosmosMsg, BankMsg, Coin as CoinStd,
+    Response, StdError, StdResult, SystemResult, CosmosMsg, BankMsg, Coin as CoinStd, SubMsg,
+    Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version, ContractVersion};
+use etf_interface::events::{TokenSwapped, SHARES_OUT, TOKENS_OUT};
 use osmosis_std::shim::Timestamp;
 use osmosis_std::types::cosmos::base::v1beta1::Coin;
 use osmosis_std::types::osmosis::epochs::v1beta1::{
@@ -15,18 +17,30 @@ use osmosis_std::types::osmosis::epochs::v1beta1::{
 use osmosis_std::types::osmosis::gamm::v1beta1::{
     QueryNumPoolsRequest, QueryNumPoolsResponse, QueryPoolParamsRequest, QueryPoolParamsResponse,
     QueryPoolRequest, QueryPoolResponse, SwapAmountInRoute, MsgSwapExactAmountIn,
+    SwapAmountOutRoute, MsgSwapExactAmountOut, MsgJoinSwapExternAmountIn, MsgJoinSwapExternAmountInResponse,
+    MsgExitSwapShareAmountIn, MsgExitSwapShareAmountInResponse,
 };
 use osmosis_std::types::osmosis::twap::v1beta1::{ArithmeticTwapToNowResponse, TwapQuerier, ArithmeticTwapResponse};
+use semver::Version;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+use crate::adapter::{Dex, ToAdapter};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMapResponse, QueryMsg};
-use crate::state::{DEBUG, MAP, OWNER};
+use crate::msg::{
+    DexPriceResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMapResponse, QueryMsg,
+    QuoteResponse,
+};
+use crate::pool::PoolInfo;
+use crate::state::{DEBUG, MAP, OWNER, POOL_ACTION_DENOM};
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:osmosis-std-cosmwasm-test";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const JOIN_POOL_REPLY_ID: u64 = 1;
+const EXIT_POOL_REPLY_ID: u64 = 2;
+const PAIR_SWAP_REPLY_ID: u64 = 3;
+
 /// Handling contract instantiation
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -51,15 +65,36 @@ pub fn instantiate(
 /// To make a contract migratable, you need
 /// - this entry_point implemented
 /// - only contract admin can migrate, so admin has to be set at contract initiation time
-/// Handling contract execution
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
     match msg {
-        // Find matched incoming message variant and execute them with your custom logic.
-        //
-        // With `Response` type, it is possible to dispatch message to invoke external logic.
-        // See: https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#dispatching-messages
+        MigrateMsg::V0_2_0 { debug } => {
+            ensure_older_version(&stored, "0.2.0")?;
+            if let Some(debug) = debug {
+                DEBUG.save(deps.storage, &debug)?;
+            }
+        }
     }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("previous_version", stored.version)
+        .add_attribute("new_version", CONTRACT_VERSION))
+}
+
+fn ensure_older_version(stored: &ContractVersion, target_version: &str) -> Result<(), ContractError> {
+    let stored_version: Version = stored.version.parse()?;
+    let target: Version = target_version.parse()?;
+    if stored.contract != CONTRACT_NAME || stored_version >= target {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract.to_owned(),
+            previous_version: stored.version.to_owned(),
+            target_version: target_version.to_string(),
+        });
+    }
+    Ok(())
 }
 
 /// Handling contract execution
@@ -76,10 +111,38 @@ pub fn execute(
             token_in, 
             token_out_min_amount,
           } => execute_swap_exact_amount_in(env, routes, token_in, token_out_min_amount),
+        ExecuteMsg::ExecuteSwapExactAmountOut {
+            routes,
+            token_out,
+            token_in_max_amount,
+          } => execute_swap_exact_amount_out(deps, env, info, routes, token_out, token_in_max_amount),
+        ExecuteMsg::ExecuteJoinSwapExternAmountIn {
+            pool_id,
+            token_in,
+            share_out_min_amount,
+          } => execute_join_swap_extern_amount_in(deps, env, info, pool_id, token_in, share_out_min_amount),
+        ExecuteMsg::ExecuteExitSwapShareAmountIn {
+            pool_id,
+            token_out_denom,
+            share_in_amount,
+            token_out_min_amount,
+          } => execute_exit_swap_share_amount_in(deps, env, info, pool_id, token_out_denom, share_in_amount, token_out_min_amount),
         ExecuteMsg::SendTokensBack {
             tokens,
             recipient
-        } => execute_send_tokens_back(deps, env, info, tokens, recipient)
+        } => execute_send_tokens_back(deps, env, info, tokens, recipient),
+        ExecuteMsg::ExecuteSwap {
+            dex,
+            token_in,
+            token_out_denom,
+            token_out_min_amount,
+        } => execute_swap(deps, env, info, dex, token_in, token_out_denom, token_out_min_amount),
+        ExecuteMsg::ExecuteSwapExactOut {
+            dex,
+            token_out,
+            token_in_denom,
+            token_in_max_amount,
+        } => execute_swap_exact_out(deps, env, info, dex, token_out, token_in_denom, token_in_max_amount),
     }
 }
 
@@ -99,6 +162,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::QueryPoolParams { pool_id } => {
             query_and_debug::<QueryPoolParamsResponse>(&deps, QueryPoolParamsRequest { pool_id })
         }
+        QueryMsg::QueryPoolInfo { pool_id } => to_binary(&PoolInfo::query(&deps.querier, pool_id)?),
         QueryMsg::QueryArithmeticTwap {pool_id, base_asset, quote_asset, start_time, end_time} 
         => query_arithmetic_twap (
             deps,
@@ -112,6 +176,13 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::QueryMap { key } => to_binary(&QueryMapResponse {
             value: MAP.load(deps.storage, key)?,
         }),
+        QueryMsg::Quote { dex, token_in, token_out_denom } => to_binary(&QuoteResponse {
+            amount: dex.adapter(deps.api)?.quote(&deps.querier, &token_in, &token_out_denom)?,
+        }),
+        QueryMsg::DexPoolInfo { dex } => to_binary(&dex.adapter(deps.api)?.pool_info(&deps.querier)?),
+        QueryMsg::DexPrice { dex, base_denom, quote_denom } => to_binary(&DexPriceResponse {
+            price: dex.adapter(deps.api)?.price(&deps.querier, &base_denom, &quote_denom)?,
+        }),
     }
 }
 
@@ -190,11 +261,25 @@ where
 /// Handling submessage reply.
 /// For more info on submessage and reply, see https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#submessages
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(_deps: DepsMut, _env: Env, _msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     // With `Response` type, it is still possible to dispatch message to invoke external logic.
     // See: https://github.com/CosmWasm/cosmwasm/blob/main/SEMANTICS.md#dispatching-messages
-
-    todo!()
+    match msg.id {
+        JOIN_POOL_REPLY_ID => {
+            let denom = POOL_ACTION_DENOM.load(deps.storage)?;
+            POOL_ACTION_DENOM.remove(deps.storage);
+            let res: MsgJoinSwapExternAmountInResponse = msg.result.try_into()?;
+            Ok(Response::new().add_attribute(SHARES_OUT, format!("{}{}", res.share_out_amount, denom)))
+        }
+        EXIT_POOL_REPLY_ID => {
+            let denom = POOL_ACTION_DENOM.load(deps.storage)?;
+            POOL_ACTION_DENOM.remove(deps.storage);
+            let res: MsgExitSwapShareAmountInResponse = msg.result.try_into()?;
+            Ok(Response::new().add_attribute(TOKENS_OUT, format!("{}{}", res.token_out_amount, denom)))
+        }
+        PAIR_SWAP_REPLY_ID => handle_pair_swap_reply(env, msg),
+        id => Err(ContractError::Std(StdError::generic_err(format!("Unknown reply id: {}", id)))),
+    }
 }
 
 
@@ -218,6 +303,83 @@ pub fn execute_swap_exact_amount_in(
         .add_attribute("method", "execute_swap_exact_amount_in"))
 }
 
+pub fn execute_swap_exact_amount_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    routes: Vec<SwapAmountOutRoute>,
+    token_out: Option<Coin>,
+    token_in_max_amount: String
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let sender = env.contract.address.into();
+    let msg_create_swap: CosmosMsg = MsgSwapExactAmountOut {
+        sender,
+        routes,
+        token_in_max_amount,
+        token_out
+    }.into();
+
+    Ok(Response::new()
+        .add_message(msg_create_swap)
+        .add_attribute("method", "execute_swap_exact_amount_out"))
+}
+
+pub fn execute_join_swap_extern_amount_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+    token_in: Option<Coin>,
+    share_out_min_amount: String
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let msg_join_pool: CosmosMsg = MsgJoinSwapExternAmountIn {
+        sender: env.contract.address.into(),
+        pool_id,
+        token_in,
+        share_out_min_amount
+    }.into();
+    POOL_ACTION_DENOM.save(deps.storage, &format!("gamm/pool/{}", pool_id))?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg_join_pool, JOIN_POOL_REPLY_ID))
+        .add_attribute("method", "execute_join_swap_extern_amount_in"))
+}
+
+pub fn execute_exit_swap_share_amount_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+    token_out_denom: String,
+    share_in_amount: String,
+    token_out_min_amount: String
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let msg_exit_pool: CosmosMsg = MsgExitSwapShareAmountIn {
+        sender: env.contract.address.into(),
+        pool_id,
+        token_out_denom: token_out_denom.to_owned(),
+        share_in_amount,
+        token_out_min_amount
+    }.into();
+    POOL_ACTION_DENOM.save(deps.storage, &token_out_denom)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg_exit_pool, EXIT_POOL_REPLY_ID))
+        .add_attribute("method", "execute_exit_swap_share_amount_in"))
+}
+
 pub fn execute_send_tokens_back(
     deps: DepsMut,
     _env: Env, 
@@ -235,4 +397,85 @@ pub fn execute_send_tokens_back(
         .add_attribute("method", "execute_send_tokens_back")
         .add_attribute("recipient", recipient)
         .add_attribute("amount_sent_back", tokens[0].amount))
-}
\ No newline at end of file
+}
+
+pub fn execute_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dex: Dex,
+    token_in: CoinStd,
+    token_out_denom: String,
+    token_out_min_amount: Uint128,
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let msg_swap = dex.adapter(deps.api)?
+        .swap_exact_in(&env.contract.address, token_in, &token_out_denom, token_out_min_amount)?;
+
+    Ok(with_swap_msg(Response::new(), &dex, msg_swap)
+        .add_attribute("method", "execute_swap"))
+}
+
+pub fn execute_swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dex: Dex,
+    token_out: CoinStd,
+    token_in_denom: String,
+    token_in_max_amount: Uint128,
+) -> Result<Response, ContractError> {
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized{})
+    }
+
+    let msg_swap = dex.adapter(deps.api)?.swap_exact_out(
+        &deps.querier,
+        &env.contract.address,
+        token_out,
+        &token_in_denom,
+        token_in_max_amount,
+    )?;
+
+    Ok(with_swap_msg(Response::new(), &dex, msg_swap)
+        .add_attribute("method", "execute_swap_exact_out"))
+}
+
+// gamm emits token_swapped itself, pair swaps get theirs from the reply
+fn with_swap_msg(res: Response, dex: &Dex, msg_swap: CosmosMsg) -> Response {
+    match dex {
+        Dex::Osmosis { .. } => res.add_message(msg_swap),
+        Dex::Pair { .. } => res.add_submessage(SubMsg::reply_on_success(msg_swap, PAIR_SWAP_REPLY_ID)),
+    }
+}
+
+fn handle_pair_swap_reply(env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let events = msg.result.into_result().map_err(StdError::generic_err)?.events;
+    let swap = events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .find(|event| event.attributes.iter().any(|a| a.key == "return_amount"))
+        .ok_or_else(|| StdError::not_found("pair swap event"))?;
+    let attribute = |key: &str| -> StdResult<String> {
+        swap.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .map(|a| a.value.to_owned())
+            .ok_or_else(|| StdError::not_found(key))
+    };
+
+    let swapped_coin = |amount: &str, denom: &str| -> StdResult<CoinStd> {
+        Ok(CoinStd { denom: attribute(denom)?, amount: attribute(amount)?.parse()? })
+    };
+
+    Ok(Response::new().add_event(TokenSwapped {
+        module: "pair".to_string(),
+        sender: env.contract.address.into_string(),
+        pool_id: attribute("_contract_address")?,
+        tokens_in: swapped_coin("offer_amount", "offer_asset")?,
+        tokens_out: swapped_coin("return_amount", "ask_asset")?,
+    }.into()))
+}
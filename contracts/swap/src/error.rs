@@ -8,10 +8,22 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Cannot migrate from {previous_contract:?} {previous_version:?} to {target_version:?}")]
+    CannotMigrate { previous_contract: String, previous_version: String, target_version: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
 
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum OsmosisError {
     #[error("{0}")]
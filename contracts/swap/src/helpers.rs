@@ -0,0 +1,243 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Coin as CoinStd, CosmosMsg, QuerierWrapper, StdResult, Uint128, WasmMsg,
+};
+use osmosis_std::shim::Timestamp;
+use osmosis_std::types::osmosis::gamm::v1beta1::{SwapAmountInRoute, SwapAmountOutRoute};
+use osmosis_std::types::osmosis::twap::v1beta1::ArithmeticTwapResponse;
+use serde::de::DeserializeOwned;
+
+use crate::adapter::Dex;
+use crate::msg::{
+    DexPriceResponse, ExecuteMsg, QueryEpochsInfoResponse, QueryMapResponse, QueryMsg,
+    QueryNumPoolsResponse, QueryPoolParamsResponse, QueryPoolResponse, QuoteResponse,
+};
+use crate::pool::PoolInfo;
+
+/// Typed queries and messages of the swap contract, for other contracts and tests.
+/// Swaps spend tokens the contract already holds, so messages carry no funds unless given
+#[cw_serde]
+pub struct SwapContract(pub Addr);
+
+impl SwapContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
+        self.call_with_funds(msg, vec![])
+    }
+
+    pub fn call_with_funds<T: Into<ExecuteMsg>>(
+        &self,
+        msg: T,
+        funds: Vec<CoinStd>,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg.into())?,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: &QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), msg)
+    }
+
+    // ----------------------------------- MESSAGES
+
+    pub fn swap_exact_amount_in(
+        &self,
+        routes: Vec<SwapAmountInRoute>,
+        token_in: CoinStd,
+        token_out_min_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteSwapExactAmountIn {
+            routes,
+            token_in: Some(token_in.into()),
+            token_out_min_amount: token_out_min_amount.to_string(),
+        })
+    }
+
+    pub fn swap_exact_amount_out(
+        &self,
+        routes: Vec<SwapAmountOutRoute>,
+        token_out: CoinStd,
+        token_in_max_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteSwapExactAmountOut {
+            routes,
+            token_out: Some(token_out.into()),
+            token_in_max_amount: token_in_max_amount.to_string(),
+        })
+    }
+
+    pub fn join_swap_extern_amount_in(
+        &self,
+        pool_id: u64,
+        token_in: CoinStd,
+        share_out_min_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteJoinSwapExternAmountIn {
+            pool_id,
+            token_in: Some(token_in.into()),
+            share_out_min_amount: share_out_min_amount.to_string(),
+        })
+    }
+
+    pub fn exit_swap_share_amount_in(
+        &self,
+        pool_id: u64,
+        token_out_denom: impl Into<String>,
+        share_in_amount: Uint128,
+        token_out_min_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteExitSwapShareAmountIn {
+            pool_id,
+            token_out_denom: token_out_denom.into(),
+            share_in_amount: share_in_amount.to_string(),
+            token_out_min_amount: token_out_min_amount.to_string(),
+        })
+    }
+
+    pub fn send_tokens_back(
+        &self,
+        tokens: Vec<CoinStd>,
+        recipient: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::SendTokensBack {
+            tokens,
+            recipient: recipient.into(),
+        })
+    }
+
+    pub fn swap(
+        &self,
+        dex: Dex,
+        token_in: CoinStd,
+        token_out_denom: impl Into<String>,
+        token_out_min_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteSwap {
+            dex,
+            token_in,
+            token_out_denom: token_out_denom.into(),
+            token_out_min_amount,
+        })
+    }
+
+    pub fn swap_exact_out(
+        &self,
+        dex: Dex,
+        token_out: CoinStd,
+        token_in_denom: impl Into<String>,
+        token_in_max_amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ExecuteSwapExactOut {
+            dex,
+            token_out,
+            token_in_denom: token_in_denom.into(),
+            token_in_max_amount,
+        })
+    }
+
+    // ----------------------------------- QUERIES
+
+    pub fn num_pools(&self, querier: &QuerierWrapper) -> StdResult<QueryNumPoolsResponse> {
+        self.query(querier, &QueryMsg::QueryNumPools {})
+    }
+
+    pub fn epochs_info(&self, querier: &QuerierWrapper) -> StdResult<QueryEpochsInfoResponse> {
+        self.query(querier, &QueryMsg::QueryEpochsInfo {})
+    }
+
+    /// raw gamm pool, `pool_info` decodes it
+    pub fn pool(&self, querier: &QuerierWrapper, pool_id: u64) -> StdResult<QueryPoolResponse> {
+        self.query(querier, &QueryMsg::QueryPool { pool_id })
+    }
+
+    pub fn pool_params(
+        &self,
+        querier: &QuerierWrapper,
+        pool_id: u64,
+    ) -> StdResult<QueryPoolParamsResponse> {
+        self.query(querier, &QueryMsg::QueryPoolParams { pool_id })
+    }
+
+    pub fn pool_info(&self, querier: &QuerierWrapper, pool_id: u64) -> StdResult<PoolInfo> {
+        self.query(querier, &QueryMsg::QueryPoolInfo { pool_id })
+    }
+
+    /// arithmetic TWAP of `base_asset` in `quote_asset`, until now if no end is given
+    pub fn twap(
+        &self,
+        querier: &QuerierWrapper,
+        pool_id: u64,
+        base_asset: impl Into<String>,
+        quote_asset: impl Into<String>,
+        start_time: Option<Timestamp>,
+        end_time: Option<Timestamp>,
+    ) -> StdResult<ArithmeticTwapResponse> {
+        self.query(
+            querier,
+            &QueryMsg::QueryArithmeticTwap {
+                pool_id,
+                base_asset: base_asset.into(),
+                quote_asset: quote_asset.into(),
+                start_time,
+                end_time,
+            },
+        )
+    }
+
+    pub fn map_value(
+        &self,
+        querier: &QuerierWrapper,
+        key: impl Into<String>,
+    ) -> StdResult<QueryMapResponse> {
+        self.query(querier, &QueryMsg::QueryMap { key: key.into() })
+    }
+
+    pub fn quote(
+        &self,
+        querier: &QuerierWrapper,
+        dex: Dex,
+        token_in: CoinStd,
+        token_out_denom: impl Into<String>,
+    ) -> StdResult<QuoteResponse> {
+        self.query(
+            querier,
+            &QueryMsg::Quote {
+                dex,
+                token_in,
+                token_out_denom: token_out_denom.into(),
+            },
+        )
+    }
+
+    pub fn dex_pool_info(&self, querier: &QuerierWrapper, dex: Dex) -> StdResult<PoolInfo> {
+        self.query(querier, &QueryMsg::DexPoolInfo { dex })
+    }
+
+    pub fn dex_price(
+        &self,
+        querier: &QuerierWrapper,
+        dex: Dex,
+        base_denom: impl Into<String>,
+        quote_denom: impl Into<String>,
+    ) -> StdResult<DexPriceResponse> {
+        self.query(
+            querier,
+            &QueryMsg::DexPrice {
+                dex,
+                base_denom: base_denom.into(),
+                quote_denom: quote_denom.into(),
+            },
+        )
+    }
+}
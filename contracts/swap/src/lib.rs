@@ -1,6 +1,10 @@
+pub mod adapter;
 pub mod contract;
 mod error;
+pub mod helpers;
 pub mod msg;
+pub mod pair;
+pub mod pool;
 pub mod state;
 mod types;
 
@@ -0,0 +1,82 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Decimal, Uint128};
+
+// Terraswap style AMM pair contract interface, as far as the swap contract uses it.
+// Pairs are expected to emit `offer_asset`, `ask_asset`, `offer_amount` and `return_amount`
+// wasm attributes on swaps, like terraswap and astroport pairs do
+
+#[cw_serde]
+pub enum AssetInfo {
+    NativeToken { denom: String },
+    Token { contract_addr: String },
+}
+
+#[cw_serde]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+impl Asset {
+    pub fn native(denom: impl Into<String>, amount: Uint128) -> Self {
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: denom.into(),
+            },
+            amount,
+        }
+    }
+
+    /// cw20 assets aren't supported by the swap contract
+    pub fn native_denom(&self) -> Option<&str> {
+        match &self.info {
+            AssetInfo::NativeToken { denom } => Some(denom),
+            AssetInfo::Token { .. } => None,
+        }
+    }
+}
+
+#[cw_serde]
+pub enum PairExecuteMsg {
+    /// offer asset has to be sent along. With `belief_price` set and `max_spread` of 0, the swap fails
+    /// unless it returns at least `offer_amount / belief_price`
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum PairQueryMsg {
+    #[returns(PoolResponse)]
+    Pool {},
+
+    #[returns(SimulationResponse)]
+    Simulation { offer_asset: Asset },
+
+    #[returns(ReverseSimulationResponse)]
+    ReverseSimulation { ask_asset: Asset },
+}
+
+#[cw_serde]
+pub struct PoolResponse {
+    pub assets: Vec<Asset>,
+    pub total_share: Uint128,
+}
+
+#[cw_serde]
+pub struct SimulationResponse {
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ReverseSimulationResponse {
+    pub offer_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+}
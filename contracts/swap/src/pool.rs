@@ -0,0 +1 @@
+pub use etf_interface::pool::*;
@@ -3,6 +3,8 @@ use cw_storage_plus::{Item, Map};
 
 pub const DEBUG: Item<bool> = Item::new("debug");
 pub const OWNER: Item<Addr> = Item::new("owner");
+/// denom received by the pending join / exit pool submessage
+pub const POOL_ACTION_DENOM: Item<String> = Item::new("pool_action_denom");
 
 /// for testing cosmwasm vm / storage-plus compatibility
 pub const MAP: Map<String, String> = Map::new("map");
@@ -0,0 +1,182 @@
+use cosmwasm_std::{coin, coins, Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{AppBuilder, Contract, ContractWrapper, Executor};
+use osmo_swap::adapter::Dex;
+use osmo_swap::helpers::SwapContract;
+use osmo_swap::msg::{ExecuteMsg, InstantiateMsg};
+use osmo_swap::pool::PoolType;
+use osmosis_multitest::OsmosisStargate;
+use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::{
+    MsgCreateBalancerPool, MsgCreateBalancerPoolResponse,
+};
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    PoolAsset, PoolParams, SwapAmountInRoute, SwapAmountOutRoute,
+};
+
+fn swap_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            osmo_swap::contract::execute,
+            osmo_swap::contract::instantiate,
+            osmo_swap::contract::query,
+        )
+        .with_reply(osmo_swap::contract::reply),
+    )
+}
+
+// gamm swaps and pool queries of the contract, against the stargate mock instead of a chain
+#[test]
+fn test_gamm_mock_swap() {
+    let owner = Addr::unchecked("owner");
+    let mut app = AppBuilder::new()
+        .with_stargate(OsmosisStargate::default())
+        .build(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &owner,
+                    vec![coin(1_010_000, "uatom"), coin(2_000_000, "uosmo")],
+                )
+                .unwrap();
+        });
+
+    // 1uatom:2uosmo, 1% swap fee
+    let res = app
+        .execute(
+            owner.clone(),
+            MsgCreateBalancerPool {
+                sender: owner.to_string(),
+                pool_params: Some(PoolParams {
+                    swap_fee: "10000000000000000".to_string(),
+                    exit_fee: "0".to_string(),
+                    smooth_weight_change_params: None,
+                }),
+                pool_assets: [coin(1_000_000, "uatom"), coin(2_000_000, "uosmo")]
+                    .into_iter()
+                    .map(|c| PoolAsset {
+                        token: Some(c.into()),
+                        weight: "1000000".to_string(),
+                    })
+                    .collect(),
+                future_pool_governor: String::new(),
+            }
+            .into(),
+        )
+        .unwrap();
+    let pool_id = MsgCreateBalancerPoolResponse::try_from(res.data.unwrap())
+        .unwrap()
+        .pool_id;
+
+    let code_id = app.store_code(swap_contract());
+    let swap = app
+        .instantiate_contract(
+            code_id,
+            owner.clone(),
+            &InstantiateMsg { debug: false },
+            &coins(10_000, "uatom"),
+            "swap",
+            None,
+        )
+        .unwrap();
+    let client = SwapContract(swap.clone());
+
+    let pool = client.pool_info(&app.wrap(), pool_id).unwrap();
+    assert_eq!(pool.pool_type, PoolType::Balancer);
+    assert_eq!(
+        pool.assets,
+        vec![coin(1_000_000, "uatom"), coin(2_000_000, "uosmo")]
+    );
+    assert_eq!(pool.weights, vec![Uint128::new(1_000_000); 2]);
+    assert_eq!(pool.swap_fee, Decimal::percent(1));
+    assert_eq!(
+        client
+            .dex_price(&app.wrap(), Dex::Osmosis { pool_id }, "uatom", "uosmo")
+            .unwrap()
+            .price,
+        Decimal::from_ratio(2u128, 1u128)
+    );
+    assert_eq!(
+        client
+            .quote(
+                &app.wrap(),
+                Dex::Osmosis { pool_id },
+                coin(10_000, "uatom"),
+                "uosmo"
+            )
+            .unwrap()
+            .amount,
+        Uint128::new(19_605)
+    );
+
+    // gamm's token_swapped comes with the contract's response, as on chain
+    let res = app
+        .execute_contract(
+            owner,
+            swap.clone(),
+            &ExecuteMsg::ExecuteSwapExactAmountIn {
+                routes: vec![SwapAmountInRoute {
+                    pool_id,
+                    token_out_denom: "uosmo".to_string(),
+                }],
+                token_in: Some(coin(10_000, "uatom").into()),
+                token_out_min_amount: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    let swapped = res.events.iter().find(|e| e.ty == "token_swapped").unwrap();
+    assert!(swapped
+        .attributes
+        .iter()
+        .any(|a| a.key == "tokens_out" && a.value == "19605uosmo"));
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uatom").unwrap().amount,
+        Uint128::zero()
+    );
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uosmo").unwrap().amount,
+        Uint128::new(19_605)
+    );
+    assert_eq!(
+        client.pool_info(&app.wrap(), pool_id).unwrap().assets,
+        vec![coin(1_010_000, "uatom"), coin(1_980_395, "uosmo")]
+    );
+}
+
+#[test]
+fn test_swap_exact_amount_out_unauthorized() {
+    let owner = Addr::unchecked("owner");
+    let mut app = AppBuilder::new()
+        .with_stargate(OsmosisStargate::default())
+        .build(|_router, _api, _storage| {});
+
+    let code_id = app.store_code(swap_contract());
+    let swap = app
+        .instantiate_contract(
+            code_id,
+            owner,
+            &InstantiateMsg { debug: false },
+            &[],
+            "swap",
+            None,
+        )
+        .unwrap();
+
+    // only the owner may spend the contract's balance
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            swap,
+            &ExecuteMsg::ExecuteSwapExactAmountOut {
+                routes: vec![SwapAmountOutRoute {
+                    pool_id: 1,
+                    token_in_denom: "uatom".to_string(),
+                }],
+                token_out: Some(coin(10_000, "uosmo").into()),
+                token_in_max_amount: "10000".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+}
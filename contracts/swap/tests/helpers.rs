@@ -83,13 +83,22 @@ pub fn setup_pools(app: &OsmosisTestApp, signer: &SigningAccount) -> Vec<u64> {
 }
 
 pub fn get_wasm_byte_code() -> Vec<u8> {
+    read_artifact("osmo_swap.wasm")
+}
+
+/// Byte code of the previous release, used to test migrations
+pub fn get_previous_wasm_byte_code() -> Vec<u8> {
+    read_artifact("osmo_swap_v0_1_0.wasm")
+}
+
+fn read_artifact(filename: &str) -> Vec<u8> {
     let manifest_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     std::fs::read(
         manifest_path
             .join("..")
             .join("..")
             .join("artifacts")
-            .join("osmo_swap.wasm"),
+            .join(filename),
     )
     .unwrap()
 }
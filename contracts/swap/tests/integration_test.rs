@@ -2,7 +2,7 @@ mod helpers;
 use core::time;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Coin, Decimal, Uint128};
 use helpers::with_env_setup;
 use osmosis_std::{
     shim::{Duration, Timestamp},
@@ -14,12 +14,14 @@ use osmosis_std::{
         },
     },
 };
+use osmo_swap::adapter::Dex;
 use osmo_swap::msg::{
-    ArithmeticTwapToNowResponse, ExecuteMsg, QueryEpochsInfoResponse,
-    QueryMapResponse, QueryMsg, QueryNumPoolsResponse, QueryPoolParamsResponse, QueryPoolResponse,
+    ArithmeticTwapToNowResponse, DexPriceResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryEpochsInfoResponse,
+    QueryMapResponse, QueryMsg, QueryNumPoolsResponse, QueryPoolParamsResponse, QueryPoolResponse, QuoteResponse,
 };
+use osmo_swap::pool::{PoolInfo, PoolType};
 use osmosis_testing::RunnerError::ExecuteError;
-use osmosis_testing::{Account, Runner};
+use osmosis_testing::{Account, Gamm, Module, OsmosisTestApp, Runner, Wasm};
 
 
 #[test]
@@ -175,6 +177,154 @@ fn test_any_balancer_pool_params_response_deser() {
     );
 }
 
+#[test]
+fn test_pool_info_query() {
+    with_env_setup(
+        |app, wasm, signer, _code_id, contract_addr| {
+            let balancer_pool_id = helpers::setup_pools(app, &signer)[0];
+            let stableswap_pool_id = Gamm::new(app)
+                .create_basic_stableswap_pool(
+                    &[Coin::new(1_000, "uusdc"), Coin::new(1_000, "uion")],
+                    &signer,
+                )
+                .unwrap()
+                .data
+                .pool_id;
+
+            let balancer: PoolInfo = wasm
+                .query(&contract_addr, &QueryMsg::QueryPoolInfo { pool_id: balancer_pool_id })
+                .unwrap();
+            assert_eq!(
+                balancer,
+                PoolInfo {
+                    id: balancer_pool_id,
+                    pool_type: PoolType::Balancer,
+                    assets: vec![Coin::new(1_000, "uion"), Coin::new(1_000, "uosmo")],
+                    weights: vec![Uint128::new(1073741824000000), Uint128::new(1073741824000000)],
+                    scaling_factors: vec![],
+                    swap_fee: Decimal::percent(1),
+                    exit_fee: Decimal::percent(1),
+                    total_shares: Coin::new(100000000000000000000, "gamm/pool/1"),
+                }
+            );
+
+            let stableswap: PoolInfo = wasm
+                .query(&contract_addr, &QueryMsg::QueryPoolInfo { pool_id: stableswap_pool_id })
+                .unwrap();
+            assert_eq!(stableswap.pool_type, PoolType::Stableswap);
+            assert_eq!(
+                stableswap.assets,
+                vec![Coin::new(1_000, "uion"), Coin::new(1_000, "uusdc")]
+            );
+            assert_eq!(stableswap.scaling_factors, vec![1, 1]);
+            assert!(stableswap.weights.is_empty());
+            assert_eq!(stableswap.swap_fee, Decimal::percent(1));
+        },
+        false,
+    );
+}
+
+#[test]
+fn test_osmosis_adapter_queries() {
+    with_env_setup(
+        |app, wasm, signer, _code_id, contract_addr| {
+            let pool_id = helpers::setup_pools(app, &signer)[0];
+            let dex = Dex::Osmosis { pool_id };
+
+            let pool: PoolInfo = wasm
+                .query(&contract_addr, &QueryMsg::DexPoolInfo { dex: dex.clone() })
+                .unwrap();
+            assert_eq!(
+                pool,
+                wasm.query::<QueryMsg, PoolInfo>(&contract_addr, &QueryMsg::QueryPoolInfo { pool_id })
+                    .unwrap()
+            );
+
+            // 1% swap fee on a 1_000uion / 1_000uosmo pool
+            let quote: QuoteResponse = wasm
+                .query(
+                    &contract_addr,
+                    &QueryMsg::Quote {
+                        dex: dex.clone(),
+                        token_in: Coin::new(100, "uosmo"),
+                        token_out_denom: "uion".to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(quote.amount, Uint128::new(90));
+
+            let res: DexPriceResponse = wasm
+                .query(
+                    &contract_addr,
+                    &QueryMsg::DexPrice {
+                        dex,
+                        base_denom: "uosmo".to_string(),
+                        quote_denom: "uion".to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(res.price, Decimal::one());
+        },
+        false,
+    );
+}
+
+#[test]
+fn test_migrate_from_v0_1_0() {
+    let app = OsmosisTestApp::new();
+    let wasm = Wasm::new(&app);
+    let signer = app
+        .init_account(&[Coin::new(100_000_000_000, "uosmo")])
+        .unwrap();
+
+    let old_code_id = wasm
+        .store_code(&helpers::get_previous_wasm_byte_code(), None, &signer)
+        .unwrap()
+        .data
+        .code_id;
+    let contract_addr = wasm
+        .instantiate(
+            old_code_id,
+            &InstantiateMsg { debug: false },
+            Some(&signer.address()),
+            None,
+            &[],
+            &signer,
+        )
+        .unwrap()
+        .data
+        .address;
+
+    let new_code_id = wasm
+        .store_code(&helpers::get_wasm_byte_code(), None, &signer)
+        .unwrap()
+        .data
+        .code_id;
+    wasm.migrate(
+        &contract_addr,
+        new_code_id,
+        &MigrateMsg::V0_2_0 { debug: Some(true) },
+        &signer,
+    )
+    .unwrap();
+
+    // queries keep working against the migrated state
+    assert_eq!(
+        QueryNumPoolsResponse { num_pools: 0 },
+        wasm.query(&contract_addr, &QueryMsg::QueryNumPools {})
+            .unwrap()
+    );
+
+    // migrating to the same release again is rejected
+    assert!(wasm
+        .migrate(
+            &contract_addr,
+            new_code_id,
+            &MigrateMsg::V0_2_0 { debug: None },
+            &signer,
+        )
+        .is_err());
+}
 
 fn test_twap_query() {
     with_env_setup(
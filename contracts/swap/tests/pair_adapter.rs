@@ -0,0 +1,445 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, Empty, Env, Fraction,
+    MessageInfo, Response, StdError, StdResult, Uint128,
+};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use osmo_swap::adapter::Dex;
+use osmo_swap::helpers::SwapContract;
+use osmo_swap::msg::{DexPriceResponse, ExecuteMsg, InstantiateMsg, QueryMsg, QuoteResponse};
+use osmo_swap::pair::{
+    Asset, PairExecuteMsg, PairQueryMsg, PoolResponse, ReverseSimulationResponse,
+    SimulationResponse,
+};
+use osmo_swap::pool::{PoolInfo, PoolType};
+
+// constant product pair without fees, reserves are its bank balances
+mod mock_pair {
+    use super::*;
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub denoms: [String; 2],
+    }
+
+    const DENOMS: Item<[String; 2]> = Item::new("denoms");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        DENOMS.save(deps.storage, &msg.denoms)?;
+        Ok(Response::new())
+    }
+
+    fn reserves(deps: Deps, env: &Env) -> StdResult<Vec<Asset>> {
+        DENOMS
+            .load(deps.storage)?
+            .iter()
+            .map(|denom| {
+                let balance = deps.querier.query_balance(&env.contract.address, denom)?;
+                Ok(Asset::native(denom, balance.amount))
+            })
+            .collect()
+    }
+
+    fn reserve_pair(
+        deps: Deps,
+        env: &Env,
+        offer_denom: &str,
+    ) -> StdResult<(Uint128, Uint128, String)> {
+        let assets = reserves(deps, env)?;
+        let offer = assets
+            .iter()
+            .find(|a| a.native_denom() == Some(offer_denom))
+            .ok_or_else(|| StdError::generic_err("offer asset not in pair"))?;
+        let ask = assets
+            .iter()
+            .find(|a| a.native_denom() != Some(offer_denom))
+            .unwrap();
+        Ok((
+            offer.amount,
+            ask.amount,
+            ask.native_denom().unwrap().to_string(),
+        ))
+    }
+
+    fn return_amount(
+        offer_reserve: Uint128,
+        ask_reserve: Uint128,
+        offer_amount: Uint128,
+    ) -> Uint128 {
+        ask_reserve.multiply_ratio(offer_amount, offer_reserve + offer_amount)
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: PairExecuteMsg,
+    ) -> StdResult<Response> {
+        let PairExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+        } = msg;
+        let offer_denom = offer_asset.native_denom().unwrap().to_string();
+        if info.funds != coins(offer_asset.amount.u128(), &offer_denom) {
+            return Err(StdError::generic_err("offer asset not sent"));
+        }
+        // the offer is already part of the balance
+        let (offer_reserve, ask_reserve, ask_denom) =
+            reserve_pair(deps.as_ref(), &env, &offer_denom)?;
+        let offer_reserve = offer_reserve - offer_asset.amount;
+        let returned = return_amount(offer_reserve, ask_reserve, offer_asset.amount);
+        if let (Some(belief_price), Some(max_spread)) = (belief_price, max_spread) {
+            let expected = offer_asset.amount * belief_price.inv().unwrap();
+            if returned < expected * (Decimal::one() - max_spread) {
+                return Err(StdError::generic_err("Operation exceeds max spread limit"));
+            }
+        }
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: to.unwrap_or_else(|| info.sender.to_string()),
+                amount: coins(returned.u128(), &ask_denom),
+            })
+            .add_attribute("action", "swap")
+            .add_attribute("offer_asset", offer_denom)
+            .add_attribute("ask_asset", ask_denom)
+            .add_attribute("offer_amount", offer_asset.amount)
+            .add_attribute("return_amount", returned))
+    }
+
+    pub fn query(deps: Deps, env: Env, msg: PairQueryMsg) -> StdResult<Binary> {
+        match msg {
+            PairQueryMsg::Pool {} => to_binary(&PoolResponse {
+                assets: reserves(deps, &env)?,
+                total_share: Uint128::new(1_000),
+            }),
+            PairQueryMsg::Simulation { offer_asset } => {
+                let (offer_reserve, ask_reserve, _) =
+                    reserve_pair(deps, &env, offer_asset.native_denom().unwrap())?;
+                to_binary(&SimulationResponse {
+                    return_amount: return_amount(offer_reserve, ask_reserve, offer_asset.amount),
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                })
+            }
+            PairQueryMsg::ReverseSimulation { ask_asset } => {
+                let ask_denom = ask_asset.native_denom().unwrap();
+                let assets = reserves(deps, &env)?;
+                let offer_denom = assets
+                    .iter()
+                    .find_map(|a| a.native_denom().filter(|d| *d != ask_denom))
+                    .unwrap();
+                let (offer_reserve, ask_reserve, _) = reserve_pair(deps, &env, offer_denom)?;
+                // rounded up, so the offer always returns at least the ask
+                let offer_amount = offer_reserve
+                    .multiply_ratio(ask_asset.amount, ask_reserve - ask_asset.amount)
+                    + Uint128::one();
+                to_binary(&ReverseSimulationResponse {
+                    offer_amount,
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                })
+            }
+        }
+    }
+}
+
+fn swap_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            osmo_swap::contract::execute,
+            osmo_swap::contract::instantiate,
+            osmo_swap::contract::query,
+        )
+        .with_reply(osmo_swap::contract::reply),
+    )
+}
+
+fn pair_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mock_pair::execute,
+        mock_pair::instantiate,
+        mock_pair::query,
+    ))
+}
+
+// pair holds 1_000_000uosmo and 2_000_000uatom, the swap contract 10_000uosmo
+fn setup() -> (App, Addr, Addr) {
+    let owner = Addr::unchecked("owner");
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![coin(1_010_000, "uosmo"), coin(2_000_000, "uatom")],
+            )
+            .unwrap();
+    });
+
+    let swap_code_id = app.store_code(swap_contract());
+    let swap = app
+        .instantiate_contract(
+            swap_code_id,
+            owner.clone(),
+            &InstantiateMsg { debug: false },
+            &coins(10_000, "uosmo"),
+            "swap",
+            None,
+        )
+        .unwrap();
+    let pair_code_id = app.store_code(pair_contract());
+    let pair = app
+        .instantiate_contract(
+            pair_code_id,
+            owner.clone(),
+            &mock_pair::InstantiateMsg {
+                denoms: ["uosmo".to_string(), "uatom".to_string()],
+            },
+            &[coin(1_000_000, "uosmo"), coin(2_000_000, "uatom")],
+            "pair",
+            None,
+        )
+        .unwrap();
+    (app, swap, pair)
+}
+
+fn pair_dex(pair: &Addr) -> Dex {
+    Dex::Pair {
+        contract: pair.to_string(),
+    }
+}
+
+fn swapped_event(res: &AppResponse) -> (String, String) {
+    let event = res
+        .events
+        .iter()
+        .find(|e| e.ty == "wasm-token_swapped")
+        .unwrap();
+    let attribute = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|a| a.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    (attribute("tokens_in"), attribute("tokens_out"))
+}
+
+#[test]
+fn test_pair_swap_exact_in() {
+    let (mut app, swap, pair) = setup();
+
+    let quote: QuoteResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &swap,
+            &QueryMsg::Quote {
+                dex: pair_dex(&pair),
+                token_in: coin(1_000, "uosmo"),
+                token_out_denom: "uatom".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(quote.amount, Uint128::new(1_998));
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            swap.clone(),
+            &ExecuteMsg::ExecuteSwap {
+                dex: pair_dex(&pair),
+                token_in: coin(1_000, "uosmo"),
+                token_out_denom: "uatom".to_string(),
+                token_out_min_amount: quote.amount,
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        swapped_event(&res),
+        ("1000uosmo".to_string(), "1998uatom".to_string())
+    );
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uatom").unwrap().amount,
+        Uint128::new(1_998)
+    );
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uosmo").unwrap().amount,
+        Uint128::new(9_000)
+    );
+}
+
+#[test]
+fn test_pair_swap_min_out_not_reached() {
+    let (mut app, swap, pair) = setup();
+
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        swap.clone(),
+        &ExecuteMsg::ExecuteSwap {
+            dex: pair_dex(&pair),
+            token_in: coin(1_000, "uosmo"),
+            token_out_denom: "uatom".to_string(),
+            token_out_min_amount: Uint128::new(1_999),
+        },
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uosmo").unwrap().amount,
+        Uint128::new(10_000)
+    );
+}
+
+#[test]
+fn test_pair_swap_exact_out() {
+    let (mut app, swap, pair) = setup();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            swap.clone(),
+            &ExecuteMsg::ExecuteSwapExactOut {
+                dex: pair_dex(&pair),
+                token_out: coin(1_000, "uatom"),
+                token_in_denom: "uosmo".to_string(),
+                token_in_max_amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("above the maximum of 500"));
+
+    let res = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            swap.clone(),
+            &ExecuteMsg::ExecuteSwapExactOut {
+                dex: pair_dex(&pair),
+                token_out: coin(1_000, "uatom"),
+                token_in_denom: "uosmo".to_string(),
+                token_in_max_amount: Uint128::new(600),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        swapped_event(&res),
+        ("501uosmo".to_string(), "1001uatom".to_string())
+    );
+}
+
+#[test]
+fn test_pair_swap_unauthorized() {
+    let (mut app, swap, pair) = setup();
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            swap,
+            &ExecuteMsg::ExecuteSwap {
+                dex: pair_dex(&pair),
+                token_in: coin(1_000, "uosmo"),
+                token_out_denom: "uatom".to_string(),
+                token_out_min_amount: Uint128::one(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+}
+
+#[test]
+fn test_pair_pool_info_and_price() {
+    let (app, swap, pair) = setup();
+
+    let pool: PoolInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &swap,
+            &QueryMsg::DexPoolInfo {
+                dex: pair_dex(&pair),
+            },
+        )
+        .unwrap();
+    assert_eq!(pool.pool_type, PoolType::Pair);
+    assert_eq!(
+        pool.assets,
+        vec![coin(1_000_000, "uosmo"), coin(2_000_000, "uatom")]
+    );
+    assert!(pool.has_denom("uatom"));
+    assert_eq!(pool.total_shares, coin(1_000, pair.as_str()));
+
+    let res: DexPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &swap,
+            &QueryMsg::DexPrice {
+                dex: pair_dex(&pair),
+                base_denom: "uosmo".to_string(),
+                quote_denom: "uatom".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.price, Decimal::from_ratio(2u128, 1u128));
+
+    app.wrap()
+        .query_wasm_smart::<DexPriceResponse>(
+            &swap,
+            &QueryMsg::DexPrice {
+                dex: pair_dex(&pair),
+                base_denom: "uosmo".to_string(),
+                quote_denom: "uion".to_string(),
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn test_swap_contract_helpers() {
+    let (mut app, swap, pair) = setup();
+    let client = SwapContract(swap.clone());
+
+    let quote = client
+        .quote(&app.wrap(), pair_dex(&pair), coin(1_000, "uosmo"), "uatom")
+        .unwrap();
+    assert_eq!(quote.amount, Uint128::new(1_998));
+    let price = client
+        .dex_price(&app.wrap(), pair_dex(&pair), "uosmo", "uatom")
+        .unwrap();
+    assert_eq!(price.price, Decimal::from_ratio(2u128, 1u128));
+    let pool = client.dex_pool_info(&app.wrap(), pair_dex(&pair)).unwrap();
+    assert_eq!(pool.pool_type, PoolType::Pair);
+
+    let msg = client
+        .swap(pair_dex(&pair), coin(1_000, "uosmo"), "uatom", quote.amount)
+        .unwrap();
+    let res = app.execute(Addr::unchecked("owner"), msg).unwrap();
+    assert_eq!(
+        swapped_event(&res),
+        ("1000uosmo".to_string(), "1998uatom".to_string())
+    );
+
+    let msg = client
+        .send_tokens_back(vec![coin(1_998, "uatom")], "owner")
+        .unwrap();
+    app.execute(Addr::unchecked("owner"), msg).unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&swap, "uatom").unwrap().amount,
+        Uint128::zero()
+    );
+}
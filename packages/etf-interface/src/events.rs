@@ -0,0 +1,36 @@
+use cosmwasm_std::{Coin, Event};
+
+/// gamm's swap event, the swap contract emits it as `wasm-token_swapped` for pair swaps
+pub const TOKEN_SWAPPED: &str = "token_swapped";
+pub const WASM_TOKEN_SWAPPED: &str = "wasm-token_swapped";
+
+/// attributes of the swap contract's pool join / exit responses, coins as `{amount}{denom}`
+pub const SHARES_OUT: &str = "shares_out";
+pub const TOKENS_IN: &str = "tokens_in";
+pub const TOKENS_OUT: &str = "tokens_out";
+
+/// `token_swapped` event of a pool swap
+pub struct TokenSwapped {
+    pub module: String,
+    pub sender: String,
+    pub pool_id: String,
+    pub tokens_in: Coin,
+    pub tokens_out: Coin,
+}
+
+impl TokenSwapped {
+    pub fn is(event: &Event) -> bool {
+        event.ty == TOKEN_SWAPPED || event.ty == WASM_TOKEN_SWAPPED
+    }
+}
+
+impl From<TokenSwapped> for Event {
+    fn from(swapped: TokenSwapped) -> Self {
+        Event::new(TOKEN_SWAPPED)
+            .add_attribute("module", swapped.module)
+            .add_attribute("sender", swapped.sender)
+            .add_attribute("pool_id", swapped.pool_id)
+            .add_attribute(TOKENS_IN, swapped.tokens_in.to_string())
+            .add_attribute(TOKENS_OUT, swapped.tokens_out.to_string())
+    }
+}
@@ -0,0 +1,7 @@
+//! Messages, responses and events of the etf contracts, so contracts, schemas and
+//! TS bindings can be built without depending on the contracts themselves
+pub mod events;
+pub mod manager;
+pub mod pool;
+pub mod share_token;
+pub mod swap;
@@ -0,0 +1,763 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Coin, Uint128, SubMsg, Empty, Decimal, Timestamp};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    InstantiateSwap { 
+        code_id: u64, 
+        debug: bool,
+    }, // should I use it within manager contract or maybe instantiate both indepenedntly?
+    InstantiateCw20 { 
+        code_id: u64, 
+        etf_name: String, 
+        etf_symbol: String,
+    },
+    // issues etf shares as native factory/{manager}/{etf_symbol} tokens instead of cw20,
+    // funds have to cover tokenfactory's denom creation fee
+    CreateNativeDenom {
+        etf_name: String,
+        etf_symbol: String,
+    },
+    // shares and the position go to on_behalf_of if set. Deposits made through an ibc-hooks memo pass their
+    // ibc_sender, excess funds are then transferred back to it over ibc. Only uosmo and usdc are deposit denoms,
+    // so only transfers arriving as one of them (like osmo going back home) can be deposited that way. ibc/... vouchers
+    // fail the memo, ibc-hooks then acks the transfer with an error and it's refunded on the source chain
+    SwapTokens { 
+        initial_balance: Coin,
+        etf_swap_routes: EtfSwapRoutes,
+        on_behalf_of: Option<String>,
+        ibc_sender: Option<IbcSender>,
+    },
+    // buys exactly `shares` of the etf: their value at the nav per share is split into tokens by the ratios,
+    // or index weights, and the part of max_initial_balance that isn't spent is sent back. Not available for batched etfs
+    SwapTokensExactAmountOut {
+        max_initial_balance: Coin,
+        etf_swap_routes: EtfSwapRoutes,
+        shares: Uint128,
+        max_slippage: Decimal,  // allowed input on top of the estimate, per route
+    },
+    // QueryMintTokens {
+    //     sender: String,
+    //     mint_contract: String
+    // },
+
+    // redeems the whole position. Native shares can be redeemed by any holder and in any amount by sending them
+    // as funds, for their part of every position of the etf, in kind
+    RedeemTokens {
+        etf_name: String
+    },
+    Callback {
+        operands: Vec<SubMsg<Empty>> 
+    },
+    // owner only, flags set to true get paused, the rest is left as it is
+    Pause {
+        buys: bool,
+        redemptions: bool
+    },
+    // owner only, flags set to true get unpaused, the rest is left as it is
+    Unpause {
+        buys: bool,
+        redemptions: bool
+    },
+    // owner only, None switches the breaker off
+    SetDowntimeBreaker {
+        breaker: Option<DowntimeBreaker>
+    },
+    // owner only, None removes all limits of the etf
+    SetEtfLimits {
+        etf_name: String,
+        limits: Option<EtfLimits>
+    },
+    // owner only, None switches batching off; can't be changed while the batch has queued requests
+    SetBatchMode {
+        etf_name: String,
+        batch: Option<BatchConfig>
+    },
+    // funds have to contain the batch's deposit denom, shares become claimable once the batch is settled
+    QueueDeposit {
+        etf_name: String
+    },
+    // queues the whole position, or the part of the etf matching native shares sent as funds
+    QueueRedemption {
+        etf_name: String
+    },
+    // anyone can settle, queued buys are netted against queued redemptions valued at TWAPs before swapping the rest
+    SettleBatch {
+        etf_name: String
+    },
+    // mints shares and sends proceeds of settled batches
+    ClaimBatch {
+        etf_name: String
+    },
+    // funds have to cover amount_per_period for all periods, the first purchase is due right away
+    CreateDcaPlan {
+        etf_name: String,
+        amount_per_period: Coin,
+        period: u64,    // seconds between purchases
+        num_periods: u32,
+        routes: Vec<Route>,
+        ratios: Vec<Uint128>
+    },
+    // anyone can execute, the caller gets a bounty out of every purchase that goes through.
+    // Plans whose purchase fails are paused and get that period's deposit back, bounty included
+    ExecuteDue {},
+    PauseDcaPlan {
+        plan_id: u64
+    },
+    ResumeDcaPlan {
+        plan_id: u64
+    },
+    // escrowed funds of periods not executed yet are sent back
+    CancelDcaPlan {
+        plan_id: u64
+    },
+    // only ever dispatched by `ExecuteDue`
+    DcaPurchase {
+        owner: String,
+        etf_swap_routes: EtfSwapRoutes,
+        deposit: Coin
+    },
+    // owner only, length of TWAPs nav orders are checked against
+    SetNavTwapWindow {
+        seconds: u64
+    },
+    // buys escrow the deposit, redemptions of etfs with native shares escrow all shares of the position
+    PlaceNavOrder {
+        etf_name: String,
+        kind: NavOrderKind,
+        nav_per_share: Decimal,
+        expires_at: Timestamp
+    },
+    // anyone can execute an order once its condition holds
+    ExecuteNavOrder {
+        owner: String,
+        order_id: u64
+    },
+    // escrow is sent back, expired orders included
+    CancelNavOrder {
+        order_id: u64
+    },
+    // owner only, holders can only donate to whitelisted beneficiaries
+    SetDonationBeneficiaries {
+        etf_name: String,
+        beneficiaries: Vec<String>
+    },
+    // None stops donating, growth made so far is only donated on the next harvest while set
+    SetDonation {
+        etf_name: String,
+        donation: Option<DonationSettings>
+    },
+    // anyone can harvest, holder's growth above the high-water mark is donated in etf tokens
+    HarvestDonation {
+        holder: String,
+        etf_name: String
+    },
+    // None turns holder governance off, open proposals can't be executed then
+    SetGovConfig {
+        config: Option<GovConfig>
+    },
+    // proposer has to hold shares of the etf
+    Propose {
+        etf_name: String,
+        action: EtfAdminMsg
+    },
+    // weighted by voter's shares at the proposal's snapshot height. Native shares vote with the shares
+    // sent along, they're escrowed until the proposal is executed
+    Vote {
+        proposal_id: u64,
+        vote: VoteOption
+    },
+    // anyone can execute once voting is over, passed actions are applied and rejected ones closed
+    ExecuteProposal {
+        proposal_id: u64
+    },
+    // etf's routes and ratios get computed on-chain, buyers pass them empty. None goes back to caller's ratios
+    SetIndexMethodology {
+        etf_name: String,
+        methodology: Option<IndexMethodology>
+    },
+    // anyone can recalculate index weights once the rebalance period is over, used by purchases from then on
+    Rebalance {
+        etf_name: String
+    },
+    // owner only, routes through `pool_id` swap in the pair contract instead of gamm. None removes it
+    SetPairRoute {
+        pool_id: u64,
+        pair: Option<String>
+    },
+    // owner only, None lifts the limit. Redemptions over the rolling window's capacity get queued until it has room
+    SetOutflowLimit {
+        etf_name: String,
+        limit: Option<OutflowLimit>
+    },
+    // anyone can process, redeems the oldest queued position once a window has capacity for it
+    ProcessQueuedRedemption {
+        etf_name: String
+    },
+    // owner only and final. Buys, redemptions and swaps of the etf stop and the basket held for it is
+    // snapshotted, to be claimed in kind. Batches have to be settled first
+    WindDown {
+        etf_name: String
+    },
+    // pro-rata part of a wound-down etf's basket, etfs with native shares need them sent back as funds
+    ClaimWindDown {
+        etf_name: String
+    }
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(GetTokensResponse)]
+    GetTokens {
+        sender: String,
+        etf_type: String
+    },
+    #[returns(GetInitialSwapResponse)]
+    GetInitialSwap {
+        sender: String,
+        etf_type: String
+    },
+    #[returns(GetBalanceResponse)]
+    GetBalance {
+        sender: String,
+        etf_type: String
+    },
+    #[returns(GetPauseStateResponse)]
+    GetPauseState {},
+    #[returns(EtfLimitsResponse)]
+    EtfLimits {
+        etf_name: String
+    },
+    #[returns(GetShareTokenResponse)]
+    GetShareToken {
+        etf_name: String
+    },
+    // current value of user's etf tokens in their deposit denom, at pool spot prices
+    #[returns(GetNavResponse)]
+    GetNav {
+        sender: String,
+        etf_type: String
+    },
+    #[returns(BatchStateResponse)]
+    BatchState {
+        etf_name: String
+    },
+    #[returns(BatchClaim)]
+    BatchClaim {
+        sender: String,
+        etf_name: String
+    },
+    #[returns(DcaPlansResponse)]
+    DcaPlans {
+        owner: String
+    },
+    #[returns(NavOrdersResponse)]
+    NavOrders {
+        owner: String
+    },
+    // of all positions, valued in the denom with TWAPs
+    #[returns(NavPerShareResponse)]
+    NavPerShare {
+        etf_name: String,
+        denom: String
+    },
+    #[returns(DonationResponse)]
+    Donation {
+        holder: String,
+        etf_name: String
+    },
+    #[returns(DonationBeneficiariesResponse)]
+    DonationBeneficiaries {
+        etf_name: String
+    },
+    // everything donated to the beneficiary, across etfs
+    #[returns(DonationTotalsResponse)]
+    DonationTotals {
+        beneficiary: String
+    },
+    #[returns(GovConfigResponse)]
+    GovConfig {},
+    #[returns(ProposalResponse)]
+    Proposal {
+        proposal_id: u64
+    },
+    #[returns(ProposalsResponse)]
+    Proposals {
+        etf_name: String
+    },
+    #[returns(VoteResponse)]
+    Vote {
+        proposal_id: u64,
+        voter: String
+    },
+    // defaults to current shares if no height is given. Native shares are the holder's current balance,
+    // banks keep no history
+    #[returns(VotingPowerResponse)]
+    VotingPower {
+        etf_name: String,
+        holder: String,
+        height: Option<u64>
+    },
+    #[returns(IndexResponse)]
+    Index {
+        etf_name: String
+    },
+    #[returns(PairRouteResponse)]
+    PairRoute {
+        pool_id: u64
+    },
+    #[returns(IbcRefundResponse)]
+    IbcRefund {
+        channel: String,
+        sequence: u64
+    },
+    // window a redemption made now would be counted in, and the redemptions waiting for capacity
+    #[returns(OutflowCapacityResponse)]
+    OutflowCapacity {
+        etf_name: String
+    },
+    #[returns(WindDownResponse)]
+    WindDown {
+        etf_name: String
+    },
+    // claimable is valued with the position for cw20 shares and with holder's bank balance for native ones
+    #[returns(WindDownClaimResponse)]
+    WindDownClaim {
+        etf_name: String,
+        holder: String
+    },
+}
+
+// called by ibc-hooks once transfers sent with an `ibc_callback` memo are acknowledged or time out
+#[cw_serde]
+pub enum SudoMsg {
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64
+    },
+}
+
+/// Message type for `migrate` entry_point, one variant per release that changes storage layout
+#[cw_serde]
+pub enum MigrateMsg {
+    /// 0.1.0 -> 0.2.0: initial swaps are kept per (sender, etf), redeemed balances and stale caches are cleared.
+    /// 0.1.0 had no owner, so the one allowed to pause the contract has to be provided
+    V0_2_0 {
+        owner: String
+    },
+}
+
+// #[cw_serde]
+// pub enum CallbackMsg {
+//     Conjunction { operands: Vec<WasmMsg> },
+// }
+
+// We define a custom struct for each query response
+#[cw_serde]
+pub struct GetTokensResponse {
+    pub tokens_per_etf: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct GetBalanceResponse {
+    pub balance: Coin,
+}
+
+
+#[cw_serde]
+pub struct GetInitialSwapResponse {
+    pub initial_swap: Coin
+}
+
+#[cw_serde]
+pub struct GetPauseStateResponse {
+    pub buys_paused: bool,
+    pub redemptions_paused: bool,
+    pub downtime_breaker: Option<DowntimeBreaker>,
+    // false while the breaker refuses swaps because of recent chain downtime
+    pub recovered_from_downtime: bool,
+}
+
+// Refuses swaps until `recovery_seconds` have passed since the chain was down for at least `downtime`
+#[cw_serde]
+pub struct DowntimeBreaker {
+    pub downtime: i32,  // osmosis_std::types::osmosis::downtimedetector::v1beta1::Downtime
+    pub recovery_seconds: i64
+}
+
+
+#[cw_serde]
+pub struct GetNavResponse {
+    pub nav: Coin,
+}
+
+// exactly one of the fields is set for an existing etf
+#[cw_serde]
+pub struct GetShareTokenResponse {
+    pub cw20_contract: Option<String>,
+    pub native_denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct EtfLimitsResponse {
+    pub limits: Option<EtfLimits>,
+    // deposits not redeemed yet, per deposit denom
+    pub total_deposits: Vec<Coin>,
+    // total_deposits valued in limits.quote_denom, None when the etf has no limits
+    pub total_deposits_value: Option<Uint128>,
+}
+
+// Limits applied to `SwapTokens` deposits, all amounts are valued in `quote_denom` at TWAPs over the nav twap window
+#[cw_serde]
+pub struct EtfLimits {
+    pub quote_denom: String,
+    pub price_pools: Vec<PricePool>,    // pool per deposit denom other than quote_denom, used to value it
+    pub min_deposit: Option<Uint128>,
+    pub max_deposit: Option<Uint128>,
+    pub max_per_user: Option<Uint128>,  // user's deposits not redeemed yet
+    pub max_total: Option<Uint128>,     // deposits of all users not redeemed yet
+}
+
+#[cw_serde]
+pub struct PricePool {
+    pub denom: String,
+    pub pool_id: u64
+}
+
+
+// Fixed basket bought for queued deposits, with the same meaning as in `EtfSwapRoutes`
+#[cw_serde]
+pub struct BatchConfig {
+    pub deposit_denom: String,
+    pub routes: Vec<Route>,
+    pub ratios: Vec<Uint128>
+}
+
+#[cw_serde]
+pub struct BatchStateResponse {
+    pub config: Option<BatchConfig>,
+    pub queued_deposits: Uint128,   // in config's deposit denom
+    pub queued_redemptions: u64,    // number of positions waiting to be redeemed
+}
+
+// results of settled batches, waiting for `ClaimBatch`
+#[cw_serde]
+#[derive(Default)]
+pub struct BatchClaim {
+    pub shares: Uint128,
+    pub proceeds: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct DcaPlansResponse {
+    pub plans: Vec<DcaPlan>,
+}
+
+// escrow of a plan is amount_per_period times periods_left
+#[cw_serde]
+pub struct DcaPlan {
+    pub id: u64,
+    pub etf_swap_routes: EtfSwapRoutes,
+    pub amount_per_period: Coin,
+    pub period: u64,
+    pub periods_left: u32,
+    pub next_execution: Timestamp,
+    pub paused: bool,
+}
+
+// Buys execute once nav per share is at or below the order's, redemptions at or above it
+#[cw_serde]
+pub enum NavOrderKind {
+    Buy {
+        deposit: Coin,
+        routes: Vec<Route>,
+        ratios: Vec<Uint128>
+    },
+    // native shares sent as funds are escrowed and compared against nav per share in uosmo
+    Redeem {},
+}
+
+#[cw_serde]
+pub struct NavOrder {
+    pub id: u64,
+    pub etf_name: String,
+    pub kind: NavOrderKind,
+    pub nav_per_share: Decimal,
+    pub denom: String,      // nav is valued in the deposit denom of the order
+    pub expires_at: Timestamp,
+    pub escrow: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct NavOrdersResponse {
+    pub orders: Vec<NavOrder>,
+}
+
+#[cw_serde]
+pub struct NavPerShareResponse {
+    pub nav_per_share: Decimal,
+    pub denom: String,
+}
+
+#[cw_serde]
+pub struct DonationSettings {
+    pub beneficiary: String,
+    pub percent: Decimal,   // part of nav growth donated, up to 1
+}
+
+#[cw_serde]
+pub struct Donation {
+    pub beneficiary: String,
+    pub percent: Decimal,
+    // nav of the position after the last donation, plus deposits made since, in the deposit denom
+    pub high_water_mark: Uint128,
+}
+
+#[cw_serde]
+pub struct DonationResponse {
+    pub donation: Option<Donation>,
+}
+
+#[cw_serde]
+pub struct DonationBeneficiariesResponse {
+    pub beneficiaries: Vec<String>,
+}
+
+#[cw_serde]
+pub struct DonationTotalsResponse {
+    pub donated: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct GovConfig {
+    pub quorum: Decimal,        // part of all shares at the snapshot that has to vote
+    pub threshold: Decimal,     // part of yes among yes and no votes needed to pass
+    pub voting_period: u64,     // in seconds
+}
+
+// etf parameters holders can change, mirror the owner's admin messages
+#[cw_serde]
+pub enum EtfAdminMsg {
+    SetEtfLimits {
+        limits: Option<EtfLimits>
+    },
+    SetBatchMode {
+        batch: Option<BatchConfig>
+    },
+    SetDonationBeneficiaries {
+        beneficiaries: Vec<String>
+    },
+    SetIndexMethodology {
+        methodology: Option<IndexMethodology>
+    },
+    SetOutflowLimit {
+        limit: Option<OutflowLimit>
+    },
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum VoteOption {
+    Yes,
+    No,
+    Abstain,
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum ProposalStatus {
+    Open,
+    Executed,
+    Rejected,
+}
+
+#[cw_serde]
+pub struct Proposal {
+    pub id: u64,
+    pub etf_name: String,
+    pub proposer: String,
+    pub action: EtfAdminMsg,
+    pub snapshot_height: u64,
+    pub voting_end: Timestamp,
+    pub config: GovConfig,      // config at creation, later changes don't affect open proposals
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub abstain: Uint128,
+    pub status: ProposalStatus,
+}
+
+#[cw_serde]
+pub struct Ballot {
+    pub vote: VoteOption,
+    pub weight: Uint128,
+    pub escrow: Option<Coin>,   // native shares, sent back once the proposal is executed
+}
+
+#[cw_serde]
+pub struct GovConfigResponse {
+    pub config: Option<GovConfig>,
+}
+
+#[cw_serde]
+pub struct ProposalResponse {
+    pub proposal: Proposal,
+}
+
+#[cw_serde]
+pub struct ProposalsResponse {
+    pub proposals: Vec<Proposal>,
+}
+
+#[cw_serde]
+pub struct VoteResponse {
+    pub ballot: Option<Ballot>,
+}
+
+#[cw_serde]
+pub struct VotingPowerResponse {
+    pub shares: Uint128,
+    pub total_shares: Uint128,
+}
+
+#[cw_serde]
+pub struct IbcSender {
+    pub channel: String,    // channel on this chain the transfer came in through
+    pub sender: String,     // sender on the counterparty chain
+}
+
+#[cw_serde]
+pub struct IbcRefund {
+    pub channel: String,
+    pub receiver: String,
+    pub amount: Coin,
+    pub recovery_addr: String,  // gets the refund if the transfer fails or times out
+}
+
+#[cw_serde]
+pub struct IbcRefundResponse {
+    pub refund: Option<IbcRefund>,
+}
+
+#[cw_serde]
+pub struct PairRouteResponse {
+    pub pair: Option<Addr>,
+}
+
+// Caps the shares redeemed over any `window` seconds, like ibcratelimit's quotas cap a channel's flow per period.
+// Shares are minted for the uosmo value of deposits, so they stand in for nav
+#[cw_serde]
+pub struct OutflowLimit {
+    pub max_percent: Decimal,   // of all shares at the start of the window, up to 1
+    pub window: u64,            // in seconds
+}
+
+// ibcratelimit's `Flow`, outflows only. The window slides: the previous window's outflow still counts
+// for the part of it that's within the last `window` seconds
+#[cw_serde]
+pub struct Outflow {
+    pub capacity: Uint128,      // shares redeemable over a window
+    pub outflow: Uint128,       // shares redeemed in the current window so far
+    #[serde(default)]
+    pub previous_outflow: Uint128,
+    pub window_end: Timestamp,
+}
+
+// native shares of queued positions are held by the manager until they're redeemed
+#[cw_serde]
+pub struct QueuedRedemption {
+    pub id: u64,
+    pub sender: String,
+    pub shares: Uint128,
+    pub queued_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct OutflowCapacityResponse {
+    pub limit: Option<OutflowLimit>,
+    pub outflow: Option<Outflow>,
+    pub remaining: Option<Uint128>,     // shares redeemable without being queued, if nothing is queued
+    pub queue: Vec<QueuedRedemption>,
+}
+
+#[cw_serde]
+pub struct WindDown {
+    pub basket: Vec<Coin>,          // tokens of all positions at the wind-down
+    pub total_shares: Uint128,      // shares the basket is split between
+    pub claimed: Vec<Coin>,
+    pub claimed_shares: Uint128,
+    pub wound_down_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct WindDownResponse {
+    pub wind_down: Option<WindDown>,
+}
+
+#[cw_serde]
+pub struct WindDownClaimResponse {
+    pub claimable: Vec<Coin>,
+    pub claimed: Vec<Coin>,
+}
+
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Weighting {
+    Equal,
+    MarketCap,      // bank supply of the denom times its price
+    Liquidity,      // value of all liquidity in the component's route pool
+}
+
+#[cw_serde]
+pub struct IndexComponent {
+    pub route: Route,
+    pub cap: Option<Decimal>,   // max weight of the component, up to 1
+}
+
+#[cw_serde]
+pub struct IndexMethodology {
+    pub weighting: Weighting,
+    pub deposit_denom: String,  // the only denom routes are built for, components are valued in it
+    pub components: Vec<IndexComponent>,
+    pub rebalance_period: u64,  // in seconds
+}
+
+#[cw_serde]
+pub struct IndexWeights {
+    pub ratios: Vec<Uint128>,   // per component, in percents like the caller supplied ones
+    pub next_rebalance: Timestamp,
+}
+
+#[cw_serde]
+pub struct IndexResponse {
+    pub methodology: Option<IndexMethodology>,
+    pub weights: Option<IndexWeights>,
+}
+
+#[cw_serde]
+pub struct EtfSwapRoutes {
+    pub name: String,
+    pub routes: Vec<Route>, // Route per each token that etf consists of
+    pub ratios: Vec<Uint128>    // ratio per each token that etf consists of -> consider merging into Vec<(Route, u64)>
+}
+
+#[cw_serde]
+pub struct Route {
+    pub pool_id: u64, 
+    pub token_out_denom: String
+}
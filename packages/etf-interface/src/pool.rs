@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Coin, Decimal, QuerierWrapper, StdError, StdResult, Timestamp, Uint128};
+use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use osmosis_std::types::osmosis::gamm::poolmodels::stableswap::v1beta1::Pool as StableswapPool;
+use osmosis_std::types::osmosis::gamm::v1beta1::{GammQuerier, Pool as BalancerPool};
+use osmosis_std::shim::Timestamp as ShimTimestamp;
+use osmosis_std::types::osmosis::gamm::v2::GammQuerier as GammV2Querier;
+use osmosis_std::types::osmosis::twap::v1beta1::TwapQuerier;
+
+#[cw_serde]
+pub enum PoolType {
+    Balancer,
+    Stableswap,
+    /// CosmWasm AMM pair contract, not a gamm pool
+    Pair,
+}
+
+impl FromStr for PoolType {
+    type Err = StdError;
+
+    // names returned by gamm's `PoolType` query
+    fn from_str(pool_type: &str) -> StdResult<Self> {
+        match pool_type {
+            "Balancer" => Ok(PoolType::Balancer),
+            "Stableswap" => Ok(PoolType::Stableswap),
+            other => Err(StdError::generic_err(format!("Unsupported pool type: {}", other))),
+        }
+    }
+}
+
+/// Pool data shared by all gamm pool types and pair contracts
+#[cw_serde]
+pub struct PoolInfo {
+    pub id: u64,
+    pub pool_type: PoolType,
+    pub assets: Vec<Coin>,
+    /// balancer only, in the same order as `assets`
+    pub weights: Vec<Uint128>,
+    /// stableswap only, in the same order as `assets`
+    pub scaling_factors: Vec<u64>,
+    pub swap_fee: Decimal,
+    pub exit_fee: Decimal,
+    pub total_shares: Coin,
+}
+
+impl PoolInfo {
+    pub fn query(querier: &QuerierWrapper, pool_id: u64) -> StdResult<Self> {
+        let gamm = GammQuerier::new(querier);
+        let pool_type: PoolType = gamm.pool_type(pool_id)?.pool_type.parse()?;
+        let pool = gamm
+            .pool(pool_id)?
+            .pool
+            .ok_or_else(|| StdError::not_found("pool"))?;
+
+        match pool_type {
+            PoolType::Balancer => {
+                let pool: BalancerPool = pool.try_into().map_err(|e| {
+                    StdError::parse_err("osmosis_std::types::osmosis::gamm::v1beta1::Pool", e)
+                })?;
+                let params = pool.pool_params.unwrap_or_default();
+                let (assets, weights) = pool
+                    .pool_assets
+                    .into_iter()
+                    .map(|asset| Ok((to_coin(asset.token)?, asset.weight.parse::<Uint128>()?)))
+                    .collect::<StdResult<Vec<_>>>()?
+                    .into_iter()
+                    .unzip();
+                Ok(PoolInfo {
+                    id: pool.id,
+                    pool_type,
+                    assets,
+                    weights,
+                    scaling_factors: vec![],
+                    swap_fee: parse_dec(&params.swap_fee)?,
+                    exit_fee: parse_dec(&params.exit_fee)?,
+                    total_shares: to_coin(pool.total_shares)?,
+                })
+            }
+            PoolType::Stableswap => {
+                let pool: StableswapPool = pool.try_into().map_err(|e| {
+                    StdError::parse_err(
+                        "osmosis_std::types::osmosis::gamm::poolmodels::stableswap::v1beta1::Pool",
+                        e,
+                    )
+                })?;
+                let params = pool.pool_params.unwrap_or_default();
+                Ok(PoolInfo {
+                    id: pool.id,
+                    pool_type,
+                    assets: pool
+                        .pool_liquidity
+                        .into_iter()
+                        .map(|c| to_coin(Some(c)))
+                        .collect::<StdResult<_>>()?,
+                    weights: vec![],
+                    scaling_factors: pool.scaling_factors,
+                    swap_fee: parse_dec(&params.swap_fee)?,
+                    exit_fee: parse_dec(&params.exit_fee)?,
+                    total_shares: to_coin(pool.total_shares)?,
+                })
+            }
+            // never parsed from gamm's pool types
+            PoolType::Pair => Err(StdError::generic_err(format!("Pool {} is not a gamm pool", pool_id))),
+        }
+    }
+
+    pub fn has_denom(&self, denom: &str) -> bool {
+        self.assets.iter().any(|c| c.denom == denom)
+    }
+
+    /// Price of `base_denom` in `quote_denom`, as computed by the chain for this pool type
+    pub fn spot_price(
+        &self,
+        querier: &QuerierWrapper,
+        base_denom: &str,
+        quote_denom: &str,
+    ) -> StdResult<Decimal> {
+        self.ensure_denoms(base_denom, quote_denom)?;
+        let spot_price = GammV2Querier::new(querier)
+            .spot_price(self.id, base_denom.to_string(), quote_denom.to_string())?
+            .spot_price;
+        parse_dec(&spot_price)
+    }
+
+    /// Arithmetic TWAP of `base_denom` in `quote_denom` from `start_time` until now
+    pub fn twap_price(
+        &self,
+        querier: &QuerierWrapper,
+        base_denom: &str,
+        quote_denom: &str,
+        start_time: Timestamp,
+    ) -> StdResult<Decimal> {
+        self.ensure_denoms(base_denom, quote_denom)?;
+        let start_time = ShimTimestamp {
+            seconds: start_time.seconds() as i64,
+            nanos: start_time.subsec_nanos() as i32,
+        };
+        let twap = TwapQuerier::new(querier)
+            .arithmetic_twap(
+                self.id,
+                base_denom.to_string(),
+                quote_denom.to_string(),
+                Some(start_time),
+                None,
+            )?
+            .arithmetic_twap;
+        parse_dec(&twap)
+    }
+
+    fn ensure_denoms(&self, base_denom: &str, quote_denom: &str) -> StdResult<()> {
+        for denom in [base_denom, quote_denom] {
+            if !self.has_denom(denom) {
+                return Err(StdError::generic_err(format!(
+                    "Token {} not found in pool {}",
+                    denom, self.id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_coin(coin: Option<ProtoCoin>) -> StdResult<Coin> {
+    coin.ok_or_else(|| StdError::not_found("coin"))?.try_into()
+}
+
+// sdk.Dec comes either human readable ("0.01") or as its 18 decimal places integer representation
+fn parse_dec(dec: &str) -> StdResult<Decimal> {
+    if dec.contains('.') {
+        Decimal::from_str(dec)
+    } else {
+        Decimal::from_atomics(dec.parse::<Uint128>()?, 18)
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
+}
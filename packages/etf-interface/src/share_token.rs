@@ -0,0 +1,24 @@
+use cosmwasm_schema::cw_serde;
+use cw20::{Cw20Coin, Logo, MinterResponse};
+
+// cw20-base's messages, the manager only instantiates share tokens and mints / burns them
+pub use cw20::Cw20ExecuteMsg as ExecuteMsg;
+pub use cw20::Cw20QueryMsg as QueryMsg;
+
+#[cw_serde]
+pub struct InstantiateMarketingInfo {
+    pub project: Option<String>,
+    pub description: Option<String>,
+    pub marketing: Option<String>,
+    pub logo: Option<Logo>,
+}
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+}
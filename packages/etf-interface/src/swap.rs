@@ -0,0 +1,153 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+
+use osmosis_std::{shim::Timestamp, types::{cosmos::base::v1beta1::Coin, osmosis::gamm::v1beta1::Pool}};
+pub use osmosis_std::types::osmosis::epochs::v1beta1::QueryEpochsInfoResponse;
+use osmosis_std::types::osmosis::gamm::v1beta1::{SwapAmountInRoute, SwapAmountOutRoute};
+pub use osmosis_std::types::osmosis::gamm::v1beta1::{QueryNumPoolsResponse, QueryPoolParamsResponse, QueryPoolResponse,};
+use osmosis_std::types::osmosis::twap::v1beta1::ArithmeticTwapResponse;
+pub use osmosis_std::types::osmosis::twap::v1beta1::{ArithmeticTwapToNowRequest, ArithmeticTwapToNowResponse,};
+use cosmwasm_std::{Coin as CoinStd, Decimal, Uint128};
+
+use crate::pool::PoolInfo;
+
+/// Pool a swap goes through
+#[cw_serde]
+pub enum Dex {
+    /// gamm pool, swapped with `MsgSwapExactAmountIn` / `MsgSwapExactAmountOut`
+    Osmosis { pool_id: u64 },
+    /// CosmWasm AMM pair contract, see `osmo_swap::pair` for the interface
+    Pair { contract: String },
+}
+
+/// Message type for `instantiate` entry_point
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub debug: bool,
+}
+
+/// Message type for `execute` entry_point
+#[cw_serde]
+pub enum ExecuteMsg {
+    ExecuteSwapExactAmountIn {
+        routes: Vec<SwapAmountInRoute>,
+        token_in: Option<Coin>,
+        token_out_min_amount: String
+    },
+    ExecuteSwapExactAmountOut {
+        routes: Vec<SwapAmountOutRoute>,
+        token_out: Option<Coin>,
+        token_in_max_amount: String
+    },
+    /// owner only, enters `pool_id` with a single token, `shares_out` attribute holds the shares received
+    ExecuteJoinSwapExternAmountIn {
+        pool_id: u64,
+        token_in: Option<Coin>,
+        share_out_min_amount: String
+    },
+    /// owner only, exits `pool_id` into a single token, `tokens_out` attribute holds the tokens received
+    ExecuteExitSwapShareAmountIn {
+        pool_id: u64,
+        token_out_denom: String,
+        share_in_amount: String,
+        token_out_min_amount: String
+    },
+    SendTokensBack {
+        tokens: Vec<CoinStd>,
+        recipient: String
+    },
+    /// owner only, swaps through a single pool of any supported dex. Pair swaps emit a
+    /// `wasm-token_swapped` event shaped like gamm's `token_swapped`
+    ExecuteSwap {
+        dex: Dex,
+        token_in: CoinStd,
+        token_out_denom: String,
+        token_out_min_amount: Uint128,
+    },
+    /// owner only, exact amount out counterpart of `ExecuteSwap`
+    ExecuteSwapExactOut {
+        dex: Dex,
+        token_out: CoinStd,
+        token_in_denom: String,
+        token_in_max_amount: Uint128,
+    },
+}
+
+/// Message type for `migrate` entry_point, one variant per release
+#[cw_serde]
+pub enum MigrateMsg {
+    /// 0.1.0 -> 0.2.0, optionally switching query debugging on or off
+    V0_2_0 { debug: Option<bool> },
+}
+
+/// Message type for `query` entry_point
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(QueryNumPoolsResponse)]
+    QueryNumPools {},
+
+    #[returns(QueryEpochsInfoResponse)]
+    QueryEpochsInfo {},
+
+    #[returns(QueryPoolResponse)]
+    QueryPool { pool_id: u64 },
+
+    #[returns(QueryPoolParamsResponse)]
+    QueryPoolParams { pool_id: u64 },
+
+    /// Balancer and stableswap pools decoded into a common shape
+    #[returns(PoolInfo)]
+    QueryPoolInfo { pool_id: u64 },
+
+    #[returns(ArithmeticTwapResponse)]
+    QueryArithmeticTwap{            
+        pool_id: u64,
+        base_asset: String,
+        quote_asset: String,
+        start_time: Option<Timestamp>,
+        end_time: Option<Timestamp>},
+
+    #[returns(QueryMapResponse)]
+    QueryMap { key: String },
+
+    /// amount of `token_out_denom` swapping `token_in` through `dex` would return
+    #[returns(QuoteResponse)]
+    Quote { dex: Dex, token_in: CoinStd, token_out_denom: String },
+
+    #[returns(PoolInfo)]
+    DexPoolInfo { dex: Dex },
+
+    /// spot price of `base_denom` in `quote_denom`
+    #[returns(DexPriceResponse)]
+    DexPrice { dex: Dex, base_denom: String, quote_denom: String },
+}
+// #[cw_serde]
+// #[derive(QueryResponses)]
+// pub enum ResponseTypes{
+//     #[returns(QueryEpochsInfoResponse)]
+//     QueryEpochsInfoResponse,
+//     #[returns(QueryPoolParamsResponse)]
+//     QueryPoolParamsResponse,
+//     #[returns(QueryPoolResponse)]
+//     QueryPoolResponse
+// }
+
+#[cw_serde]
+pub struct QueryMapResponse {
+    pub value: String,
+}
+
+#[cw_serde]
+pub struct QuoteResponse {
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct DexPriceResponse {
+    pub price: Decimal,
+}
+
+#[cw_serde]
+pub struct OtherQueryPoolResponse{
+    pub pool: Pool
+}
\ No newline at end of file
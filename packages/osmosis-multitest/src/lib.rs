@@ -0,0 +1,7 @@
+//! Pure Rust mock of osmosis' gamm and twap modules for `cw_multi_test::App`, so contracts sending
+//! stargate messages and queries can be tested without building `libosmosistesting`
+mod pool;
+mod stargate;
+
+pub use pool::{pool_address, BalancerPool};
+pub use stargate::OsmosisStargate;
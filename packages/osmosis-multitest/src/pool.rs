@@ -0,0 +1,224 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Result as AnyResult};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Decimal, Order, Storage, Timestamp, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+use osmosis_std::types::cosmos::base::v1beta1::Coin as ProtoCoin;
+use osmosis_std::types::osmosis::gamm::v1beta1::{Pool, PoolAsset, PoolParams};
+
+// shares minted to a pool's creator, as gamm does
+pub const INIT_POOL_SHARES: u128 = 100_000_000_000_000_000_000;
+
+pub const POOLS: Map<u64, BalancerPool> = Map::new("osmosis_gamm_pools");
+pub const POOL_COUNT: Item<u64> = Item::new("osmosis_gamm_pool_count");
+//(pool id, block time in nanos), reserves at the end of that block
+pub const TWAP_RECORDS: Map<(u64, u64), Vec<Coin>> = Map::new("osmosis_twap_records");
+
+/// Balancer pool as kept by the mock, assets and weights in the same order
+#[cw_serde]
+pub struct BalancerPool {
+    pub id: u64,
+    pub assets: Vec<Coin>,
+    pub weights: Vec<Uint128>,
+    pub swap_fee: Decimal,
+    pub exit_fee: Decimal,
+    pub total_shares: Coin,
+}
+
+impl BalancerPool {
+    /// Module account holding the pool's reserves
+    pub fn address(&self) -> Addr {
+        pool_address(self.id)
+    }
+
+    fn position(&self, denom: &str) -> AnyResult<usize> {
+        match self.assets.iter().position(|c| c.denom == denom) {
+            Some(i) => Ok(i),
+            None => bail!("Token {} not found in pool {}", denom, self.id),
+        }
+    }
+
+    /// Price of `base_denom` in `quote_denom`, `(quote / quote weight) / (base / base weight)`
+    pub fn spot_price(&self, base_denom: &str, quote_denom: &str) -> AnyResult<Decimal> {
+        spot_price(self, &self.assets, base_denom, quote_denom)
+    }
+
+    /// Amount of `token_out_denom` swapping `token_in` returns, constant product after the swap fee.
+    /// Like gamm, the fee stays in the pool
+    pub fn swap_exact_amount_in(
+        &mut self,
+        token_in: &Coin,
+        token_out_denom: &str,
+    ) -> AnyResult<Coin> {
+        let (i, o) = (
+            self.position(&token_in.denom)?,
+            self.position(token_out_denom)?,
+        );
+        if i == o {
+            bail!("Cannot swap {} for itself", token_out_denom);
+        }
+        if self.weights[i] != self.weights[o] {
+            bail!(
+                "Pool {} has unequal weights, only constant product swaps are mocked",
+                self.id
+            );
+        }
+
+        let amount_in_after_fee = token_in.amount * (Decimal::one() - self.swap_fee);
+        let amount_out = self.assets[o].amount.multiply_ratio(
+            amount_in_after_fee,
+            self.assets[i].amount + amount_in_after_fee,
+        );
+        if amount_out.is_zero() {
+            bail!("Token amount out is zero");
+        }
+        self.assets[i].amount += token_in.amount;
+        self.assets[o].amount -= amount_out;
+        Ok(Coin {
+            denom: token_out_denom.to_string(),
+            amount: amount_out,
+        })
+    }
+
+    /// Proto form returned by gamm's `Pool` query
+    pub fn to_proto(&self) -> Pool {
+        Pool {
+            address: self.address().into_string(),
+            id: self.id,
+            pool_params: Some(PoolParams {
+                swap_fee: to_sdk_dec(self.swap_fee),
+                exit_fee: to_sdk_dec(self.exit_fee),
+                smooth_weight_change_params: None,
+            }),
+            future_pool_governor: String::new(),
+            total_shares: Some(self.total_shares.clone().into()),
+            pool_assets: self
+                .assets
+                .iter()
+                .zip(self.weights.iter())
+                .map(|(token, weight)| PoolAsset {
+                    token: Some(token.clone().into()),
+                    weight: weight.to_string(),
+                })
+                .collect(),
+            total_weight: self.weights.iter().sum::<Uint128>().to_string(),
+        }
+    }
+}
+
+pub fn pool_address(pool_id: u64) -> Addr {
+    Addr::unchecked(format!("osmo1gammpool{}", pool_id))
+}
+
+pub fn load_pool(storage: &dyn Storage, pool_id: u64) -> AnyResult<BalancerPool> {
+    match POOLS.may_load(storage, pool_id)? {
+        Some(pool) => Ok(pool),
+        None => bail!("Pool {} does not exist", pool_id),
+    }
+}
+
+/// Saves the pool and its reserves as the block's TWAP record
+pub fn save_pool(storage: &mut dyn Storage, time: Timestamp, pool: &BalancerPool) -> AnyResult<()> {
+    POOLS.save(storage, pool.id, pool)?;
+    TWAP_RECORDS.save(storage, (pool.id, time.nanos()), &pool.assets)?;
+    Ok(())
+}
+
+/// Time weighted average of the spot price of `base_denom` in `quote_denom` between `start` and `end`,
+/// reserves being constant between two records
+pub fn arithmetic_twap(
+    storage: &dyn Storage,
+    pool: &BalancerPool,
+    base_denom: &str,
+    quote_denom: &str,
+    start: Timestamp,
+    end: Timestamp,
+) -> AnyResult<Decimal> {
+    if end < start {
+        bail!("End time {} is before start time {}", end, start);
+    }
+    let record_at = |time: Timestamp| -> AnyResult<Vec<Coin>> {
+        match TWAP_RECORDS
+            .prefix(pool.id)
+            .range(
+                storage,
+                None,
+                Some(Bound::inclusive(time.nanos())),
+                Order::Descending,
+            )
+            .next()
+            .transpose()?
+        {
+            Some((_, assets)) => Ok(assets),
+            None => bail!("No TWAP record of pool {} before {}", pool.id, time),
+        }
+    };
+
+    let mut assets = record_at(start)?;
+    if end == start {
+        return spot_price(pool, &assets, base_denom, quote_denom);
+    }
+
+    let total = Uint128::from(end.nanos() - start.nanos());
+    let mut twap = Decimal::zero();
+    let mut from = start.nanos();
+    let records = TWAP_RECORDS
+        .prefix(pool.id)
+        .range(
+            storage,
+            Some(Bound::exclusive(start.nanos())),
+            Some(Bound::exclusive(end.nanos())),
+            Order::Ascending,
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+    for (time, next_assets) in records
+        .into_iter()
+        .chain(std::iter::once((end.nanos(), vec![])))
+    {
+        let price = spot_price(pool, &assets, base_denom, quote_denom)?;
+        twap += price * Decimal::from_ratio(time - from, total);
+        from = time;
+        assets = next_assets;
+    }
+    Ok(twap)
+}
+
+fn spot_price(
+    pool: &BalancerPool,
+    assets: &[Coin],
+    base_denom: &str,
+    quote_denom: &str,
+) -> AnyResult<Decimal> {
+    let (b, q) = (pool.position(base_denom)?, pool.position(quote_denom)?);
+    Ok(Decimal::checked_from_ratio(
+        assets[q].amount.checked_mul(pool.weights[b])?,
+        assets[b].amount.checked_mul(pool.weights[q])?,
+    )?)
+}
+
+pub fn from_proto_coin(coin: Option<ProtoCoin>) -> AnyResult<Coin> {
+    match coin {
+        Some(coin) => Ok(Coin {
+            denom: coin.denom,
+            amount: coin.amount.parse()?,
+        }),
+        None => bail!("Missing coin"),
+    }
+}
+
+// sdk.Dec comes either human readable ("0.01") or as its 18 decimal places integer representation
+pub fn parse_dec(dec: &str) -> AnyResult<Decimal> {
+    if dec.contains('.') {
+        Ok(Decimal::from_str(dec)?)
+    } else {
+        Ok(Decimal::from_atomics(dec.parse::<Uint128>()?, 18)?)
+    }
+}
+
+// sdk.Dec as the chain prints it, always with 18 decimal places
+pub fn to_sdk_dec(dec: Decimal) -> String {
+    let atomics = dec.atomics().u128();
+    let unit = 10u128.pow(Decimal::DECIMAL_PLACES);
+    format!("{}.{:018}", atomics / unit, atomics % unit)
+}
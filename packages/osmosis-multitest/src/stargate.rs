@@ -0,0 +1,550 @@
+use std::fmt::Debug;
+
+use anyhow::{bail, Result as AnyResult};
+use cosmwasm_std::{
+    coin, to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CustomQuery, Decimal, Event,
+    Querier, Storage, Timestamp, Uint128,
+};
+use cw_multi_test::{AppResponse, BankSudo, CosmosRouter, Stargate, SudoMsg};
+use osmosis_std::shim::{Any, Timestamp as ShimTimestamp};
+use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::{
+    MsgCreateBalancerPool, MsgCreateBalancerPoolResponse,
+};
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, Pool, QueryNumPoolsResponse,
+    QueryPoolRequest, QueryPoolResponse, QueryPoolTypeRequest, QueryPoolTypeResponse,
+    QuerySwapExactAmountInRequest, QuerySwapExactAmountInResponse, QueryTotalPoolLiquidityRequest,
+    QueryTotalPoolLiquidityResponse, SwapAmountInRoute,
+};
+use osmosis_std::types::osmosis::gamm::v2::{QuerySpotPriceRequest, QuerySpotPriceResponse};
+use osmosis_std::types::osmosis::twap::v1beta1::{
+    ArithmeticTwapRequest, ArithmeticTwapResponse, ArithmeticTwapToNowRequest,
+    ArithmeticTwapToNowResponse,
+};
+use prost::Message;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use crate::pool::{
+    arithmetic_twap, from_proto_coin, load_pool, parse_dec, pool_address, save_pool, to_sdk_dec,
+    BalancerPool, INIT_POOL_SHARES, POOL_COUNT,
+};
+
+/// Stargate handler for `cw_multi_test::App` mocking osmosis' gamm and twap modules:
+/// balancer pools, `MsgSwapExactAmountIn` and the pool, spot price and arithmetic TWAP queries.
+/// Queries answer in JSON like osmosis' stargate whitelist, messages in proto like the chain
+///
+/// ```ignore
+/// let app = AppBuilder::new()
+///     .with_stargate(OsmosisStargate::default())
+///     .build(|router, _api, storage| { /* balances */ });
+/// ```
+#[derive(Default)]
+pub struct OsmosisStargate {}
+
+impl Stargate for OsmosisStargate {
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        type_url: String,
+        value: Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match type_url.as_str() {
+            MsgCreateBalancerPool::TYPE_URL => {
+                let msg = MsgCreateBalancerPool::try_from(value)?;
+                ensure_signer(&sender, &msg.sender)?;
+                create_balancer_pool(api, storage, router, block, sender, msg)
+            }
+            MsgSwapExactAmountIn::TYPE_URL => {
+                let msg = MsgSwapExactAmountIn::try_from(value)?;
+                ensure_signer(&sender, &msg.sender)?;
+                swap_exact_amount_in(api, storage, router, block, sender, msg)
+            }
+            type_url => bail!("Unsupported stargate message {}", type_url),
+        }
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        block: &BlockInfo,
+        path: String,
+        data: Binary,
+    ) -> AnyResult<Binary> {
+        Ok(match path.as_str() {
+            "/osmosis.gamm.v1beta1.Query/Pool" => {
+                let req = QueryPoolRequest::try_from(data)?;
+                let pool = load_pool(storage, req.pool_id)?.to_proto();
+                to_binary(&QueryPoolResponse {
+                    pool: Some(Any {
+                        type_url: Pool::TYPE_URL.to_string(),
+                        value: pool.encode_to_vec(),
+                    }),
+                })?
+            }
+            "/osmosis.gamm.v1beta1.Query/PoolType" => {
+                let req = QueryPoolTypeRequest::try_from(data)?;
+                load_pool(storage, req.pool_id)?;
+                to_binary(&QueryPoolTypeResponse {
+                    pool_type: "Balancer".to_string(),
+                })?
+            }
+            "/osmosis.gamm.v1beta1.Query/NumPools" => to_binary(&QueryNumPoolsResponse {
+                num_pools: POOL_COUNT.may_load(storage)?.unwrap_or_default(),
+            })?,
+            "/osmosis.gamm.v1beta1.Query/TotalPoolLiquidity" => {
+                let req = QueryTotalPoolLiquidityRequest::try_from(data)?;
+                to_binary(&QueryTotalPoolLiquidityResponse {
+                    liquidity: load_pool(storage, req.pool_id)?
+                        .assets
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                })?
+            }
+            "/osmosis.gamm.v1beta1.Query/EstimateSwapExactAmountIn" => {
+                let req = QuerySwapExactAmountInRequest::try_from(data)?;
+                let mut token = req.token_in.parse::<Coin>()?;
+                for route in req.routes.iter() {
+                    token = load_pool(storage, route.pool_id)?
+                        .swap_exact_amount_in(&token, &route.token_out_denom)?;
+                }
+                to_binary(&QuerySwapExactAmountInResponse {
+                    token_out_amount: token.amount.to_string(),
+                })?
+            }
+            "/osmosis.gamm.v2.Query/SpotPrice" => {
+                let req = QuerySpotPriceRequest::try_from(data)?;
+                let price = load_pool(storage, req.pool_id)?
+                    .spot_price(&req.base_asset_denom, &req.quote_asset_denom)?;
+                to_binary(&QuerySpotPriceResponse {
+                    spot_price: to_sdk_dec(price),
+                })?
+            }
+            "/osmosis.twap.v1beta1.Query/ArithmeticTwap" => {
+                let req = ArithmeticTwapRequest::try_from(data)?;
+                let end = req
+                    .end_time
+                    .map(to_timestamp)
+                    .transpose()?
+                    .unwrap_or(block.time);
+                let twap = query_twap(
+                    storage,
+                    req.pool_id,
+                    &req.base_asset,
+                    &req.quote_asset,
+                    req.start_time,
+                    end,
+                )?;
+                to_binary(&ArithmeticTwapResponse {
+                    arithmetic_twap: to_sdk_dec(twap),
+                })?
+            }
+            "/osmosis.twap.v1beta1.Query/ArithmeticTwapToNow" => {
+                let req = ArithmeticTwapToNowRequest::try_from(data)?;
+                let twap = query_twap(
+                    storage,
+                    req.pool_id,
+                    &req.base_asset,
+                    &req.quote_asset,
+                    req.start_time,
+                    block.time,
+                )?;
+                to_binary(&ArithmeticTwapToNowResponse {
+                    arithmetic_twap: to_sdk_dec(twap),
+                })?
+            }
+            path => bail!("Unsupported stargate query {}", path),
+        })
+    }
+}
+
+fn ensure_signer(sender: &Addr, signer: &str) -> AnyResult<()> {
+    if sender.as_str() != signer {
+        bail!("{} can't sign for {}", sender, signer);
+    }
+    Ok(())
+}
+
+fn create_balancer_pool<ExecC, QueryC>(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+    block: &BlockInfo,
+    sender: Addr,
+    msg: MsgCreateBalancerPool,
+) -> AnyResult<AppResponse>
+where
+    QueryC: CustomQuery + DeserializeOwned + 'static,
+{
+    if msg.pool_assets.len() < 2 {
+        bail!("A pool needs at least 2 assets");
+    }
+    let params = msg.pool_params.unwrap_or_default();
+    let (assets, weights): (Vec<Coin>, Vec<Uint128>) = msg
+        .pool_assets
+        .into_iter()
+        .map(|asset| {
+            Ok((
+                from_proto_coin(asset.token)?,
+                asset.weight.parse::<Uint128>()?,
+            ))
+        })
+        .collect::<AnyResult<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+
+    let pool_id = POOL_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    POOL_COUNT.save(storage, &pool_id)?;
+    let pool = BalancerPool {
+        id: pool_id,
+        assets: assets.clone(),
+        weights,
+        swap_fee: parse_dec(&params.swap_fee)?,
+        exit_fee: parse_dec(&params.exit_fee)?,
+        total_shares: coin(INIT_POOL_SHARES, format!("gamm/pool/{}", pool_id)),
+    };
+    save_pool(storage, block.time, &pool)?;
+
+    let mut res = router.execute(
+        api,
+        storage,
+        block,
+        sender.clone(),
+        BankMsg::Send {
+            to_address: pool.address().into_string(),
+            amount: sort_coins(assets),
+        }
+        .into(),
+    )?;
+    res.events.extend(
+        router
+            .sudo(
+                api,
+                storage,
+                block,
+                SudoMsg::Bank(BankSudo::Mint {
+                    to_address: sender.into_string(),
+                    amount: vec![pool.total_shares.clone()],
+                }),
+            )?
+            .events,
+    );
+    res.events
+        .push(Event::new("pool_created").add_attribute("pool_id", pool_id.to_string()));
+    res.data = Some(MsgCreateBalancerPoolResponse { pool_id }.into());
+    Ok(res)
+}
+
+// routes run one after the other, each pool emitting gamm's `token_swapped`
+fn swap_exact_amount_in<ExecC, QueryC>(
+    api: &dyn Api,
+    storage: &mut dyn Storage,
+    router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+    block: &BlockInfo,
+    sender: Addr,
+    msg: MsgSwapExactAmountIn,
+) -> AnyResult<AppResponse>
+where
+    QueryC: CustomQuery + DeserializeOwned + 'static,
+{
+    let token_out_min_amount: Uint128 = msg.token_out_min_amount.parse()?;
+    let mut token_in = from_proto_coin(msg.token_in)?;
+    let mut res = AppResponse::default();
+    for SwapAmountInRoute {
+        pool_id,
+        token_out_denom,
+    } in msg.routes
+    {
+        let mut pool = load_pool(storage, pool_id)?;
+        let token_out = pool.swap_exact_amount_in(&token_in, &token_out_denom)?;
+        save_pool(storage, block.time, &pool)?;
+
+        for (from, to, token) in [
+            (sender.clone(), pool_address(pool_id), token_in.clone()),
+            (pool_address(pool_id), sender.clone(), token_out.clone()),
+        ] {
+            let sent = router.execute(
+                api,
+                storage,
+                block,
+                from,
+                BankMsg::Send {
+                    to_address: to.into_string(),
+                    amount: vec![token],
+                }
+                .into(),
+            )?;
+            res.events.extend(sent.events);
+        }
+        res.events.push(
+            Event::new("token_swapped")
+                .add_attribute("module", "gamm")
+                .add_attribute("sender", sender.as_str())
+                .add_attribute("pool_id", pool_id.to_string())
+                .add_attribute("tokens_in", token_in.to_string())
+                .add_attribute("tokens_out", token_out.to_string()),
+        );
+        token_in = token_out;
+    }
+
+    if token_in.amount < token_out_min_amount {
+        bail!(
+            "{} is less than the minimum amount {}",
+            token_in,
+            token_out_min_amount
+        );
+    }
+    res.data = Some(
+        MsgSwapExactAmountInResponse {
+            token_out_amount: token_in.amount.to_string(),
+        }
+        .into(),
+    );
+    Ok(res)
+}
+
+fn query_twap(
+    storage: &dyn Storage,
+    pool_id: u64,
+    base_asset: &str,
+    quote_asset: &str,
+    start_time: Option<ShimTimestamp>,
+    end: Timestamp,
+) -> AnyResult<Decimal> {
+    let start = match start_time {
+        Some(start_time) => to_timestamp(start_time)?,
+        None => bail!("TWAP start time is required"),
+    };
+    arithmetic_twap(
+        storage,
+        &load_pool(storage, pool_id)?,
+        base_asset,
+        quote_asset,
+        start,
+        end,
+    )
+}
+
+fn to_timestamp(time: ShimTimestamp) -> AnyResult<Timestamp> {
+    Ok(
+        Timestamp::from_seconds(u64::try_from(time.seconds)?)
+            .plus_nanos(u64::try_from(time.nanos)?),
+    )
+}
+
+// bank sends need coins sorted by denom
+fn sort_coins(mut coins: Vec<Coin>) -> Vec<Coin> {
+    coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+    coins
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coin, Addr, Uint128};
+    use cw_multi_test::{AppBuilder, Executor};
+    use osmosis_std::shim::Timestamp;
+    use osmosis_std::types::osmosis::gamm::poolmodels::balancer::v1beta1::{
+        MsgCreateBalancerPool, MsgCreateBalancerPoolResponse,
+    };
+    use osmosis_std::types::osmosis::gamm::v1beta1::{
+        GammQuerier, MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, Pool, PoolAsset,
+        PoolParams, SwapAmountInRoute,
+    };
+    use osmosis_std::types::osmosis::gamm::v2::GammQuerier as GammV2Querier;
+    use osmosis_std::types::osmosis::twap::v1beta1::TwapQuerier;
+
+    use crate::{pool_address, OsmosisStargate};
+
+    fn pool_asset(amount: u128, denom: &str) -> PoolAsset {
+        PoolAsset {
+            token: Some(coin(amount, denom).into()),
+            weight: "1000000".to_string(),
+        }
+    }
+
+    #[test]
+    fn gamm_and_twap_integration() {
+        let owner = Addr::unchecked("owner");
+        let mut app = AppBuilder::new()
+            .with_stargate(OsmosisStargate::default())
+            .build(|router, _api, storage| {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        &owner,
+                        vec![coin(2_000_000, "uatom"), coin(2_000_000, "uosmo")],
+                    )
+                    .unwrap();
+            });
+        let start = app.block_info().time;
+
+        // create a 1uatom:2uosmo pool with a 1% swap fee
+        let res = app
+            .execute(
+                owner.clone(),
+                MsgCreateBalancerPool {
+                    sender: owner.to_string(),
+                    pool_params: Some(PoolParams {
+                        swap_fee: "10000000000000000".to_string(),
+                        exit_fee: "0".to_string(),
+                        smooth_weight_change_params: None,
+                    }),
+                    pool_assets: vec![
+                        pool_asset(1_000_000, "uatom"),
+                        pool_asset(2_000_000, "uosmo"),
+                    ],
+                    future_pool_governor: String::new(),
+                }
+                .into(),
+            )
+            .unwrap();
+        let pool_id = MsgCreateBalancerPoolResponse::try_from(res.data.unwrap())
+            .unwrap()
+            .pool_id;
+        assert_eq!(pool_id, 1);
+        assert_eq!(
+            app.wrap()
+                .query_balance(pool_address(pool_id), "uosmo")
+                .unwrap()
+                .amount,
+            Uint128::new(2_000_000)
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(&owner, "gamm/pool/1")
+                .unwrap()
+                .amount,
+            Uint128::new(100_000_000_000_000_000_000)
+        );
+
+        // pool query decodes into gamm's balancer pool
+        let pool: Pool = GammQuerier::new(&app.wrap())
+            .pool(pool_id)
+            .unwrap()
+            .pool
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            pool.pool_assets[1].token,
+            Some(coin(2_000_000, "uosmo").into())
+        );
+        assert_eq!(pool.pool_params.unwrap().swap_fee, "0.010000000000000000");
+
+        // spot price
+        let spot_price = GammV2Querier::new(&app.wrap())
+            .spot_price(pool_id, "uatom".to_string(), "uosmo".to_string())
+            .unwrap()
+            .spot_price;
+        assert_eq!(spot_price, "2.000000000000000000");
+
+        // swap 100s later, 9_900uatom are left after the fee
+        app.update_block(|block| {
+            block.height += 20;
+            block.time = block.time.plus_seconds(100);
+        });
+        let res = app
+            .execute(
+                owner.clone(),
+                MsgSwapExactAmountIn {
+                    sender: owner.to_string(),
+                    routes: vec![SwapAmountInRoute {
+                        pool_id,
+                        token_out_denom: "uosmo".to_string(),
+                    }],
+                    token_in: Some(coin(10_000, "uatom").into()),
+                    token_out_min_amount: "1".to_string(),
+                }
+                .into(),
+            )
+            .unwrap();
+        let token_out_amount = MsgSwapExactAmountInResponse::try_from(res.data.unwrap())
+            .unwrap()
+            .token_out_amount;
+        assert_eq!(token_out_amount, "19605");
+
+        let swapped = res.events.iter().find(|e| e.ty == "token_swapped").unwrap();
+        let attribute = |key: &str| {
+            swapped
+                .attributes
+                .iter()
+                .find(|a| a.key == key)
+                .unwrap()
+                .value
+                .to_owned()
+        };
+        assert_eq!(attribute("module"), "gamm");
+        assert_eq!(attribute("pool_id"), "1");
+        assert_eq!(attribute("tokens_in"), "10000uatom");
+        assert_eq!(attribute("tokens_out"), "19605uosmo");
+        assert_eq!(
+            app.wrap().query_balance(&owner, "uosmo").unwrap().amount,
+            Uint128::new(19_605)
+        );
+
+        // minimum amount out isn't met
+        let err = app
+            .execute(
+                owner.clone(),
+                MsgSwapExactAmountIn {
+                    sender: owner.to_string(),
+                    routes: vec![SwapAmountInRoute {
+                        pool_id,
+                        token_out_denom: "uosmo".to_string(),
+                    }],
+                    token_in: Some(coin(10_000, "uatom").into()),
+                    token_out_min_amount: "1000000".to_string(),
+                }
+                .into(),
+            )
+            .unwrap_err();
+        assert!(err
+            .root_cause()
+            .to_string()
+            .contains("less than the minimum amount"));
+
+        // half of the window at 2, half at 1_980_395 / 1_010_000
+        app.update_block(|block| {
+            block.height += 20;
+            block.time = block.time.plus_seconds(100);
+        });
+        let twap = TwapQuerier::new(&app.wrap())
+            .arithmetic_twap_to_now(
+                pool_id,
+                "uatom".to_string(),
+                "uosmo".to_string(),
+                Some(Timestamp {
+                    seconds: start.seconds() as i64,
+                    nanos: start.subsec_nanos() as i32,
+                }),
+            )
+            .unwrap()
+            .arithmetic_twap;
+        assert_eq!(twap, "1.980393564356435643");
+
+        // before the pool existed
+        TwapQuerier::new(&app.wrap())
+            .arithmetic_twap_to_now(
+                pool_id,
+                "uatom".to_string(),
+                "uosmo".to_string(),
+                Some(Timestamp {
+                    seconds: start.seconds() as i64 - 1,
+                    nanos: 0,
+                }),
+            )
+            .unwrap_err();
+    }
+}
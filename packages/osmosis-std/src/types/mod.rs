@@ -1,2 +1,3 @@
 pub mod cosmos;
+pub mod ibc;
 pub mod osmosis;
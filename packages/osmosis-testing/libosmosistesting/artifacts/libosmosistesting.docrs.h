@@ -78,6 +78,14 @@ extern GoUint64 InitTestEnv();
 extern char* InitAccount(GoUint64 envId, GoString coinsJson);
 extern void BeginBlock(GoUint64 envId);
 extern void EndBlock(GoUint64 envId);
+extern void IncreaseTime(GoUint64 envId, GoInt64 seconds);
+extern char* AdvanceEpoch(GoUint64 envId, GoString epochIdentifier);
+extern GoInt64 GetBlockTime(GoUint64 envId);
+extern GoInt64 GetBlockHeight(GoUint64 envId);
+extern GoUint64 Snapshot(GoUint64 envId);
+extern void Restore(GoUint64 envId, GoUint64 snapshotId);
+extern GoUint64 InitTestEnvFromSnapshot(GoUint64 snapshotId);
+extern void ReleaseSnapshot(GoUint64 snapshotId);
 extern char* Execute(GoUint64 envId, GoString base64ReqDeliverTx);
 extern char* Query(GoUint64 envId, GoString path, GoString base64QueryMsgBytes);
 extern GoUint64 AccountSequence(GoUint64 envId, GoString bech32Address);
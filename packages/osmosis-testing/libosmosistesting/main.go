@@ -17,6 +17,7 @@ import (
 	// tendermint
 	abci "github.com/tendermint/tendermint/abci/types"
 	tmproto "github.com/tendermint/tendermint/proto/tendermint/types"
+	dbm "github.com/tendermint/tm-db"
 
 	// cosmos sdk
 	"github.com/cosmos/cosmos-sdk/crypto/keys/secp256k1"
@@ -32,9 +33,11 @@ import (
 )
 
 var (
-	envCounter  uint64 = 0
-	envRegister        = sync.Map{}
-	mu          sync.Mutex
+	envCounter       uint64 = 0
+	envRegister             = sync.Map{}
+	snapshotCounter  uint64 = 0
+	snapshotRegister        = sync.Map{}
+	mu               sync.Mutex
 )
 
 //export InitTestEnv
@@ -46,15 +49,13 @@ func InitTestEnv() uint64 {
 	defer mu.Unlock()
 
 	env := new(testenv.TestEnv)
-	env.App = testenv.SetupOsmosisApp()
+	env.DB = dbm.NewMemDB()
+	env.App = testenv.SetupOsmosisApp(env.DB)
 
 	env.Ctx = env.App.BaseApp.NewContext(false, tmproto.Header{Height: 0, ChainID: "osmosis-1", Time: time.Now().UTC()})
 
 	env.BeginNewBlock(false)
-
-	reqEndBlock := abci.RequestEndBlock{Height: env.Ctx.BlockHeight()}
-	env.App.EndBlock(reqEndBlock)
-	env.App.Commit()
+	env.EndBlock()
 
 	envCounter += 1
 	id := envCounter
@@ -98,12 +99,93 @@ func BeginBlock(envId uint64) {
 //export EndBlock
 func EndBlock(envId uint64) {
 	env := loadEnv(envId)
-	reqEndBlock := abci.RequestEndBlock{Height: env.Ctx.BlockHeight()}
-	env.App.EndBlock(reqEndBlock)
-	env.App.Commit()
+	env.EndBlock()
+	envRegister.Store(envId, env)
+}
+
+//export IncreaseTime
+func IncreaseTime(envId uint64, seconds int64) {
+	env := loadEnv(envId)
+	env.BeginNewBlockAt(env.Ctx.BlockTime().Add(time.Duration(seconds) * time.Second))
+	env.EndBlock()
+	envRegister.Store(envId, env)
+}
+
+//export AdvanceEpoch
+func AdvanceEpoch(envId uint64, epochIdentifier string) *C.char {
+	env := loadEnv(envId)
+
+	epoch := env.App.EpochsKeeper.GetEpochInfo(env.Ctx, epochIdentifier)
+	if epoch.Identifier == "" {
+		err := errors.New("No epoch found for `" + epochIdentifier + "`")
+		return encodeErrToResultBytes(result.ExecuteError, err)
+	}
+
+	// the epoch ends in the first block past its duration
+	env.BeginNewBlockAt(env.Ctx.BlockTime().Add(epoch.Duration).Add(time.Second))
+	env.EndBlock()
+	envRegister.Store(envId, env)
+
+	return encodeBytesResultBytes([]byte{})
+}
+
+//export GetBlockTime
+func GetBlockTime(envId uint64) int64 {
+	env := loadEnv(envId)
+	return env.Ctx.BlockTime().UnixNano()
+}
+
+//export GetBlockHeight
+func GetBlockHeight(envId uint64) int64 {
+	env := loadEnv(envId)
+	return env.Ctx.BlockHeight()
+}
+
+//export Snapshot
+func Snapshot(envId uint64) uint64 {
+	env := loadEnv(envId)
+	mu.Lock()
+	defer mu.Unlock()
+
+	snapshotCounter += 1
+	id := snapshotCounter
+
+	snapshotRegister.Store(id, env.Snapshot())
+
+	return id
+}
+
+//export Restore
+func Restore(envId uint64, snapshotId uint64) {
+	env := loadEnv(envId)
+	mu.Lock()
+	defer mu.Unlock()
+
+	env.Restore(loadSnapshot(snapshotId))
+
 	envRegister.Store(envId, env)
 }
 
+//export InitTestEnvFromSnapshot
+func InitTestEnvFromSnapshot(snapshotId uint64) uint64 {
+	mu.Lock()
+	defer mu.Unlock()
+
+	env := testenv.NewTestEnvFromSnapshot(loadSnapshot(snapshotId))
+
+	envCounter += 1
+	id := envCounter
+
+	envRegister.Store(id, env)
+
+	return id
+}
+
+//export ReleaseSnapshot
+func ReleaseSnapshot(snapshotId uint64) {
+	snapshotRegister.Delete(snapshotId)
+}
+
 //export Execute
 func Execute(envId uint64, base64ReqDeliverTx string) *C.char {
 	env := loadEnv(envId)
@@ -229,6 +311,14 @@ func loadEnv(envId uint64) testenv.TestEnv {
 	return env
 }
 
+func loadSnapshot(snapshotId uint64) testenv.Snapshot {
+	item, ok := snapshotRegister.Load(snapshotId)
+	if !ok {
+		panic(fmt.Sprintf("snapshot not found: %d", snapshotId))
+	}
+	return item.(testenv.Snapshot)
+}
+
 func encodeErrToResultBytes(code byte, err error) *C.char {
 	return C.CString(result.EncodeResultFromError(code, err))
 }
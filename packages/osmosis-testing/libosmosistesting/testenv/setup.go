@@ -35,6 +35,7 @@ import (
 type TestEnv struct {
 	App *app.OsmosisApp
 	Ctx sdk.Context
+	DB  *dbm.MemDB
 }
 
 // DebugAppOptions is a stub implementing AppOptions
@@ -48,9 +49,9 @@ func (ao DebugAppOptions) Get(o string) interface{} {
 	return nil
 }
 
-func SetupOsmosisApp() *app.OsmosisApp {
-	db := dbm.NewMemDB()
-	appInstance := app.NewOsmosisApp(
+// NewOsmosisApp creates an app over db, loading its latest committed state if there is any.
+func NewOsmosisApp(db dbm.DB) *app.OsmosisApp {
+	return app.NewOsmosisApp(
 		log.NewNopLogger(),
 		db,
 		nil,
@@ -62,6 +63,10 @@ func SetupOsmosisApp() *app.OsmosisApp {
 		app.GetWasmEnabledProposals(),
 		app.EmptyWasmOpts,
 	)
+}
+
+func SetupOsmosisApp(db dbm.DB) *app.OsmosisApp {
+	appInstance := NewOsmosisApp(db)
 	genesisState := app.NewDefaultGenesisState()
 
 	encCfg := app.MakeEncodingConfig()
@@ -100,6 +105,18 @@ func SetupOsmosisApp() *app.OsmosisApp {
 }
 
 func (env *TestEnv) BeginNewBlock(executeNextEpoch bool) {
+	newBlockTime := env.Ctx.BlockTime().Add(5 * time.Second)
+	if executeNextEpoch {
+		epochIdentifier := env.App.SuperfluidKeeper.GetEpochIdentifier(env.Ctx)
+		epoch := env.App.EpochsKeeper.GetEpochInfo(env.Ctx, epochIdentifier)
+		newBlockTime = env.Ctx.BlockTime().Add(epoch.Duration).Add(time.Second)
+	}
+
+	env.BeginNewBlockAt(newBlockTime)
+}
+
+// BeginNewBlockAt begins a new block with the given block time.
+func (env *TestEnv) BeginNewBlockAt(newBlockTime time.Time) {
 	var valAddr []byte
 
 	validators := env.App.StakingKeeper.GetAllValidators(env.Ctx)
@@ -114,11 +131,18 @@ func (env *TestEnv) BeginNewBlock(executeNextEpoch bool) {
 		valAddr = valAddr2.Bytes()
 	}
 
-	env.beginNewBlockWithProposer(executeNextEpoch, valAddr)
+	env.beginNewBlockWithProposer(newBlockTime, valAddr)
+}
+
+// EndBlock ends the current block and commits it.
+func (env *TestEnv) EndBlock() {
+	reqEndBlock := abci.RequestEndBlock{Height: env.Ctx.BlockHeight()}
+	env.App.EndBlock(reqEndBlock)
+	env.App.Commit()
 }
 
 // beginNewBlockWithProposer begins a new block with a proposer.
-func (env *TestEnv) beginNewBlockWithProposer(executeNextEpoch bool, proposer sdk.ValAddress) {
+func (env *TestEnv) beginNewBlockWithProposer(newBlockTime time.Time, proposer sdk.ValAddress) {
 	validator, found := env.App.StakingKeeper.GetValidator(env.Ctx, proposer)
 
 	if !found {
@@ -130,13 +154,6 @@ func (env *TestEnv) beginNewBlockWithProposer(executeNextEpoch bool, proposer sd
 
 	valAddr := valConsAddr.Bytes()
 
-	epochIdentifier := env.App.SuperfluidKeeper.GetEpochIdentifier(env.Ctx)
-	epoch := env.App.EpochsKeeper.GetEpochInfo(env.Ctx, epochIdentifier)
-	newBlockTime := env.Ctx.BlockTime().Add(5 * time.Second)
-	if executeNextEpoch {
-		newBlockTime = env.Ctx.BlockTime().Add(epoch.Duration).Add(time.Second)
-	}
-
 	header := tmtypes.Header{ChainID: "osmosis-1", Height: env.Ctx.BlockHeight() + 1, Time: newBlockTime}
 	newCtx := env.Ctx.WithBlockTime(newBlockTime).WithBlockHeight(env.Ctx.BlockHeight() + 1)
 	env.Ctx = newCtx
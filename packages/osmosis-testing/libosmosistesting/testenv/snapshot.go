@@ -0,0 +1,54 @@
+package testenv
+
+import (
+	// tendermint
+	tmproto "github.com/tendermint/tendermint/proto/tendermint/types"
+	dbm "github.com/tendermint/tm-db"
+)
+
+// Snapshot is a copy of a TestEnv's committed state, along with the header of its last block.
+type Snapshot struct {
+	DB     *dbm.MemDB
+	Header tmproto.Header
+}
+
+// Snapshot copies the env's committed state. Must be taken between blocks.
+func (env *TestEnv) Snapshot() Snapshot {
+	return Snapshot{
+		DB:     copyDB(env.DB),
+		Header: env.Ctx.BlockHeader(),
+	}
+}
+
+// Restore replaces the env's app with one loaded from a copy of the snapshot,
+// so that the snapshot can be restored again.
+func (env *TestEnv) Restore(snapshot Snapshot) {
+	env.DB = copyDB(snapshot.DB)
+	env.App = NewOsmosisApp(env.DB)
+
+	// no block is running, the next one begins from the snapshot's last block
+	env.Ctx = env.App.NewContext(true, snapshot.Header)
+}
+
+func NewTestEnvFromSnapshot(snapshot Snapshot) TestEnv {
+	env := TestEnv{}
+	env.Restore(snapshot)
+	return env
+}
+
+func copyDB(db *dbm.MemDB) *dbm.MemDB {
+	dst := dbm.NewMemDB()
+
+	itr, err := db.Iterator(nil, nil)
+	requireNoErr(err)
+	defer itr.Close()
+
+	for ; itr.Valid(); itr.Next() {
+		key := append([]byte{}, itr.Key()...)
+		value := append([]byte{}, itr.Value()...)
+		requireNoErr(dst.Set(key, value))
+	}
+	requireNoErr(itr.Error())
+
+	return dst
+}
@@ -12,7 +12,7 @@ pub use osmosis_std;
 
 pub use account::{Account, NonSigningAccount, SigningAccount};
 pub use module::*;
-pub use runner::app::OsmosisTestApp;
+pub use runner::app::{OsmosisTestApp, Snapshot};
 pub use runner::error::{DecodeError, EncodeError, RunnerError};
 pub use runner::result::{ExecuteResponse, RunnerExecuteResult, RunnerResult};
 pub use runner::Runner;
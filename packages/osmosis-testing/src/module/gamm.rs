@@ -2,6 +2,10 @@ use cosmwasm_std::Coin;
 use osmosis_std::types::osmosis::gamm;
 use osmosis_std::types::osmosis::gamm::{
     poolmodels::balancer::v1beta1::{MsgCreateBalancerPool, MsgCreateBalancerPoolResponse},
+    poolmodels::stableswap::v1beta1::{
+        MsgCreateStableswapPool, MsgCreateStableswapPoolResponse,
+        PoolParams as StableswapPoolParams,
+    },
     v1beta1::{PoolAsset, PoolParams, QueryPoolRequest, QueryPoolResponse},
 };
 use prost::Message;
@@ -33,6 +37,10 @@ where
         pub create_balancer_pool: MsgCreateBalancerPool => MsgCreateBalancerPoolResponse
     }
 
+    fn_execute! {
+        pub create_stableswap_pool: MsgCreateStableswapPool => MsgCreateStableswapPoolResponse
+    }
+
     fn_query! {
         _query_pool ["/osmosis.gamm.v1beta1.Query/Pool"]: QueryPoolRequest => QueryPoolResponse
     }
@@ -66,6 +74,36 @@ where
         )
     }
 
+    pub fn create_basic_stableswap_pool(
+        &self,
+        initial_liquidity: &[Coin],
+        signer: &SigningAccount,
+    ) -> RunnerExecuteResult<MsgCreateStableswapPoolResponse> {
+        let mut initial_liquidity = initial_liquidity.to_vec();
+        initial_liquidity.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+        self.create_stableswap_pool(
+            MsgCreateStableswapPool {
+                sender: signer.address(),
+                pool_params: Some(StableswapPoolParams {
+                    swap_fee: "10000000000000000".to_string(),
+                    exit_fee: "10000000000000000".to_string(),
+                }),
+                scaling_factors: vec![1; initial_liquidity.len()],
+                initial_pool_liquidity: initial_liquidity
+                    .into_iter()
+                    .map(|c| osmosis_std::types::cosmos::base::v1beta1::Coin {
+                        denom: c.denom,
+                        amount: format!("{}", c.amount),
+                    })
+                    .collect(),
+                future_pool_governor: "".to_string(),
+                scaling_factor_controller: "".to_string(),
+            },
+            signer,
+        )
+    }
+
     pub fn query_pool(&self, pool_id: u64) -> RunnerResult<gamm::v1beta1::Pool> {
         let res = self._query_pool(&QueryPoolRequest { pool_id })?;
         gamm::v1beta1::Pool::decode(res.pool.unwrap().value.as_slice())
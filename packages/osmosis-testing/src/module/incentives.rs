@@ -0,0 +1,148 @@
+use osmosis_std::types::osmosis::incentives::{
+    ActiveGaugesRequest, ActiveGaugesResponse, GaugeByIdRequest, GaugeByIdResponse, GaugesRequest,
+    GaugesResponse, ModuleToDistributeCoinsRequest, ModuleToDistributeCoinsResponse, MsgAddToGauge,
+    MsgAddToGaugeResponse, MsgCreateGauge, MsgCreateGaugeResponse, QueryLockableDurationsRequest,
+    QueryLockableDurationsResponse, RewardsEstRequest, RewardsEstResponse,
+};
+
+use crate::module::Module;
+use crate::runner::Runner;
+use crate::{fn_execute, fn_query};
+
+pub struct Incentives<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for Incentives<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> Incentives<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_execute! {
+        pub create_gauge: MsgCreateGauge => MsgCreateGaugeResponse
+    }
+
+    fn_execute! {
+        pub add_to_gauge: MsgAddToGauge => MsgAddToGaugeResponse
+    }
+
+    fn_query! {
+        pub query_gauge_by_id ["/osmosis.incentives.Query/GaugeByID"]: GaugeByIdRequest => GaugeByIdResponse
+    }
+
+    fn_query! {
+        pub query_gauges ["/osmosis.incentives.Query/Gauges"]: GaugesRequest => GaugesResponse
+    }
+
+    fn_query! {
+        pub query_active_gauges ["/osmosis.incentives.Query/ActiveGauges"]: ActiveGaugesRequest => ActiveGaugesResponse
+    }
+
+    fn_query! {
+        pub query_module_to_distribute_coins ["/osmosis.incentives.Query/ModuleToDistributeCoins"]: ModuleToDistributeCoinsRequest => ModuleToDistributeCoinsResponse
+    }
+
+    fn_query! {
+        pub query_rewards_est ["/osmosis.incentives.Query/RewardsEst"]: RewardsEstRequest => RewardsEstResponse
+    }
+
+    fn_query! {
+        pub query_lockable_durations ["/osmosis.incentives.Query/LockableDurations"]: QueryLockableDurationsRequest => QueryLockableDurationsResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Coin;
+    use osmosis_std::shim::{Duration, Timestamp};
+    use osmosis_std::types::osmosis::incentives::{
+        GaugeByIdRequest, MsgAddToGauge, MsgCreateGauge,
+    };
+    use osmosis_std::types::osmosis::lockup::{LockQueryType, QueryCondition};
+
+    use crate::{Account, Incentives, Module, OsmosisTestApp};
+
+    #[test]
+    fn incentives_integration() {
+        let app = OsmosisTestApp::new();
+        // enough to cover gauge creation and top up fees
+        let signer = app
+            .init_account(&[Coin::new(1_000_000_000_000, "uosmo")])
+            .unwrap();
+        let incentives = Incentives::new(&app);
+        let start_time = app.get_block_time();
+
+        // create gauge rewarding a day long locks of uosmo
+        let res = incentives
+            .create_gauge(
+                MsgCreateGauge {
+                    is_perpetual: false,
+                    owner: signer.address(),
+                    distribute_to: Some(QueryCondition {
+                        lock_query_type: LockQueryType::ByDuration as i32,
+                        denom: "uosmo".to_string(),
+                        duration: Some(Duration {
+                            seconds: 86400,
+                            nanos: 0,
+                        }),
+                        timestamp: None,
+                    }),
+                    coins: vec![Coin::new(1_000_000, "uosmo").into()],
+                    start_time: Some(Timestamp {
+                        seconds: start_time.seconds() as i64,
+                        nanos: start_time.subsec_nanos() as i32,
+                    }),
+                    num_epochs_paid_over: 10,
+                },
+                &signer,
+            )
+            .unwrap();
+        let gauge_id = res
+            .events
+            .iter()
+            .find(|e| e.ty == "create_gauge")
+            .unwrap()
+            .attributes
+            .iter()
+            .find(|a| a.key == "gauge_id")
+            .unwrap()
+            .value
+            .parse::<u64>()
+            .unwrap();
+
+        let gauge = incentives
+            .query_gauge_by_id(&GaugeByIdRequest { id: gauge_id })
+            .unwrap()
+            .gauge
+            .unwrap();
+
+        assert_eq!(gauge.coins, [Coin::new(1_000_000, "uosmo").into()]);
+        assert_eq!(gauge.num_epochs_paid_over, 10);
+        assert!(!gauge.is_perpetual);
+
+        // add to gauge
+        incentives
+            .add_to_gauge(
+                MsgAddToGauge {
+                    owner: signer.address(),
+                    gauge_id,
+                    rewards: vec![Coin::new(500_000, "uosmo").into()],
+                },
+                &signer,
+            )
+            .unwrap();
+
+        let gauge = incentives
+            .query_gauge_by_id(&GaugeByIdRequest { id: gauge_id })
+            .unwrap()
+            .gauge
+            .unwrap();
+
+        assert_eq!(gauge.coins, [Coin::new(1_500_000, "uosmo").into()]);
+    }
+}
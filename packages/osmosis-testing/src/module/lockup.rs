@@ -0,0 +1,201 @@
+use osmosis_std::types::osmosis::lockup::{
+    AccountLockedCoinsRequest, AccountLockedCoinsResponse, AccountLockedLongerDurationRequest,
+    AccountLockedLongerDurationResponse, AccountUnlockableCoinsRequest,
+    AccountUnlockableCoinsResponse, AccountUnlockingCoinsRequest, AccountUnlockingCoinsResponse,
+    LockedRequest, LockedResponse, ModuleBalanceRequest, ModuleBalanceResponse,
+    ModuleLockedAmountRequest, ModuleLockedAmountResponse, MsgBeginUnlocking, MsgBeginUnlockingAll,
+    MsgBeginUnlockingAllResponse, MsgBeginUnlockingResponse, MsgExtendLockup,
+    MsgExtendLockupResponse, MsgLockTokens, MsgLockTokensResponse, QueryParamsRequest,
+    QueryParamsResponse,
+};
+
+use crate::module::Module;
+use crate::runner::Runner;
+use crate::{fn_execute, fn_query};
+
+pub struct Lockup<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for Lockup<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> Lockup<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_execute! {
+        pub lock_tokens: MsgLockTokens => MsgLockTokensResponse
+    }
+
+    fn_execute! {
+        pub begin_unlocking: MsgBeginUnlocking => MsgBeginUnlockingResponse
+    }
+
+    fn_execute! {
+        pub begin_unlocking_all: MsgBeginUnlockingAll => MsgBeginUnlockingAllResponse
+    }
+
+    fn_execute! {
+        pub extend_lockup: MsgExtendLockup => MsgExtendLockupResponse
+    }
+
+    fn_query! {
+        pub query_params ["/osmosis.lockup.Query/Params"]: QueryParamsRequest => QueryParamsResponse
+    }
+
+    fn_query! {
+        pub query_module_balance ["/osmosis.lockup.Query/ModuleBalance"]: ModuleBalanceRequest => ModuleBalanceResponse
+    }
+
+    fn_query! {
+        pub query_module_locked_amount ["/osmosis.lockup.Query/ModuleLockedAmount"]: ModuleLockedAmountRequest => ModuleLockedAmountResponse
+    }
+
+    fn_query! {
+        pub query_account_locked_coins ["/osmosis.lockup.Query/AccountLockedCoins"]: AccountLockedCoinsRequest => AccountLockedCoinsResponse
+    }
+
+    fn_query! {
+        pub query_account_unlocking_coins ["/osmosis.lockup.Query/AccountUnlockingCoins"]: AccountUnlockingCoinsRequest => AccountUnlockingCoinsResponse
+    }
+
+    fn_query! {
+        pub query_account_unlockable_coins ["/osmosis.lockup.Query/AccountUnlockableCoins"]: AccountUnlockableCoinsRequest => AccountUnlockableCoinsResponse
+    }
+
+    fn_query! {
+        pub query_account_locked_longer_duration ["/osmosis.lockup.Query/AccountLockedLongerDuration"]: AccountLockedLongerDurationRequest => AccountLockedLongerDurationResponse
+    }
+
+    fn_query! {
+        pub query_locked_by_id ["/osmosis.lockup.Query/LockedByID"]: LockedRequest => LockedResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::cosmos::bank::v1beta1::QueryBalanceRequest;
+    use cosmwasm_std::Coin;
+    use osmosis_std::shim::Duration;
+    use osmosis_std::types::osmosis::lockup::{
+        AccountLockedCoinsRequest, AccountUnlockingCoinsRequest, LockedRequest, MsgBeginUnlocking,
+        MsgLockTokens,
+    };
+
+    use crate::{Account, Bank, Gamm, Lockup, Module, OsmosisTestApp};
+
+    #[test]
+    fn lockup_integration() {
+        let app = OsmosisTestApp::new();
+        let signer = app
+            .init_account(&[
+                Coin::new(1_000_000_000_000, "uatom"),
+                Coin::new(1_000_000_000_000, "uosmo"),
+            ])
+            .unwrap();
+        let gamm = Gamm::new(&app);
+        let lockup = Lockup::new(&app);
+        let bank = Bank::new(&app);
+
+        let pool_id = gamm
+            .create_basic_pool(
+                &[Coin::new(1_000_000, "uatom"), Coin::new(2_000_000, "uosmo")],
+                &signer,
+            )
+            .unwrap()
+            .data
+            .pool_id;
+        let shares: osmosis_std::types::cosmos::base::v1beta1::Coin =
+            Coin::new(1_000_000_000_000_000_000, format!("gamm/pool/{}", pool_id)).into();
+
+        // lock shares for a day
+        let lock_id = lockup
+            .lock_tokens(
+                MsgLockTokens {
+                    owner: signer.address(),
+                    duration: Some(Duration {
+                        seconds: 86400,
+                        nanos: 0,
+                    }),
+                    coins: vec![shares.clone()],
+                },
+                &signer,
+            )
+            .unwrap()
+            .data
+            .id;
+
+        let locked = lockup
+            .query_account_locked_coins(&AccountLockedCoinsRequest {
+                owner: signer.address(),
+            })
+            .unwrap()
+            .coins;
+
+        assert_eq!(locked, vec![shares.clone()]);
+
+        // query lock
+        let lock = lockup
+            .query_locked_by_id(&LockedRequest { lock_id })
+            .unwrap()
+            .lock
+            .unwrap();
+
+        assert_eq!(lock.owner, signer.address());
+        assert_eq!(lock.coins, vec![shares.clone()]);
+        assert_eq!(lock.duration.unwrap().seconds, 86400);
+
+        // begin unlocking the whole lock
+        let success = lockup
+            .begin_unlocking(
+                MsgBeginUnlocking {
+                    owner: signer.address(),
+                    id: lock_id,
+                    coins: vec![],
+                },
+                &signer,
+            )
+            .unwrap()
+            .data
+            .success;
+
+        assert!(success);
+
+        let unlocking = lockup
+            .query_account_unlocking_coins(&AccountUnlockingCoinsRequest {
+                owner: signer.address(),
+            })
+            .unwrap()
+            .coins;
+
+        assert_eq!(unlocking, vec![shares.clone()]);
+
+        // lock is withdrawn back to its owner once the day has passed
+        app.increase_time(86400);
+        app.skip_blocks(1);
+
+        let unlocking = lockup
+            .query_account_unlocking_coins(&AccountUnlockingCoinsRequest {
+                owner: signer.address(),
+            })
+            .unwrap()
+            .coins;
+
+        assert!(unlocking.is_empty());
+
+        let balance = bank
+            .query_balance(&QueryBalanceRequest {
+                address: signer.address(),
+                denom: shares.denom,
+            })
+            .unwrap()
+            .balance
+            .unwrap();
+
+        assert_eq!(balance.amount, "100000000000000000000");
+    }
+}
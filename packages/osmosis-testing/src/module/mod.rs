@@ -2,7 +2,13 @@ use crate::runner::Runner;
 
 mod bank;
 mod gamm;
+mod incentives;
+mod lockup;
+mod pool_incentives;
+mod superfluid;
+mod swaprouter;
 mod tokenfactory;
+mod twap;
 mod wasm;
 
 #[macro_use]
@@ -10,7 +16,13 @@ pub mod macros;
 
 pub use bank::Bank;
 pub use gamm::Gamm;
+pub use incentives::Incentives;
+pub use lockup::Lockup;
+pub use pool_incentives::PoolIncentives;
+pub use superfluid::Superfluid;
+pub use swaprouter::Swaprouter;
 pub use tokenfactory::TokenFactory;
+pub use twap::Twap;
 pub use wasm::Wasm;
 
 pub trait Module<'a, R: Runner<'a>> {
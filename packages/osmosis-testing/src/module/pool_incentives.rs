@@ -0,0 +1,114 @@
+use osmosis_std::types::osmosis::poolincentives::v1beta1::{
+    QueryDistrInfoRequest, QueryDistrInfoResponse, QueryExternalIncentiveGaugesRequest,
+    QueryExternalIncentiveGaugesResponse, QueryGaugeIdsRequest, QueryGaugeIdsResponse,
+    QueryIncentivizedPoolsRequest, QueryIncentivizedPoolsResponse, QueryLockableDurationsRequest,
+    QueryLockableDurationsResponse, QueryParamsRequest, QueryParamsResponse,
+};
+
+use crate::fn_query;
+use crate::module::Module;
+use crate::runner::Runner;
+
+pub struct PoolIncentives<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for PoolIncentives<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> PoolIncentives<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_query! {
+        pub query_gauge_ids ["/osmosis.poolincentives.v1beta1.Query/GaugeIds"]: QueryGaugeIdsRequest => QueryGaugeIdsResponse
+    }
+
+    fn_query! {
+        pub query_distr_info ["/osmosis.poolincentives.v1beta1.Query/DistrInfo"]: QueryDistrInfoRequest => QueryDistrInfoResponse
+    }
+
+    fn_query! {
+        pub query_params ["/osmosis.poolincentives.v1beta1.Query/Params"]: QueryParamsRequest => QueryParamsResponse
+    }
+
+    fn_query! {
+        pub query_lockable_durations ["/osmosis.poolincentives.v1beta1.Query/LockableDurations"]: QueryLockableDurationsRequest => QueryLockableDurationsResponse
+    }
+
+    fn_query! {
+        pub query_incentivized_pools ["/osmosis.poolincentives.v1beta1.Query/IncentivizedPools"]: QueryIncentivizedPoolsRequest => QueryIncentivizedPoolsResponse
+    }
+
+    fn_query! {
+        pub query_external_incentive_gauges ["/osmosis.poolincentives.v1beta1.Query/ExternalIncentiveGauges"]: QueryExternalIncentiveGaugesRequest => QueryExternalIncentiveGaugesResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Coin;
+    use osmosis_std::types::osmosis::incentives::GaugeByIdRequest;
+    use osmosis_std::types::osmosis::poolincentives::v1beta1::{
+        QueryGaugeIdsRequest, QueryLockableDurationsRequest,
+    };
+
+    use crate::{Gamm, Incentives, Module, OsmosisTestApp, PoolIncentives};
+
+    #[test]
+    fn pool_incentives_integration() {
+        let app = OsmosisTestApp::new();
+        let signer = app
+            .init_account(&[
+                Coin::new(1_000_000_000_000, "uatom"),
+                Coin::new(1_000_000_000_000, "uosmo"),
+            ])
+            .unwrap();
+        let gamm = Gamm::new(&app);
+        let incentives = Incentives::new(&app);
+        let pool_incentives = PoolIncentives::new(&app);
+
+        let pool_id = gamm
+            .create_basic_pool(
+                &[Coin::new(1_000_000, "uatom"), Coin::new(2_000_000, "uosmo")],
+                &signer,
+            )
+            .unwrap()
+            .data
+            .pool_id;
+
+        // a gauge is created with the pool for every lockable duration
+        let lockable_durations = pool_incentives
+            .query_lockable_durations(&QueryLockableDurationsRequest {})
+            .unwrap()
+            .lockable_durations;
+        let gauge_ids = pool_incentives
+            .query_gauge_ids(&QueryGaugeIdsRequest { pool_id })
+            .unwrap()
+            .gauge_ids_with_duration;
+
+        assert_eq!(gauge_ids.len(), lockable_durations.len());
+
+        for gauge_id in gauge_ids {
+            assert!(lockable_durations.contains(&gauge_id.duration.unwrap()));
+
+            // pool gauges are perpetual and distribute to the pool's shares
+            let gauge = incentives
+                .query_gauge_by_id(&GaugeByIdRequest {
+                    id: gauge_id.gauge_id,
+                })
+                .unwrap()
+                .gauge
+                .unwrap();
+
+            assert!(gauge.is_perpetual);
+            assert_eq!(
+                gauge.distribute_to.unwrap().denom,
+                format!("gamm/pool/{}", pool_id)
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use osmosis_std::types::osmosis::superfluid::{
+    AllAssetsRequest, AllAssetsResponse, AllIntermediaryAccountsRequest,
+    AllIntermediaryAccountsResponse, AssetMultiplierRequest, AssetMultiplierResponse,
+    AssetTypeRequest, AssetTypeResponse, MsgLockAndSuperfluidDelegate,
+    MsgLockAndSuperfluidDelegateResponse, MsgSuperfluidDelegate, MsgSuperfluidDelegateResponse,
+    MsgSuperfluidUnbondLock, MsgSuperfluidUnbondLockResponse, MsgSuperfluidUndelegate,
+    MsgSuperfluidUndelegateResponse, MsgUnPoolWhitelistedPool, MsgUnPoolWhitelistedPoolResponse,
+    QueryParamsRequest, QueryParamsResponse, SuperfluidDelegationsByDelegatorRequest,
+    SuperfluidDelegationsByDelegatorResponse, SuperfluidUndelegationsByDelegatorRequest,
+    SuperfluidUndelegationsByDelegatorResponse, TotalSuperfluidDelegationsRequest,
+    TotalSuperfluidDelegationsResponse,
+};
+
+use crate::module::Module;
+use crate::runner::Runner;
+use crate::{fn_execute, fn_query};
+
+pub struct Superfluid<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for Superfluid<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> Superfluid<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_execute! {
+        pub superfluid_delegate: MsgSuperfluidDelegate => MsgSuperfluidDelegateResponse
+    }
+
+    fn_execute! {
+        pub superfluid_undelegate: MsgSuperfluidUndelegate => MsgSuperfluidUndelegateResponse
+    }
+
+    fn_execute! {
+        pub superfluid_unbond_lock: MsgSuperfluidUnbondLock => MsgSuperfluidUnbondLockResponse
+    }
+
+    fn_execute! {
+        pub lock_and_superfluid_delegate: MsgLockAndSuperfluidDelegate => MsgLockAndSuperfluidDelegateResponse
+    }
+
+    fn_execute! {
+        pub unpool_whitelisted_pool: MsgUnPoolWhitelistedPool => MsgUnPoolWhitelistedPoolResponse
+    }
+
+    fn_query! {
+        pub query_params ["/osmosis.superfluid.Query/Params"]: QueryParamsRequest => QueryParamsResponse
+    }
+
+    fn_query! {
+        pub query_asset_type ["/osmosis.superfluid.Query/AssetType"]: AssetTypeRequest => AssetTypeResponse
+    }
+
+    fn_query! {
+        pub query_all_assets ["/osmosis.superfluid.Query/AllAssets"]: AllAssetsRequest => AllAssetsResponse
+    }
+
+    fn_query! {
+        pub query_asset_multiplier ["/osmosis.superfluid.Query/AssetMultiplier"]: AssetMultiplierRequest => AssetMultiplierResponse
+    }
+
+    fn_query! {
+        pub query_all_intermediary_accounts ["/osmosis.superfluid.Query/AllIntermediaryAccounts"]: AllIntermediaryAccountsRequest => AllIntermediaryAccountsResponse
+    }
+
+    fn_query! {
+        pub query_total_superfluid_delegations ["/osmosis.superfluid.Query/TotalSuperfluidDelegations"]: TotalSuperfluidDelegationsRequest => TotalSuperfluidDelegationsResponse
+    }
+
+    fn_query! {
+        pub query_superfluid_delegations_by_delegator ["/osmosis.superfluid.Query/SuperfluidDelegationsByDelegator"]: SuperfluidDelegationsByDelegatorRequest => SuperfluidDelegationsByDelegatorResponse
+    }
+
+    fn_query! {
+        pub query_superfluid_undelegations_by_delegator ["/osmosis.superfluid.Query/SuperfluidUndelegationsByDelegator"]: SuperfluidUndelegationsByDelegatorRequest => SuperfluidUndelegationsByDelegatorResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use osmosis_std::types::osmosis::superfluid::{
+        AllAssetsRequest, QueryParamsRequest, SuperfluidDelegationsByDelegatorRequest,
+        TotalSuperfluidDelegationsRequest,
+    };
+
+    use crate::{Account, Module, OsmosisTestApp, Superfluid};
+
+    #[test]
+    fn superfluid_integration() {
+        let app = OsmosisTestApp::new();
+        let signer = app.init_account(&[]).unwrap();
+        let superfluid = Superfluid::new(&app);
+
+        // default params
+        let params = superfluid
+            .query_params(&QueryParamsRequest {})
+            .unwrap()
+            .params
+            .unwrap();
+
+        assert_eq!(params.minimum_risk_factor, "0.500000000000000000");
+
+        // assets are only whitelisted through governance, none at genesis
+        let assets = superfluid
+            .query_all_assets(&AllAssetsRequest {})
+            .unwrap()
+            .assets;
+
+        assert!(assets.is_empty());
+
+        let total = superfluid
+            .query_total_superfluid_delegations(&TotalSuperfluidDelegationsRequest {})
+            .unwrap()
+            .total_delegations;
+
+        assert_eq!(total, "0");
+
+        let delegations = superfluid
+            .query_superfluid_delegations_by_delegator(&SuperfluidDelegationsByDelegatorRequest {
+                delegator_address: signer.address(),
+            })
+            .unwrap();
+
+        assert!(delegations.superfluid_delegation_records.is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+use osmosis_std::types::osmosis::swaprouter::v1beta1::{
+    EstimateSwapExactAmountInRequest, EstimateSwapExactAmountInResponse,
+    EstimateSwapExactAmountOutRequest, EstimateSwapExactAmountOutResponse, MsgSwapExactAmountIn,
+    MsgSwapExactAmountInResponse, MsgSwapExactAmountOut, MsgSwapExactAmountOutResponse,
+    NumPoolsRequest, NumPoolsResponse, ParamsRequest, ParamsResponse,
+};
+
+use crate::module::Module;
+use crate::runner::Runner;
+use crate::{fn_execute, fn_query};
+
+pub struct Swaprouter<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for Swaprouter<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> Swaprouter<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_execute! {
+        pub swap_exact_amount_in: MsgSwapExactAmountIn => MsgSwapExactAmountInResponse
+    }
+
+    fn_execute! {
+        pub swap_exact_amount_out: MsgSwapExactAmountOut => MsgSwapExactAmountOutResponse
+    }
+
+    fn_query! {
+        pub query_params ["/osmosis.swaprouter.v1beta1.Query/Params"]: ParamsRequest => ParamsResponse
+    }
+
+    fn_query! {
+        pub query_num_pools ["/osmosis.swaprouter.v1beta1.Query/NumPools"]: NumPoolsRequest => NumPoolsResponse
+    }
+
+    fn_query! {
+        pub query_estimate_swap_exact_amount_in ["/osmosis.swaprouter.v1beta1.Query/EstimateSwapExactAmountIn"]: EstimateSwapExactAmountInRequest => EstimateSwapExactAmountInResponse
+    }
+
+    fn_query! {
+        pub query_estimate_swap_exact_amount_out ["/osmosis.swaprouter.v1beta1.Query/EstimateSwapExactAmountOut"]: EstimateSwapExactAmountOutRequest => EstimateSwapExactAmountOutResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmrs::proto::cosmos::bank::v1beta1::QueryBalanceRequest;
+    use cosmwasm_std::Coin;
+    use osmosis_std::types::osmosis::swaprouter::v1beta1::{
+        EstimateSwapExactAmountInRequest, MsgSwapExactAmountIn, SwapAmountInRoute,
+    };
+
+    use crate::{Account, Bank, Gamm, Module, OsmosisTestApp, Swaprouter};
+
+    #[test]
+    fn swaprouter_integration() {
+        let app = OsmosisTestApp::new();
+        let signer = app
+            .init_account(&[
+                Coin::new(1_000_000_000_000, "uatom"),
+                Coin::new(1_000_000_000_000, "uosmo"),
+            ])
+            .unwrap();
+        let trader = app.init_account(&[Coin::new(10_000, "uatom")]).unwrap();
+        let gamm = Gamm::new(&app);
+        let swaprouter = Swaprouter::new(&app);
+        let bank = Bank::new(&app);
+
+        let pool_id = gamm
+            .create_basic_pool(
+                &[Coin::new(1_000_000, "uatom"), Coin::new(2_000_000, "uosmo")],
+                &signer,
+            )
+            .unwrap()
+            .data
+            .pool_id;
+        let routes = vec![SwapAmountInRoute {
+            pool_id,
+            token_out_denom: "uosmo".to_string(),
+        }];
+
+        // estimate
+        let estimated = swaprouter
+            .query_estimate_swap_exact_amount_in(&EstimateSwapExactAmountInRequest {
+                sender: trader.address(),
+                pool_id,
+                token_in: "10000uatom".to_string(),
+                routes: routes.clone(),
+            })
+            .unwrap()
+            .token_out_amount;
+
+        // swap
+        let token_out_amount = swaprouter
+            .swap_exact_amount_in(
+                MsgSwapExactAmountIn {
+                    sender: trader.address(),
+                    routes,
+                    token_in: Some(Coin::new(10_000, "uatom").into()),
+                    token_out_min_amount: "1".to_string(),
+                },
+                &trader,
+            )
+            .unwrap()
+            .data
+            .token_out_amount;
+
+        assert_eq!(token_out_amount, estimated);
+
+        let balance = bank
+            .query_balance(&QueryBalanceRequest {
+                address: trader.address(),
+                denom: "uosmo".to_string(),
+            })
+            .unwrap()
+            .balance
+            .unwrap();
+
+        assert_eq!(balance.amount, token_out_amount);
+    }
+}
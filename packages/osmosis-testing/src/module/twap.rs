@@ -0,0 +1,115 @@
+use osmosis_std::types::osmosis::twap::v1beta1::{
+    ArithmeticTwapRequest, ArithmeticTwapResponse, ArithmeticTwapToNowRequest,
+    ArithmeticTwapToNowResponse, ParamsRequest, ParamsResponse,
+};
+
+use crate::fn_query;
+use crate::module::Module;
+use crate::runner::Runner;
+
+pub struct Twap<'a, R: Runner<'a>> {
+    runner: &'a R,
+}
+
+impl<'a, R: Runner<'a>> Module<'a, R> for Twap<'a, R> {
+    fn new(runner: &'a R) -> Self {
+        Self { runner }
+    }
+}
+
+impl<'a, R> Twap<'a, R>
+where
+    R: Runner<'a>,
+{
+    fn_query! {
+        pub query_params ["/osmosis.twap.v1beta1.Query/Params"]: ParamsRequest => ParamsResponse
+    }
+
+    fn_query! {
+        pub query_arithmetic_twap ["/osmosis.twap.v1beta1.Query/ArithmeticTwap"]: ArithmeticTwapRequest => ArithmeticTwapResponse
+    }
+
+    fn_query! {
+        pub query_arithmetic_twap_to_now ["/osmosis.twap.v1beta1.Query/ArithmeticTwapToNow"]: ArithmeticTwapToNowRequest => ArithmeticTwapToNowResponse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Coin;
+    use osmosis_std::shim::Timestamp;
+    use osmosis_std::types::osmosis::twap::v1beta1::{
+        ArithmeticTwapRequest, ArithmeticTwapToNowRequest, ParamsRequest,
+    };
+
+    use crate::{Gamm, Module, OsmosisTestApp, Twap};
+
+    #[test]
+    fn twap_integration() {
+        let app = OsmosisTestApp::new();
+        let signer = app
+            .init_account(&[
+                Coin::new(1_000_000_000_000, "uatom"),
+                Coin::new(1_000_000_000_000, "uosmo"),
+            ])
+            .unwrap();
+        let gamm = Gamm::new(&app);
+        let twap = Twap::new(&app);
+
+        let params = twap
+            .query_params(&ParamsRequest {})
+            .unwrap()
+            .params
+            .unwrap();
+
+        assert_eq!(params.prune_epoch_identifier, "day");
+
+        let pool_id = gamm
+            .create_basic_pool(
+                &[Coin::new(1_000_000, "uatom"), Coin::new(2_000_000, "uosmo")],
+                &signer,
+            )
+            .unwrap()
+            .data
+            .pool_id;
+        let created_at = app.get_block_time();
+
+        app.increase_time(3600);
+
+        // reserves have not moved since the pool was created
+        let arithmetic_twap = twap
+            .query_arithmetic_twap_to_now(&ArithmeticTwapToNowRequest {
+                pool_id,
+                base_asset: "uatom".to_string(),
+                quote_asset: "uosmo".to_string(),
+                start_time: Some(Timestamp {
+                    seconds: created_at.seconds() as i64,
+                    nanos: created_at.subsec_nanos() as i32,
+                }),
+            })
+            .unwrap()
+            .arithmetic_twap;
+
+        assert_eq!(arithmetic_twap, "2.000000000000000000");
+
+        let end_at = created_at.plus_seconds(1800);
+        let arithmetic_twap = twap
+            .query_arithmetic_twap(&ArithmeticTwapRequest {
+                pool_id,
+                base_asset: "uosmo".to_string(),
+                quote_asset: "uatom".to_string(),
+                start_time: Some(Timestamp {
+                    seconds: created_at.seconds() as i64,
+                    nanos: created_at.subsec_nanos() as i32,
+                }),
+                end_time: Some(Timestamp {
+                    seconds: end_at.seconds() as i64,
+                    nanos: end_at.subsec_nanos() as i32,
+                }),
+            })
+            .unwrap()
+            .arithmetic_twap;
+
+        assert_eq!(arithmetic_twap, "0.500000000000000000");
+    }
+}
@@ -4,13 +4,14 @@ use cosmrs::crypto::secp256k1::SigningKey;
 use cosmrs::proto::tendermint::abci::{RequestDeliverTx, ResponseDeliverTx};
 use cosmrs::tx;
 use cosmrs::tx::{Fee, SignerInfo};
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Coin, Timestamp};
 use prost::Message;
 
 use crate::account::{Account, FeeSetting, SigningAccount};
 use crate::bindings::{
-    AccountNumber, AccountSequence, BeginBlock, EndBlock, Execute, InitAccount, InitTestEnv, Query,
-    Simulate,
+    AccountNumber, AccountSequence, AdvanceEpoch, BeginBlock, EndBlock, Execute, GetBlockHeight,
+    GetBlockTime, IncreaseTime, InitAccount, InitTestEnv, InitTestEnvFromSnapshot, Query,
+    ReleaseSnapshot, Restore, Simulate, Snapshot as TakeSnapshot,
 };
 use crate::redefine_as_go_string;
 use crate::runner::error::{DecodeError, EncodeError, RunnerError};
@@ -33,6 +34,19 @@ impl Default for OsmosisTestApp {
     }
 }
 
+/// Copy of an `OsmosisTestApp`'s state, taken with [`OsmosisTestApp::snapshot`].
+/// Expensive setup can be done once, then restored or started from as many times as needed
+#[derive(Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    id: u64,
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        unsafe { ReleaseSnapshot(self.id) }
+    }
+}
+
 impl OsmosisTestApp {
     pub fn new() -> Self {
         Self {
@@ -40,6 +54,30 @@ impl OsmosisTestApp {
         }
     }
 
+    /// Create a new app starting from the snapshot's state, independent of the app it was taken from
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        Self {
+            id: unsafe { InitTestEnvFromSnapshot(snapshot.id) },
+        }
+    }
+
+    /// Copy the current state of the chain
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            id: unsafe { TakeSnapshot(self.id) },
+        }
+    }
+
+    /// Reset the chain to the snapshot's state, the snapshot can be restored again
+    pub fn restore(&self, snapshot: &Snapshot) {
+        unsafe { Restore(self.id, snapshot.id) }
+    }
+
+    /// Create a new app starting from the current state, changes to either app do not affect the other
+    pub fn fork(&self) -> Self {
+        Self::from_snapshot(&self.snapshot())
+    }
+
     /// Initialize account with initial balance of any coins.
     /// This function mints new coins and send to newly created account
     pub fn init_account(&self, coins: &[Coin]) -> RunnerResult<SigningAccount> {
@@ -84,6 +122,45 @@ impl OsmosisTestApp {
             .collect()
     }
 
+    /// Get the current block time
+    pub fn get_block_time(&self) -> Timestamp {
+        let nanos = unsafe { GetBlockTime(self.id) };
+        Timestamp::from_nanos(nanos as u64)
+    }
+
+    /// Get the current block height
+    pub fn get_block_height(&self) -> i64 {
+        unsafe { GetBlockHeight(self.id) }
+    }
+
+    /// Move block time forward by `seconds`, in a single new block
+    pub fn increase_time(&self, seconds: u64) {
+        unsafe { IncreaseTime(self.id, seconds as i64) }
+    }
+
+    /// Run `blocks` empty blocks, each 5 seconds after the previous one
+    pub fn skip_blocks(&self, blocks: u64) {
+        for _ in 0..blocks {
+            unsafe {
+                BeginBlock(self.id);
+                EndBlock(self.id);
+            }
+        }
+    }
+
+    /// Move block time past the end of the current `epoch_identifier` epoch ("day", "week", ..)
+    /// so the next epoch starts and its hooks run, in a single new block
+    pub fn advance_epoch(&self, epoch_identifier: &str) -> RunnerResult<()> {
+        redefine_as_go_string!(epoch_identifier);
+
+        unsafe {
+            let res = AdvanceEpoch(self.id, epoch_identifier);
+            RawResult::from_non_null_ptr(res).into_result()?;
+        }
+
+        Ok(())
+    }
+
     fn create_signed_tx<I>(
         &self,
         msgs: I,
@@ -283,8 +360,12 @@ mod tests {
     use cosmrs::proto::cosmos::bank::v1beta1::QueryAllBalancesRequest;
     use cosmwasm_std::{attr, coins, Coin};
 
+    use osmosis_std::types::osmosis::epochs::v1beta1::{
+        QueryCurrentEpochRequest, QueryCurrentEpochResponse,
+    };
     use osmosis_std::types::osmosis::tokenfactory::v1beta1::{
-        MsgCreateDenom, MsgCreateDenomResponse, QueryParamsRequest, QueryParamsResponse,
+        MsgCreateDenom, MsgCreateDenomResponse, QueryDenomsFromCreatorRequest, QueryParamsRequest,
+        QueryParamsResponse,
     };
 
     use crate::account::{Account, FeeSetting};
@@ -293,7 +374,7 @@ mod tests {
     use crate::module::Wasm;
     use crate::runner::app::OsmosisTestApp;
     use crate::runner::*;
-    use crate::{Bank, ExecuteResponse};
+    use crate::{Bank, ExecuteResponse, RunnerError, TokenFactory};
 
     #[test]
     fn test_init_accounts() {
@@ -387,6 +468,145 @@ mod tests {
         assert_eq!(denom_creation_fee, [Coin::new(10000000, "uosmo").into()])
     }
 
+    #[test]
+    fn test_increase_time_and_skip_blocks() {
+        let app = OsmosisTestApp::default();
+        let time = app.get_block_time();
+        let height = app.get_block_height();
+
+        app.increase_time(3600);
+
+        assert_eq!(app.get_block_time(), time.plus_seconds(3600));
+        assert_eq!(app.get_block_height(), height + 1);
+
+        app.skip_blocks(10);
+
+        assert_eq!(app.get_block_time(), time.plus_seconds(3600 + 50));
+        assert_eq!(app.get_block_height(), height + 11);
+    }
+
+    #[test]
+    fn test_advance_epoch() {
+        let app = OsmosisTestApp::default();
+        let current_epoch = |identifier: &str| {
+            app.query::<QueryCurrentEpochRequest, QueryCurrentEpochResponse>(
+                "/osmosis.epochs.v1beta1.Query/CurrentEpoch",
+                &QueryCurrentEpochRequest {
+                    identifier: identifier.to_string(),
+                },
+            )
+            .unwrap()
+            .current_epoch
+        };
+        let day = current_epoch("day");
+        let week = current_epoch("week");
+
+        app.advance_epoch("day").unwrap();
+
+        assert_eq!(current_epoch("day"), day + 1);
+        assert_eq!(current_epoch("week"), week);
+
+        let err = app.advance_epoch("fortnight").unwrap_err();
+
+        assert_eq!(
+            err,
+            RunnerError::ExecuteError {
+                msg: "No epoch found for `fortnight`".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let app = OsmosisTestApp::default();
+        let acc = app.init_account(&coins(100_000_000_000, "uosmo")).unwrap();
+        let tokenfactory = TokenFactory::new(&app);
+        let denoms = |app: &OsmosisTestApp| {
+            TokenFactory::new(app)
+                .query_denoms_from_creator(&QueryDenomsFromCreatorRequest {
+                    creator: acc.address(),
+                })
+                .unwrap()
+                .denoms
+        };
+
+        let snapshot = app.snapshot();
+        let time = app.get_block_time();
+        let height = app.get_block_height();
+
+        let create_denom = |subdenom: &str| {
+            tokenfactory
+                .create_denom(
+                    MsgCreateDenom {
+                        sender: acc.address(),
+                        subdenom: subdenom.to_string(),
+                    },
+                    &acc,
+                )
+                .unwrap()
+                .data
+                .new_token_denom
+        };
+        let denom = create_denom("newdenom");
+        assert_eq!(denoms(&app), [denom]);
+
+        // restore, account sequence included
+        app.restore(&snapshot);
+
+        assert!(denoms(&app).is_empty());
+        assert_eq!(app.get_block_time(), time);
+        assert_eq!(app.get_block_height(), height);
+
+        let denom = create_denom("newerdenom");
+        assert_eq!(denoms(&app), [denom]);
+
+        // snapshot can be restored again
+        app.restore(&snapshot);
+
+        assert!(denoms(&app).is_empty());
+    }
+
+    #[test]
+    fn test_fork() {
+        let app = OsmosisTestApp::default();
+        let acc = app.init_account(&coins(100_000_000_000, "uosmo")).unwrap();
+        let create_denom = |app: &OsmosisTestApp, subdenom: &str| {
+            TokenFactory::new(app)
+                .create_denom(
+                    MsgCreateDenom {
+                        sender: acc.address(),
+                        subdenom: subdenom.to_string(),
+                    },
+                    &acc,
+                )
+                .unwrap()
+                .data
+                .new_token_denom
+        };
+        let denoms = |app: &OsmosisTestApp| {
+            TokenFactory::new(app)
+                .query_denoms_from_creator(&QueryDenomsFromCreatorRequest {
+                    creator: acc.address(),
+                })
+                .unwrap()
+                .denoms
+        };
+        let denom = create_denom(&app, "newdenom");
+
+        let fork = app.fork();
+
+        assert_ne!(fork, app);
+        assert_eq!(denoms(&fork), vec![denom.clone()]);
+        assert_eq!(fork.get_block_height(), app.get_block_height());
+
+        // apps move on independently
+        let forked_denom = create_denom(&fork, "forkeddenom");
+        let newer_denom = create_denom(&app, "newerdenom");
+
+        assert_eq!(denoms(&fork), [forked_denom, denom.clone()]);
+        assert_eq!(denoms(&app), [denom, newer_denom]);
+    }
+
     #[test]
     fn test_multiple_as_module() {
         let app = OsmosisTestApp::default();